@@ -0,0 +1,84 @@
+//! Verifying proofs of the same circuit one by one against `batch::verify_batch`.
+//!
+//! cargo bench --bench batch
+
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion};
+use fibonacci::{
+    batch::{verify_batch, verify_sequential, Statement},
+    dev_tools::prover::{prove, setup},
+    mimc, range_check,
+};
+use halo2_proofs::{
+    circuit::Value,
+    pasta::{EqAffine, Fp},
+    plonk::Circuit,
+};
+use rand::rngs::OsRng;
+
+const COUNTS: [usize; 3] = [1, 8, 32];
+
+/// Proves every `(circuit, instance)` and benchmarks verifying the first `COUNTS` of them.
+fn bench_circuit<C: Circuit<Fp>>(c: &mut Criterion, name: &str, witnesses: Vec<(C, Vec<Vec<Fp>>)>) {
+    let (params, pk) = setup::<EqAffine, _>(&witnesses[0].0);
+
+    let statements: Vec<_> = witnesses
+        .into_iter()
+        .map(|(circuit, instance)| {
+            let columns: Vec<_> = instance.iter().map(|column| column.as_slice()).collect();
+            Statement {
+                proof: prove(&params, &pk, &circuit, &columns, OsRng).unwrap(),
+                instance,
+            }
+        })
+        .collect();
+
+    let mut group = c.benchmark_group(name);
+    group.sample_size(10);
+    for count in COUNTS {
+        let statements = &statements[..count];
+        group.bench_with_input(
+            BenchmarkId::new("sequential", count),
+            statements,
+            |b, statements| b.iter(|| verify_sequential(&params, pk.get_vk(), statements).unwrap()),
+        );
+        group.bench_with_input(
+            BenchmarkId::new("batch", count),
+            statements,
+            |b, statements| b.iter(|| verify_batch(&params, pk.get_vk(), statements).unwrap()),
+        );
+    }
+    group.finish();
+}
+
+fn bench_range_check(c: &mut Criterion) {
+    const RANGE: usize = 8;
+    let witnesses = (0..COUNTS[2])
+        .map(|i| {
+            let circuit = range_check::ex1::MyCircuit::<Fp, RANGE> {
+                value: Value::known(Fp::from((i % RANGE) as u64).into()),
+            };
+            (circuit, vec![])
+        })
+        .collect();
+    bench_circuit(c, "verify range_check::ex1 (RANGE = 8)", witnesses);
+}
+
+fn bench_mimc(c: &mut Criterion) {
+    let constants = mimc::native::round_constants();
+    let witnesses = (0..COUNTS[2] as u64)
+        .map(|i| {
+            let message = [Fp::from(i), Fp::from(i + 1)];
+            let circuit = mimc::MyCircuit::<Fp, 2> {
+                message: message.iter().map(|m| Value::known(*m)).collect(),
+            };
+            (
+                circuit,
+                vec![vec![mimc::native::hash(&message, &constants)]],
+            )
+        })
+        .collect();
+    bench_circuit(c, "verify mimc (2 elements)", witnesses);
+}
+
+criterion_group!(benches, bench_range_check, bench_mimc);
+criterion_main!(benches);
@@ -0,0 +1,275 @@
+//! `keygen_vk`, `keygen_pk`, `create_proof` and `verify_proof` for every circuit in the
+//! crate, at its smallest `k` and, for the cheaper ones, at a few larger ones: the rows
+//! past the last one used still cost FFTs and MSMs.
+//!
+//! cargo bench --bench proving                    # all circuits
+//! cargo bench --bench proving -- range_check     # only the range checks
+
+use criterion::{criterion_group, criterion_main, BatchSize, BenchmarkId, Criterion};
+use ff::{Field, PrimeField};
+use fibonacci::{
+    bitwise, bn254, decompose_range_check,
+    dev_tools::{min_k::min_k, prover::prove},
+    ecc, merkle, mimc, poseidon, range_check, schnorr, sha256,
+};
+use group::{prime::PrimeCurveAffine, Curve};
+use halo2_proofs::{
+    circuit::Value,
+    pasta::{pallas, EqAffine, Fp},
+    plonk::{keygen_pk, keygen_vk, verify_proof, Assigned, Circuit, SingleVerifier},
+    poly::commitment::Params,
+    transcript::{Blake2bRead, Challenge255},
+};
+use num_bigint::BigUint;
+use rand::rngs::OsRng;
+
+/// Benchmarks the four steps for `circuit` at `min_k + extra` for each of `extra_k`.
+fn bench_circuit<C: Circuit<Fp>>(
+    c: &mut Criterion,
+    name: &str,
+    extra_k: &[u32],
+    circuit: C,
+    instance: Vec<Vec<Fp>>,
+) {
+    let empty = circuit.without_witnesses();
+    let instance: Vec<_> = instance.iter().map(|column| column.as_slice()).collect();
+    let min = min_k(&circuit);
+
+    let mut group = c.benchmark_group(name);
+    group.sample_size(10);
+    for k in extra_k.iter().map(|extra| min + extra) {
+        let params = Params::<EqAffine>::new(k);
+        group.bench_function(BenchmarkId::new("keygen_vk", k), |b| {
+            b.iter(|| keygen_vk(&params, &empty).unwrap())
+        });
+
+        let vk = keygen_vk(&params, &empty).unwrap();
+        group.bench_function(BenchmarkId::new("keygen_pk", k), |b| {
+            b.iter_batched(
+                || vk.clone(),
+                |vk| keygen_pk(&params, vk, &empty).unwrap(),
+                BatchSize::SmallInput,
+            )
+        });
+
+        let pk = keygen_pk(&params, vk, &empty).unwrap();
+        group.bench_function(BenchmarkId::new("create_proof", k), |b| {
+            b.iter(|| prove(&params, &pk, &circuit, &instance, OsRng).unwrap())
+        });
+
+        let proof = prove(&params, &pk, &circuit, &instance, OsRng).unwrap();
+        group.bench_function(BenchmarkId::new("verify_proof", k), |b| {
+            b.iter(|| {
+                let mut transcript = Blake2bRead::<_, _, Challenge255<_>>::init(proof.as_slice());
+                let strategy = SingleVerifier::new(&params);
+                verify_proof(
+                    &params,
+                    pk.get_vk(),
+                    strategy,
+                    &[&instance[..]],
+                    &mut transcript,
+                )
+                .unwrap()
+            })
+        });
+    }
+    group.finish();
+}
+
+fn bench_range_check(c: &mut Criterion) {
+    let value = |v: u64| -> Value<Assigned<Fp>> { Value::known(Fp::from(v).into()) };
+
+    let circuit = range_check::ex1::MyCircuit::<Fp, 8> { value: value(3) };
+    bench_circuit(
+        c,
+        "range_check::ex1 (RANGE = 8)",
+        &[0, 2, 4],
+        circuit,
+        vec![],
+    );
+
+    // the table takes LOOKUP_RANGE rows, so it decides k
+    let circuit = range_check::ex2::MyCircuit::<Fp, 8, 256> {
+        value: value(3),
+        large_value: value(200),
+    };
+    bench_circuit(
+        c,
+        "range_check::ex2 (LOOKUP_RANGE = 256)",
+        &[0, 2],
+        circuit,
+        vec![],
+    );
+    let circuit = range_check::ex2::MyCircuit::<Fp, 8, 65536> {
+        value: value(3),
+        large_value: value(60000),
+    };
+    bench_circuit(
+        c,
+        "range_check::ex2 (LOOKUP_RANGE = 65536)",
+        &[0],
+        circuit,
+        vec![],
+    );
+
+    let circuit = range_check::ex3::MyCircuit::<Fp, 8, 256> {
+        num_bits: Value::known(8),
+        value: value(200),
+    };
+    bench_circuit(
+        c,
+        "range_check::ex3 (NUM_BITS = 8, RANGE = 256)",
+        &[0, 2],
+        circuit,
+        vec![],
+    );
+
+    // 8-bit chunks take eight rows for 64 bits, 16-bit chunks four and a larger table
+    let random = value(rand::random());
+    let circuit = decompose_range_check::ex1::MyCircuit::<Fp, 256> {
+        value: random,
+        num_bits: 64,
+    };
+    bench_circuit(
+        c,
+        "decompose_range_check::ex1 (64 bits, LOOKUP_RANGE = 256)",
+        &[0],
+        circuit,
+        vec![],
+    );
+    let circuit = decompose_range_check::ex1::MyCircuit::<Fp, 65536> {
+        value: random,
+        num_bits: 64,
+    };
+    bench_circuit(
+        c,
+        "decompose_range_check::ex1 (64 bits, LOOKUP_RANGE = 65536)",
+        &[0],
+        circuit,
+        vec![],
+    );
+}
+
+fn bench_bitwise(c: &mut Criterion) {
+    let (a, b) = (rand::random(), rand::random());
+    let circuit = bitwise::MyCircuit::<Fp, 64>::new(bitwise::BitOp::Xor, a, b);
+    let instance = vec![vec![Fp::from(bitwise::BitOp::Xor.apply(a, b))]];
+    bench_circuit(c, "bitwise (64-bit XOR)", &[0], circuit, instance);
+}
+
+fn bench_fq(c: &mut Criterion) {
+    let p = bn254::fq::modulus();
+    let random = || BigUint::from_bytes_le(&rand::random::<[u8; 32]>()) % &p;
+    let (a, b) = (random(), random());
+    let out = (&a + &b) * &b % &p;
+    let instance = bn254::fq::MyCircuit::<Fp>::instance(&out);
+    let circuit = bn254::fq::MyCircuit::<Fp>::new(a, b, out);
+    bench_circuit(
+        c,
+        "bn254::fq (non-native (a + b) * b = c)",
+        &[0],
+        circuit,
+        instance,
+    );
+}
+
+fn bench_ecc(c: &mut Criterion) {
+    let secret_key = pallas::Scalar::random(OsRng);
+    let public_key = (pallas::Affine::generator() * secret_key).to_affine();
+    let circuit = ecc::MyCircuit::<pallas::Affine> {
+        secret_key: Value::known(secret_key),
+    };
+    let instance = ecc::MyCircuit::instance(public_key);
+    bench_circuit(
+        c,
+        "ecc (fixed-base scalar mul on Pallas)",
+        &[0],
+        circuit,
+        instance,
+    );
+
+    let point = (pallas::Affine::generator() * pallas::Scalar::random(OsRng)).to_affine();
+    let scalar = Fp::random(OsRng);
+    // below p, and p < q, so this is the same integer as a scalar
+    let result = (point * pallas::Scalar::from_repr(scalar.to_repr()).unwrap()).to_affine();
+    let instance = ecc::windowed::MyCircuit::instance(point, result);
+    for (name, method) in [
+        (
+            "ecc::windowed (variable-base mul, double-and-add)",
+            ecc::windowed::Method::DoubleAndAdd,
+        ),
+        (
+            "ecc::windowed (variable-base mul, 2-bit windows)",
+            ecc::windowed::Method::Windowed,
+        ),
+    ] {
+        let circuit = ecc::windowed::MyCircuit::new(method, point, scalar);
+        bench_circuit(c, name, &[0], circuit, instance.clone());
+    }
+}
+
+fn bench_hashes(c: &mut Criterion) {
+    let message = [Fp::from(1), Fp::from(2)];
+    let known: Vec<_> = message.iter().map(|m| Value::known(*m)).collect();
+
+    let spec = poseidon::native::Spec::default();
+    let circuit = poseidon::MyCircuit::<Fp, 2> {
+        message: known.clone(),
+    };
+    let instance = vec![vec![poseidon::native::hash(&spec, &message)]];
+    bench_circuit(
+        c,
+        "poseidon (2-element message)",
+        &[0, 2],
+        circuit,
+        instance,
+    );
+
+    let circuit = mimc::MyCircuit::<Fp, 2> { message: known };
+    let instance = vec![vec![mimc::native::hash(
+        &message,
+        &mimc::native::round_constants(),
+    )]];
+    bench_circuit(c, "mimc (2-element message)", &[0, 2], circuit, instance);
+
+    let leaves: Vec<_> = (0..16).map(|i| Fp::from(1000 + i)).collect();
+    let tree = merkle::native::MerkleTree::new(&spec, 8, &leaves);
+    let circuit = merkle::MyCircuit::<Fp, 8>::new(tree.leaf(5), &tree.path(5));
+    bench_circuit(
+        c,
+        "merkle (depth 8)",
+        &[0],
+        circuit,
+        vec![vec![tree.root()]],
+    );
+
+    let circuit = sha256::MyCircuit::<Fp, 1>::new(b"abc");
+    let instance = sha256::MyCircuit::<Fp, 1>::instance(b"abc");
+    bench_circuit(c, "sha256 (1 block)", &[0], circuit, instance);
+}
+
+fn bench_schnorr(c: &mut Criterion) {
+    let message = Fp::from(42);
+    let (secret_key, public_key) = schnorr::native::keygen(OsRng);
+    let signature = schnorr::native::sign(secret_key, message, OsRng);
+    let circuit = schnorr::MyCircuit::new(public_key, message, signature);
+    let instance = schnorr::MyCircuit::instance(public_key, message);
+    bench_circuit(
+        c,
+        "schnorr (Pallas, Poseidon challenge)",
+        &[0],
+        circuit,
+        instance,
+    );
+}
+
+criterion_group!(
+    benches,
+    bench_range_check,
+    bench_bitwise,
+    bench_fq,
+    bench_ecc,
+    bench_hashes,
+    bench_schnorr
+);
+criterion_main!(benches);
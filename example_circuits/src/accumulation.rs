@@ -0,0 +1,228 @@
+//! The deferred half of halo2's IPA check, accumulated across proofs.
+//!
+//! Every step of verifying a halo2 proof is cheap except one: the opening at the end
+//! leaves the verifier with the prover's final point `G` and the round challenges `u_j`,
+//! and `G` has to be `<s, g>` over the `n` generators of the params, with
+//!
+//!     s(X) = prod_i (1 + u_{k-1-i} X^{2^i})
+//!
+//! That MSM is linear in `n`. Halo doesn't do it: `(G, u)` is kept as an accumulator and
+//! carried forward, and any number of accumulators are checked at the end with a single
+//! MSM, for random `r_i`:
+//!
+//!     sum_i r_i G_i = < sum_i r_i s_i, g >
+//!
+//! In recursion the cheap part runs inside the next proof's circuit over the other curve
+//! of the cycle, so only the last accumulator is ever decided. Here everything runs
+//! natively: [`accumulate`] is the cheap part, [`decide`] the MSM.
+
+use halo2_proofs::{
+    arithmetic::{best_multiexp, CurveAffine, Field},
+    plonk::{verify_proof, Error, VerificationStrategy, VerifyingKey},
+    poly::commitment::{Guard, Params, MSM},
+    transcript::{Blake2bRead, Challenge255, EncodedChallenge},
+};
+use rand::RngCore;
+
+/// A proof's deferred check: its claimed `G` and the challenges of the IPA rounds.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Accumulator<C: CurveAffine> {
+    pub g: C,
+    pub challenges: Vec<C::Scalar>,
+}
+
+impl<C: CurveAffine> Accumulator<C> {
+    /// The coefficients of `s(X)`, the same way halo2 computes them.
+    fn s(&self) -> Vec<C::Scalar> {
+        let mut s = vec![C::Scalar::zero(); 1 << self.challenges.len()];
+        s[0] = C::Scalar::one();
+        for (i, u) in self.challenges.iter().rev().enumerate() {
+            let (left, right) = s.split_at_mut(1 << i);
+            for (right, left) in right.iter_mut().zip(left.iter()) {
+                *right = *left * u;
+            }
+        }
+        s
+    }
+}
+
+/// Computes `G` for `proof`, the linear-time part. In Halo it's the prover who computes
+/// it and sends it along with the proof; this stands in for that.
+pub fn compute_g<C: CurveAffine>(
+    params: &Params<C>,
+    vk: &VerifyingKey<C>,
+    instance: &[&[C::Scalar]],
+    proof: &[u8],
+) -> Result<C, Error> {
+    let mut transcript = Blake2bRead::<_, _, Challenge255<_>>::init(proof);
+    let strategy = ComputeG {
+        msm: MSM::new(params),
+    };
+    verify_proof(params, vk, strategy, &[instance], &mut transcript)
+}
+
+/// Verifies everything in `proof` but `G = <s, g>`, taking the prover's word for `g`, and
+/// returns the check that is left.
+pub fn accumulate<C: CurveAffine>(
+    params: &Params<C>,
+    vk: &VerifyingKey<C>,
+    instance: &[&[C::Scalar]],
+    proof: &[u8],
+    g: C,
+) -> Result<Accumulator<C>, Error> {
+    let mut transcript = Blake2bRead::<_, _, Challenge255<_>>::init(proof);
+    let strategy = UseG {
+        msm: MSM::new(params),
+        g,
+    };
+    verify_proof(params, vk, strategy, &[instance], &mut transcript)
+}
+
+/// The deferred checks of all `accumulators`, with one MSM over the generators.
+pub fn decide<C: CurveAffine>(
+    params: &Params<C>,
+    accumulators: &[Accumulator<C>],
+    mut rng: impl RngCore,
+) -> bool {
+    let generators = params.get_g();
+    if accumulators
+        .iter()
+        .any(|accumulator| 1 << accumulator.challenges.len() != generators.len())
+    {
+        return false;
+    }
+
+    let r: Vec<_> = accumulators
+        .iter()
+        .map(|_| C::Scalar::random(&mut rng))
+        .collect();
+    let mut s = vec![C::Scalar::zero(); generators.len()];
+    for (r, accumulator) in r.iter().zip(accumulators) {
+        for (s, s_i) in s.iter_mut().zip(accumulator.s()) {
+            *s += *r * s_i;
+        }
+    }
+
+    let g: Vec<_> = accumulators
+        .iter()
+        .map(|accumulator| accumulator.g)
+        .collect();
+    best_multiexp(&r, &g) == best_multiexp(&s, &generators)
+}
+
+/// Stops the verifier right before its last check and computes `G` instead.
+struct ComputeG<'params, C: CurveAffine> {
+    msm: MSM<'params, C>,
+}
+
+impl<'params, C: CurveAffine> VerificationStrategy<'params, C> for ComputeG<'params, C> {
+    type Output = C;
+
+    fn process<E: EncodedChallenge<C>>(
+        self,
+        f: impl FnOnce(MSM<'params, C>) -> Result<Guard<'params, C, E>, Error>,
+    ) -> Result<Self::Output, Error> {
+        Ok(f(self.msm)?.compute_g())
+    }
+}
+
+/// Does the verifier's last check with the claimed `G`, which leaves out the
+/// generators and with them everything linear in `n`.
+struct UseG<'params, C: CurveAffine> {
+    msm: MSM<'params, C>,
+    g: C,
+}
+
+impl<'params, C: CurveAffine> VerificationStrategy<'params, C> for UseG<'params, C> {
+    type Output = Accumulator<C>;
+
+    fn process<E: EncodedChallenge<C>>(
+        self,
+        f: impl FnOnce(MSM<'params, C>) -> Result<Guard<'params, C, E>, Error>,
+    ) -> Result<Self::Output, Error> {
+        let (msm, accumulator) = f(self.msm)?.use_g(self.g);
+        if !msm.eval() {
+            return Err(Error::ConstraintSystemFailure);
+        }
+        Ok(Accumulator {
+            g: accumulator.g,
+            challenges: accumulator
+                .u_packed
+                .iter()
+                .map(|u| u.get_scalar())
+                .collect(),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use halo2_proofs::{
+        circuit::Value,
+        pasta::{EqAffine, Fp},
+        plonk::ProvingKey,
+    };
+    use rand::rngs::OsRng;
+
+    use super::*;
+    use crate::{
+        dev_tools::prover::{self, setup},
+        mimc::{native, MyCircuit},
+    };
+
+    /// A proof of the MiMC hash of `[m, m + 1]`, and the digest.
+    fn prove(params: &Params<EqAffine>, pk: &ProvingKey<EqAffine>, m: u64) -> (Vec<u8>, Fp) {
+        let message = [Fp::from(m), Fp::from(m + 1)];
+        let digest = native::hash(&message, &native::round_constants());
+        let circuit = MyCircuit::<Fp, 2> {
+            message: message.iter().map(|m| Value::known(*m)).collect(),
+        };
+        let proof = prover::prove(params, pk, &circuit, &[&[digest]], OsRng).unwrap();
+        (proof, digest)
+    }
+
+    fn accumulators(
+        params: &Params<EqAffine>,
+        pk: &ProvingKey<EqAffine>,
+        count: u64,
+    ) -> Vec<Accumulator<EqAffine>> {
+        (0..count)
+            .map(|m| {
+                let (proof, digest) = prove(params, pk, m);
+                let g = compute_g(params, pk.get_vk(), &[&[digest]], &proof).unwrap();
+                accumulate(params, pk.get_vk(), &[&[digest]], &proof, g).unwrap()
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_decide() {
+        let (params, pk) = setup(&MyCircuit::<Fp, 2>::default());
+        let accumulators = accumulators(&params, &pk, 3);
+        assert!(decide(&params, &accumulators, OsRng));
+        assert!(decide(&params, &accumulators[..1], OsRng));
+
+        // G of one proof doesn't fit the challenges of another
+        let mut swapped = accumulators.clone();
+        swapped[0].g = accumulators[1].g;
+        assert!(!decide(&params, &swapped, OsRng));
+
+        let mut truncated = accumulators;
+        truncated[2].challenges.pop();
+        assert!(!decide(&params, &truncated, OsRng));
+    }
+
+    #[test]
+    fn test_wrong_g() {
+        // the last check still involves G, so a wrong one fails right away; only whether it
+        // is <s, g> is left for later
+        let (params, pk) = setup(&MyCircuit::<Fp, 2>::default());
+        let (proof, digest) = prove(&params, &pk, 0);
+        let (other, other_digest) = prove(&params, &pk, 1);
+        let g = compute_g(&params, pk.get_vk(), &[&[other_digest]], &other).unwrap();
+        assert!(accumulate(&params, pk.get_vk(), &[&[digest]], &proof, g).is_err());
+
+        let g = compute_g(&params, pk.get_vk(), &[&[digest]], &proof).unwrap();
+        assert!(accumulate(&params, pk.get_vk(), &[&[digest + Fp::one()]], &proof, g).is_err());
+    }
+}
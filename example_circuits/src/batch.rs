@@ -0,0 +1,157 @@
+//! Verifying many proofs of the same circuit together.
+//!
+//! Most of a halo2 verification is the final MSM over the `n` generators of the params.
+//! `BatchVerifier` scales the MSM of every proof by a random factor and adds them up, so
+//! the whole batch pays for one, and it verifies the transcripts in parallel. The price
+//! is that a failed batch doesn't say which proof failed, so [`verify_batch`] splits it
+//! in halves and verifies those, down to the failing proofs.
+
+use halo2_proofs::{
+    arithmetic::CurveAffine,
+    plonk::{verify_proof, BatchVerifier, SingleVerifier, VerifyingKey},
+    poly::commitment::Params,
+    transcript::{Blake2bRead, Challenge255},
+};
+
+/// A proof and the instance columns it was made for.
+#[derive(Debug, Clone)]
+pub struct Statement<F> {
+    pub proof: Vec<u8>,
+    pub instance: Vec<Vec<F>>,
+}
+
+/// Verifies `statements` in batches, returning the indices of the proofs that don't verify.
+///
+/// With `f` failing proofs out of `m` this takes about `2 f log(m / f)` batches on top of
+/// the first one, so it's worth it as long as failures are rare.
+pub fn verify_batch<C: CurveAffine>(
+    params: &Params<C>,
+    vk: &VerifyingKey<C>,
+    statements: &[Statement<C::Scalar>],
+) -> Result<(), Vec<usize>> {
+    let mut failed = vec![];
+    bisect(params, vk, statements, 0, &mut failed);
+    if failed.is_empty() {
+        Ok(())
+    } else {
+        Err(failed)
+    }
+}
+
+fn bisect<C: CurveAffine>(
+    params: &Params<C>,
+    vk: &VerifyingKey<C>,
+    statements: &[Statement<C::Scalar>],
+    offset: usize,
+    failed: &mut Vec<usize>,
+) {
+    if statements.is_empty() {
+        return;
+    }
+
+    let mut batch = BatchVerifier::new();
+    for statement in statements {
+        batch.add_proof(vec![statement.instance.clone()], statement.proof.clone());
+    }
+    if batch.finalize(params, vk) {
+        return;
+    }
+
+    if statements.len() == 1 {
+        failed.push(offset);
+    } else {
+        let (left, right) = statements.split_at(statements.len() / 2);
+        bisect(params, vk, left, offset, failed);
+        bisect(params, vk, right, offset + left.len(), failed);
+    }
+}
+
+/// Verifies `statements` one after the other, with the same result as [`verify_batch`].
+pub fn verify_sequential<C: CurveAffine>(
+    params: &Params<C>,
+    vk: &VerifyingKey<C>,
+    statements: &[Statement<C::Scalar>],
+) -> Result<(), Vec<usize>> {
+    let failed: Vec<_> = statements
+        .iter()
+        .enumerate()
+        .filter(|(_, statement)| {
+            let instance: Vec<_> = statement
+                .instance
+                .iter()
+                .map(|column| column.as_slice())
+                .collect();
+            let mut transcript =
+                Blake2bRead::<_, _, Challenge255<_>>::init(statement.proof.as_slice());
+            let strategy = SingleVerifier::new(params);
+            verify_proof(params, vk, strategy, &[&instance[..]], &mut transcript).is_err()
+        })
+        .map(|(i, _)| i)
+        .collect();
+    if failed.is_empty() {
+        Ok(())
+    } else {
+        Err(failed)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use halo2_proofs::{
+        circuit::Value,
+        pasta::{EqAffine, Fp},
+        plonk::ProvingKey,
+    };
+    use rand::rngs::OsRng;
+
+    use super::*;
+    use crate::{
+        dev_tools::prover::{self, setup},
+        range_check::ex1::MyCircuit,
+    };
+
+    const RANGE: usize = 8;
+
+    /// A proof that `value` is in the range, which only verifies if it is.
+    fn prove(params: &Params<EqAffine>, pk: &ProvingKey<EqAffine>, value: u64) -> Statement<Fp> {
+        let circuit = MyCircuit::<Fp, RANGE> {
+            value: Value::known(Fp::from(value).into()),
+        };
+        Statement {
+            proof: prover::prove(params, pk, &circuit, &[], OsRng).unwrap(),
+            instance: vec![],
+        }
+    }
+
+    #[test]
+    fn test_all_valid() {
+        let (params, pk) = setup(&MyCircuit::<Fp, RANGE>::default());
+        let statements: Vec<_> = (0..RANGE as u64)
+            .map(|value| prove(&params, &pk, value))
+            .collect();
+        assert_eq!(verify_batch(&params, pk.get_vk(), &statements), Ok(()));
+        assert_eq!(verify_sequential(&params, pk.get_vk(), &statements), Ok(()));
+        assert_eq!(verify_batch(&params, pk.get_vk(), &[]), Ok(()));
+    }
+
+    #[test]
+    fn test_failing_proofs() {
+        let (params, pk) = setup(&MyCircuit::<Fp, RANGE>::default());
+        let mut statements: Vec<_> = (0..RANGE as u64)
+            .map(|value| prove(&params, &pk, value))
+            .collect();
+        // out of range, and a proof that was changed after the fact
+        statements[2] = prove(&params, &pk, RANGE as u64);
+        let last = statements[5].proof.len() - 32;
+        statements[5].proof[last] ^= 1;
+
+        assert_eq!(
+            verify_batch(&params, pk.get_vk(), &statements),
+            Err(vec![2, 5])
+        );
+        assert_eq!(
+            verify_sequential(&params, pk.get_vk(), &statements),
+            Err(vec![2, 5])
+        );
+    }
+}
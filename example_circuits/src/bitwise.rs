@@ -0,0 +1,344 @@
+use halo2_proofs::{
+    arithmetic::FieldExt,
+    circuit::{floor_planner::V1, AssignedCell, Layouter, Value},
+    plonk::{Advice, Circuit, Column, ConstraintSystem, Error, Expression, Instance, Selector},
+    poly::Rotation,
+};
+
+mod table;
+
+use table::BitwiseTable;
+
+/// A bitwise operation, looked up a byte at a time.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BitOp {
+    And,
+    Xor,
+    Or,
+}
+
+impl BitOp {
+    pub const ALL: [BitOp; 3] = [BitOp::And, BitOp::Xor, BitOp::Or];
+
+    pub fn apply(self, a: u64, b: u64) -> u64 {
+        match self {
+            BitOp::And => a & b,
+            BitOp::Xor => a ^ b,
+            BitOp::Or => a | b,
+        }
+    }
+}
+
+/// Bitwise operations on 32- and 64-bit words, with one `(a, b, a op b)` byte table
+/// per operation.
+///
+/// The three columns hold running sums of the operands and the result, like
+/// `decompose_range_check`, but with the chunk of each row going into a single
+/// three-column lookup:
+///
+///     |    a    |    b    |     out     | q_op |
+///     -----------------------------------------
+///     |   z_0   |   y_0   |  a op b     |  1   |
+///     |   z_1   |   y_1   |  ...        |  1   |
+///     |   ...   |   ...   |  ...        | ...  |
+///     |    0    |    0    |    0        |  0   |
+///
+///     z_0 = a, z_{i+1} = z_i >> 8, and byte_i = z_i - 256 * z_{i+1}
+///
+/// so every row looks up `(a_i, b_i, a_i op b_i)` for the i-th bytes. The last row is
+/// constrained to zero, which also range-checks both operands to the word size.
+#[derive(Debug, Clone)]
+pub struct BitwiseConfig<F: FieldExt> {
+    a: Column<Advice>,
+    b: Column<Advice>,
+    out: Column<Advice>,
+    /// one selector and one table per operation, in the order of [`BitOp::ALL`]
+    selectors: [Selector; 3],
+    tables: [BitwiseTable<F>; 3],
+}
+
+#[derive(Debug, Clone)]
+pub struct BitwiseChip<F: FieldExt> {
+    config: BitwiseConfig<F>,
+}
+
+impl<F: FieldExt> BitwiseChip<F> {
+    pub fn construct(config: BitwiseConfig<F>) -> Self {
+        Self { config }
+    }
+
+    pub fn configure(
+        meta: &mut ConstraintSystem<F>,
+        advice: [Column<Advice>; 3],
+    ) -> BitwiseConfig<F> {
+        let [a, b, out] = advice;
+        for column in advice {
+            meta.enable_equality(column);
+        }
+        // for the zeros at the end of the running sums
+        let constant = meta.fixed_column();
+        meta.enable_constant(constant);
+
+        let selectors = BitOp::ALL.map(|_| meta.complex_selector());
+        let tables = BitOp::ALL.map(|op| BitwiseTable::configure(meta, op));
+
+        for (selector, table) in selectors.iter().zip(tables.iter()) {
+            meta.lookup(|meta| {
+                let q_op = meta.query_selector(*selector);
+                let mut byte = |column| {
+                    let cur = meta.query_advice(column, Rotation::cur());
+                    let next = meta.query_advice(column, Rotation::next());
+                    cur - next * Expression::Constant(F::from(256u64))
+                };
+
+                vec![
+                    (q_op.clone() * byte(a), table.a),
+                    (q_op.clone() * byte(b), table.b),
+                    (q_op * byte(out), table.out),
+                ]
+            });
+        }
+
+        BitwiseConfig {
+            a,
+            b,
+            out,
+            selectors,
+            tables,
+        }
+    }
+
+    pub fn load_tables(&self, layouter: &mut impl Layouter<F>) -> Result<(), Error> {
+        for table in self.config.tables.iter() {
+            table.load(layouter)?;
+        }
+        Ok(())
+    }
+
+    /// `a op b` on `num_bits`-bit words. Fails to verify if either operand is wider.
+    pub fn apply(
+        &self,
+        mut layouter: impl Layouter<F>,
+        op: BitOp,
+        a: &AssignedCell<F, F>,
+        b: &AssignedCell<F, F>,
+        num_bits: usize,
+    ) -> Result<AssignedCell<F, F>, Error> {
+        assert!(num_bits % 8 == 0 && num_bits <= 64);
+        let num_bytes = num_bits / 8;
+        let config = &self.config;
+        let selector = config.selectors[op as usize];
+
+        layouter.assign_region(
+            || format!("{:?}", op),
+            |mut region| {
+                a.copy_advice(|| "a", &mut region, config.a, 0)?;
+                b.copy_advice(|| "b", &mut region, config.b, 0)?;
+
+                let a = a.value().map(|a| a.get_lower_128() as u64);
+                let b = b.value().map(|b| b.get_lower_128() as u64);
+                let out = a.zip(b).map(|(a, b)| op.apply(a, b));
+                let result =
+                    region.assign_advice(|| "a op b", config.out, 0, || out.map(F::from))?;
+
+                for row in 0..num_bytes {
+                    selector.enable(&mut region, row)?;
+                }
+                for row in 1..num_bytes {
+                    let shift = |v: Value<u64>| v.map(|v| F::from(v >> (8 * row)));
+                    region.assign_advice(|| format!("z_{}", row), config.a, row, || shift(a))?;
+                    region.assign_advice(|| format!("y_{}", row), config.b, row, || shift(b))?;
+                    region.assign_advice(
+                        || format!("out_{}", row),
+                        config.out,
+                        row,
+                        || shift(out),
+                    )?;
+                }
+                for column in [config.a, config.b, config.out] {
+                    region.assign_advice_from_constant(|| "zero", column, num_bytes, F::zero())?;
+                }
+
+                Ok(result)
+            },
+        )
+    }
+
+    pub fn and(
+        &self,
+        layouter: impl Layouter<F>,
+        a: &AssignedCell<F, F>,
+        b: &AssignedCell<F, F>,
+        num_bits: usize,
+    ) -> Result<AssignedCell<F, F>, Error> {
+        self.apply(layouter, BitOp::And, a, b, num_bits)
+    }
+
+    pub fn xor(
+        &self,
+        layouter: impl Layouter<F>,
+        a: &AssignedCell<F, F>,
+        b: &AssignedCell<F, F>,
+        num_bits: usize,
+    ) -> Result<AssignedCell<F, F>, Error> {
+        self.apply(layouter, BitOp::Xor, a, b, num_bits)
+    }
+
+    pub fn or(
+        &self,
+        layouter: impl Layouter<F>,
+        a: &AssignedCell<F, F>,
+        b: &AssignedCell<F, F>,
+        num_bits: usize,
+    ) -> Result<AssignedCell<F, F>, Error> {
+        self.apply(layouter, BitOp::Or, a, b, num_bits)
+    }
+}
+
+/// Proves that the public input is `a op b` for two private `NUM_BITS`-bit words.
+pub struct MyCircuit<F: FieldExt, const NUM_BITS: usize> {
+    pub a: Value<u64>,
+    pub b: Value<u64>,
+    pub op: BitOp,
+    _marker: std::marker::PhantomData<F>,
+}
+
+impl<F: FieldExt, const NUM_BITS: usize> MyCircuit<F, NUM_BITS> {
+    pub fn new(op: BitOp, a: u64, b: u64) -> Self {
+        Self {
+            a: Value::known(a),
+            b: Value::known(b),
+            op,
+            _marker: std::marker::PhantomData,
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct MyConfig<F: FieldExt> {
+    bitwise: BitwiseConfig<F>,
+    out: Column<Instance>,
+}
+
+impl<F: FieldExt, const NUM_BITS: usize> Circuit<F> for MyCircuit<F, NUM_BITS> {
+    type Config = MyConfig<F>;
+    type FloorPlanner = V1;
+
+    fn without_witnesses(&self) -> Self {
+        Self {
+            a: Value::unknown(),
+            b: Value::unknown(),
+            op: self.op,
+            _marker: std::marker::PhantomData,
+        }
+    }
+
+    fn configure(meta: &mut ConstraintSystem<F>) -> Self::Config {
+        let advice = [(); 3].map(|_| meta.advice_column());
+        let out = meta.instance_column();
+        meta.enable_equality(out);
+
+        MyConfig {
+            bitwise: BitwiseChip::configure(meta, advice),
+            out,
+        }
+    }
+
+    fn synthesize(
+        &self,
+        config: Self::Config,
+        mut layouter: impl Layouter<F>,
+    ) -> Result<(), Error> {
+        let chip = BitwiseChip::construct(config.bitwise.clone());
+        chip.load_tables(&mut layouter)?;
+
+        let (a, b) = layouter.assign_region(
+            || "operands",
+            |mut region| {
+                let a =
+                    region.assign_advice(|| "a", config.bitwise.a, 0, || self.a.map(F::from))?;
+                let b =
+                    region.assign_advice(|| "b", config.bitwise.b, 0, || self.b.map(F::from))?;
+                Ok((a, b))
+            },
+        )?;
+
+        let out = chip.apply(layouter.namespace(|| "op"), self.op, &a, &b, NUM_BITS)?;
+        layouter.constrain_instance(out.cell(), config.out, 0)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use halo2_proofs::pasta::Fp;
+
+    use super::*;
+    use crate::dev_tools::{
+        failure::{assert_satisfied, verify},
+        min_k::{min_k, CircuitSize},
+    };
+
+    #[test]
+    fn test_ops() {
+        let a: u64 = rand::random();
+        let b: u64 = rand::random();
+
+        for op in BitOp::ALL {
+            let circuit = MyCircuit::<Fp, 64>::new(op, a, b);
+            let k = min_k(&circuit);
+            // the three byte tables share their 2^16 rows, which are more than the rows
+            // of the words, so they and the blinding rows decide k
+            let size = CircuitSize::measure(&circuit).unwrap();
+            assert_eq!(size.table_rows, 1 << 16);
+            assert!(size.rows < size.table_rows);
+            assert_eq!(
+                1 << k,
+                (size.table_rows + size.blinding_factors + 1).next_power_of_two()
+            );
+            assert_satisfied(k, &circuit, vec![vec![Fp::from(op.apply(a, b))]]);
+
+            let (a, b) = (a as u32 as u64, b as u32 as u64);
+            let circuit = MyCircuit::<Fp, 32>::new(op, a, b);
+            assert_satisfied(k, &circuit, vec![vec![Fp::from(op.apply(a, b))]]);
+        }
+    }
+
+    #[test]
+    fn test_wrong_output() {
+        let circuit = MyCircuit::<Fp, 32>::new(BitOp::Xor, 0xdead_beef, 0x1234_5678);
+        let k = min_k(&circuit);
+
+        // the OR instead of the XOR
+        let report =
+            verify(k, &circuit, vec![vec![Fp::from(0xdead_beef | 0x1234_5678)]]).unwrap_err();
+        assert_eq!(report.groups[0].title, "Permutation");
+    }
+
+    #[test]
+    fn test_operand_too_wide() {
+        // a 33-bit operand doesn't reach zero after four bytes
+        let (a, b) = (1 << 32 | 0xff, 0x0f);
+        let circuit = MyCircuit::<Fp, 32>::new(BitOp::And, a, b);
+        let k = min_k(&circuit);
+
+        let report = verify(k, &circuit, vec![vec![Fp::from(BitOp::And.apply(a, b))]]).unwrap_err();
+        assert!(report
+            .groups
+            .iter()
+            .any(|group| group.title.starts_with("Lookup")));
+    }
+
+    #[cfg(feature = "dev-graph")]
+    #[test]
+    fn print_bitwise() {
+        use crate::dev_tools::graph::render_all;
+
+        let circuit = MyCircuit::<Fp, 32> {
+            a: Value::unknown(),
+            b: Value::unknown(),
+            op: BitOp::Xor,
+            _marker: std::marker::PhantomData,
+        };
+        render_all("bitwise", min_k(&circuit), &circuit);
+    }
+}
@@ -0,0 +1,64 @@
+use std::marker::PhantomData;
+
+use halo2_proofs::{
+    arithmetic::FieldExt,
+    circuit::{Layouter, Value},
+    plonk::{ConstraintSystem, Error, TableColumn},
+};
+
+use super::BitOp;
+
+/// Every pair of bytes `(a, b)` with `a op b`, for one operation.
+///
+///     |  a  |  b  | a op b |
+///     ---------------------
+///     |  0  |  0  |   0    |  <- also looked up when the selector is off
+///     |  0  |  1  |  ...   |
+///     | ... | ... |  ...   |
+///     | 255 | 255 |  ...   |
+///
+/// That is `2^16` rows, so a circuit with one of these tables needs `k >= 17`. The
+/// tables of different operations sit side by side in their own columns and share rows.
+#[derive(Debug, Clone)]
+pub(super) struct BitwiseTable<F: FieldExt> {
+    pub(super) op: BitOp,
+    pub(super) a: TableColumn,
+    pub(super) b: TableColumn,
+    pub(super) out: TableColumn,
+    _marker: PhantomData<F>,
+}
+
+impl<F: FieldExt> BitwiseTable<F> {
+    pub(super) fn configure(meta: &mut ConstraintSystem<F>, op: BitOp) -> Self {
+        Self {
+            op,
+            a: meta.lookup_table_column(),
+            b: meta.lookup_table_column(),
+            out: meta.lookup_table_column(),
+            _marker: PhantomData,
+        }
+    }
+
+    pub(super) fn load(&self, layouter: &mut impl Layouter<F>) -> Result<(), Error> {
+        layouter.assign_table(
+            || format!("load {:?} table", self.op),
+            |mut table| {
+                for a in 0..256u64 {
+                    for b in 0..256u64 {
+                        let offset = (a * 256 + b) as usize;
+                        let out = self.op.apply(a, b);
+                        table.assign_cell(|| "a", self.a, offset, || Value::known(F::from(a)))?;
+                        table.assign_cell(|| "b", self.b, offset, || Value::known(F::from(b)))?;
+                        table.assign_cell(
+                            || "a op b",
+                            self.out,
+                            offset,
+                            || Value::known(F::from(out)),
+                        )?;
+                    }
+                }
+                Ok(())
+            },
+        )
+    }
+}
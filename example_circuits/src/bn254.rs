@@ -1,3 +1,110 @@
-mod ex1;
-mod ex2;
-mod ex3;
\ No newline at end of file
+//! The chips of this crate over BN254's scalar field [`fr::Fr`] instead of Pasta's `Fp`.
+//! None of them assume Pasta: the tests below run the same circuits over both fields.
+//! [`fq`] goes the other way and does BN254's base field arithmetic inside a circuit, and
+//! [`kzg`] commits to polynomials over `Fr` with BN254's pairing, outside of any circuit.
+
+pub mod fq;
+pub mod fr;
+pub mod kzg;
+
+#[cfg(test)]
+mod tests {
+    use ff::PrimeFieldBits;
+    use halo2_proofs::{arithmetic::FieldExt, circuit::Value, pasta::Fp};
+    use num_bigint::BigUint;
+
+    use super::{fq, fr::Fr};
+    use crate::{
+        bitwise::{self, BitOp},
+        decompose_range_check, mimc,
+        dev_tools::{failure::verify, min_k::min_k},
+        range_check,
+    };
+
+    /// Every range check accepts the values in range and rejects the first one past it.
+    fn range_checks<F: FieldExt + PrimeFieldBits>() {
+        for (value, in_range) in [(0u64, true), (7, true), (8, false)] {
+            let circuit = range_check::ex1::MyCircuit::<F, 8> {
+                value: Value::known(F::from(value).into()),
+            };
+            assert_eq!(verify(min_k(&circuit), &circuit, vec![]).is_ok(), in_range);
+
+            let circuit = range_check::ex2::MyCircuit::<F, 8, 256> {
+                value: Value::known(F::from(value).into()),
+                large_value: Value::known(F::from(value * 32).into()),
+            };
+            assert_eq!(verify(min_k(&circuit), &circuit, vec![]).is_ok(), in_range);
+        }
+
+        // ex3 also checks the tag: 4..8 are the 3-bit values
+        for (value, in_range) in [(4u64, true), (7, true), (8, false)] {
+            let circuit = range_check::ex3::MyCircuit::<F, 8, 256> {
+                num_bits: Value::known(3),
+                value: Value::known(F::from(value).into()),
+            };
+            assert_eq!(verify(min_k(&circuit), &circuit, vec![]).is_ok(), in_range);
+        }
+
+        for value in [0, u64::MAX] {
+            let circuit = decompose_range_check::ex1::MyCircuit::<F, 256> {
+                value: Value::known(F::from(value).into()),
+                num_bits: 64,
+            };
+            assert!(verify(min_k(&circuit), &circuit, vec![]).is_ok());
+        }
+
+        // -1 is a 254- or 255-bit number, depending on the field
+        let circuit = decompose_range_check::ex1::MyCircuit::<F, 256> {
+            value: Value::known((-F::one()).into()),
+            num_bits: 64,
+        };
+        assert!(verify(min_k(&circuit), &circuit, vec![]).is_err());
+    }
+
+    fn arithmetic<F: FieldExt + PrimeFieldBits>() {
+        let (a, b) = (0xdead_beef_u64, 0x1234_5678_u64);
+        let circuit = bitwise::MyCircuit::<F, 32>::new(BitOp::Xor, a, b);
+        let k = min_k(&circuit);
+        assert!(verify(k, &circuit, vec![vec![F::from(a ^ b)]]).is_ok());
+        assert!(verify(k, &circuit, vec![vec![F::from(a | b)]]).is_err());
+
+        let message = [F::one(), F::from(2u64)];
+        let digest = mimc::native::hash(&message, &mimc::native::round_constants());
+        let circuit = mimc::MyCircuit::<F, 2> {
+            message: message.iter().map(|m| Value::known(*m)).collect(),
+        };
+        let k = min_k(&circuit);
+        assert!(verify(k, &circuit, vec![vec![digest]]).is_ok());
+        assert!(verify(k, &circuit, vec![vec![digest + F::one()]]).is_err());
+
+        let p = fq::modulus();
+        let (a, b) = (&p - 1u64, &p - 2u64);
+        let c = (&a + &b) * &b % &p;
+        let circuit = fq::MyCircuit::<F>::new(a.clone(), b.clone(), c.clone());
+        let k = min_k(&circuit);
+        assert!(verify(k, &circuit, fq::MyCircuit::instance(&c)).is_ok());
+        let wrong = (&c + BigUint::from(1u64)) % &p;
+        let circuit = fq::MyCircuit::<F>::new(a, b, wrong.clone());
+        assert!(verify(k, &circuit, fq::MyCircuit::instance(&wrong)).is_err());
+    }
+
+    #[test]
+    fn test_range_checks_pasta() {
+        range_checks::<Fp>();
+    }
+
+    #[test]
+    fn test_range_checks_bn254() {
+        range_checks::<Fr>();
+    }
+
+    #[test]
+    fn test_arithmetic_pasta() {
+        arithmetic::<Fp>();
+    }
+
+    #[test]
+    fn test_arithmetic_bn254() {
+        arithmetic::<Fr>();
+    }
+}
@@ -0,0 +1,691 @@
+use std::marker::PhantomData;
+
+use ff::PrimeFieldBits;
+use halo2_proofs::{
+    arithmetic::FieldExt,
+    circuit::{floor_planner::V1, AssignedCell, Layouter, Region, Value},
+    plonk::{
+        Advice, Circuit, Column, ConstraintSystem, Constraints, Error, Expression, Instance,
+        Selector,
+    },
+    poly::Rotation,
+};
+use num_bigint::BigUint;
+
+use crate::decompose_range_check::ex1::DecomposeConfig;
+
+pub const LIMB_BITS: usize = 64;
+pub const NUM_LIMBS: usize = 4;
+/// A quotient needs a fifth limb, and the binary half of the multiplication check
+/// works mod `2^(64 * MUL_LIMBS)`.
+const MUL_LIMBS: usize = NUM_LIMBS + 1;
+/// `q < (2^256)^2 / p < 2^259`, so its fifth limb is checked to 8 bits.
+const QUOTIENT_TOP_BITS: usize = 8;
+/// The carries of the multiplication stay below `2^68`, checked to the next whole byte.
+const CARRY_BITS: usize = 72;
+/// The range checks look up 8-bit chunks.
+const LOOKUP_RANGE: usize = 256;
+
+/// The BN254 base field modulus `p`, the field the curve's coordinates live in.
+pub fn modulus() -> BigUint {
+    BigUint::parse_bytes(
+        b"30644e72e131a029b85045b68181585d97816a916871ca8d3c208c16d87cfd47",
+        16,
+    )
+    .unwrap()
+}
+
+/// The `n` 64-bit limbs of `x`, least significant first.
+pub fn to_limbs(x: &BigUint, n: usize) -> Vec<u64> {
+    let mut limbs = x.to_u64_digits();
+    assert!(limbs.len() <= n, "{} doesn't fit in {} limbs", x, n);
+    limbs.resize(n, 0);
+    limbs
+}
+
+/// `x mod n`, for the circuit's field of order `n`.
+fn to_field<F: FieldExt>(x: &BigUint) -> F {
+    x.to_u64_digits().iter().rev().fold(F::zero(), |acc, limb| {
+        acc * F::from_u128(1 << LIMB_BITS) + F::from(*limb)
+    })
+}
+
+/// A BN254 base field element in the circuit: four 64-bit limbs, each range-checked, so
+/// the integer is below `2^256` but not necessarily below `p` until it is
+/// [`FqChip::reduce`]d.
+#[derive(Debug, Clone)]
+pub struct AssignedFq<F: FieldExt> {
+    pub limbs: [AssignedCell<F, F>; NUM_LIMBS],
+}
+
+impl<F: FieldExt> AssignedFq<F> {
+    /// The integer the limbs add up to.
+    pub fn value(&self) -> Value<BigUint> {
+        let limbs: Value<Vec<F>> = self
+            .limbs
+            .iter()
+            .map(|limb| limb.value().copied())
+            .collect();
+        limbs.map(|limbs| {
+            limbs.iter().rev().fold(BigUint::default(), |acc, limb| {
+                (acc << LIMB_BITS) + limb.get_lower_128()
+            })
+        })
+    }
+}
+
+/// The witness of `a * b + c = q * p + r`: the limbs of `q` and `r`, and the carries
+/// between the columns of the binary check.
+struct MulAddWitness<F: FieldExt> {
+    quotient: Vec<F>,
+    remainder: Vec<F>,
+    carries: Vec<F>,
+}
+
+impl<F: FieldExt> MulAddWitness<F> {
+    fn new(a: &BigUint, b: &BigUint, c: &BigUint) -> Self {
+        let p = modulus();
+        let total = a * b + c;
+        let (q, r) = (&total / &p, &total % &p);
+        let limbs = |x: &BigUint, n| {
+            to_limbs(x, n)
+                .into_iter()
+                .map(BigUint::from)
+                .collect::<Vec<_>>()
+        };
+        let (a, b, c) = (
+            limbs(a, NUM_LIMBS),
+            limbs(b, NUM_LIMBS),
+            limbs(c, NUM_LIMBS),
+        );
+        let (q_limbs, r_limbs) = (limbs(&q, MUL_LIMBS), limbs(&r, NUM_LIMBS));
+        let p_prime = limbs(&negated_modulus(), MUL_LIMBS);
+
+        // the same columns as the gate, carried over as integers
+        let mut carry = BigUint::default();
+        let mut carries = vec![];
+        for k in 0..MUL_LIMBS {
+            let mut column = carry;
+            for i in 0..=k {
+                let j = k - i;
+                if i < NUM_LIMBS && j < NUM_LIMBS {
+                    column += &a[i] * &b[j];
+                }
+                column += &q_limbs[i] * &p_prime[j];
+            }
+            if k < NUM_LIMBS {
+                column += &c[k];
+                column -= &r_limbs[k];
+            }
+            carry = column >> LIMB_BITS;
+            carries.push(to_field(&carry));
+        }
+
+        Self {
+            quotient: q_limbs.iter().map(to_field).collect(),
+            remainder: r_limbs.iter().map(to_field).collect(),
+            carries,
+        }
+    }
+}
+
+/// `2^320 - p`: adding `q * (2^320 - p)` is subtracting `q * p` mod `2^320`, without
+/// any negative terms in the columns.
+fn negated_modulus() -> BigUint {
+    (BigUint::from(1u64) << (LIMB_BITS * MUL_LIMBS)) - modulus()
+}
+
+/// Arithmetic in the BN254 base field `Fq` inside a circuit over another field, e.g.
+/// Pasta's `Fp`. `p` is 254 bits, so an element doesn't fit in one cell: it is split into
+/// four 64-bit limbs, and every limb is range-checked with the 8-bit lookup of
+/// `decompose_range_check`.
+///
+/// Everything is one gate, `a * b + c = q * p + r` (`add` is `a * 1 + b`, `mul` is
+/// `a * b + 0`), on five advice columns:
+///
+///     | a_0 | a_1 | a_2 | a_3 |     |  q_mul_add = 1
+///     | b_0 | b_1 | b_2 | b_3 |     |
+///     | c_0 | c_1 | c_2 | c_3 |     |
+///     | q_0 | q_1 | q_2 | q_3 | q_4 |
+///     | r_0 | r_1 | r_2 | r_3 |     |
+///     | t_0 | t_1 | t_2 | t_3 | t_4 |  carries
+///
+/// The products of the limbs are 128 bits, far from wrapping around the native field,
+/// but `a * b` as a whole is 512 bits, so the equation is checked twice, CRT style:
+///
+/// 1. mod `n`, the order of the native field: `a * b + c - q * p - r = 0`, with every
+///    element recombined as `sum_i x_i * 2^(64i)`, which is only right mod `n`.
+/// 2. mod `2^320`, one 64-bit column at a time: column `k` of `a * b + q * (2^320 - p)`
+///    plus `c_k`, minus `r_k`, plus the carry in, is `t_k * 2^64`. The columns are
+///    below `2^133`, so these hold as integers.
+///
+/// Together they hold mod `n * 2^320`, over `2^574` for Pasta, and with all limbs
+/// range-checked neither side of the equation gets anywhere near that (`q * p` is below
+/// `2^264 * 2^254`), so it holds over the integers.
+///
+/// `r` is range-checked to four limbs, so it is below `2^256` but could still be
+/// `r + p`. [`FqChip::reduce`] pins it below `p` with a second gate that adds limbs,
+/// `r + d = p - 1` for a range-checked `d`:
+///
+///     | x_0 | x_1 | x_2 | x_3 |  q_sum = 1
+///     | y_0 | y_1 | y_2 | y_3 |
+///     | z_0 | z_1 | z_2 | z_3 |
+///     | c_0 | c_1 | c_2 |     |  boolean carries
+#[derive(Debug, Clone)]
+pub struct FqConfig<F: FieldExt> {
+    advice: [Column<Advice>; MUL_LIMBS],
+    q_mul_add: Selector,
+    q_sum: Selector,
+    range_check: DecomposeConfig<F, LOOKUP_RANGE>,
+}
+
+#[derive(Debug, Clone)]
+pub struct FqChip<F: FieldExt> {
+    config: FqConfig<F>,
+}
+
+impl<F: FieldExt + PrimeFieldBits> FqChip<F> {
+    pub fn construct(config: FqConfig<F>) -> Self {
+        Self { config }
+    }
+
+    pub fn configure(
+        meta: &mut ConstraintSystem<F>,
+        advice: [Column<Advice>; MUL_LIMBS],
+    ) -> FqConfig<F> {
+        for column in advice {
+            meta.enable_equality(column);
+        }
+        // for the limbs of the constants
+        let constant = meta.fixed_column();
+        meta.enable_constant(constant);
+
+        let q_mul_add = meta.selector();
+        let q_sum = meta.selector();
+        let range_check = DecomposeConfig::configure(meta);
+
+        let shift = || Expression::Constant(F::from_u128(1 << LIMB_BITS));
+        // sum_i x_i * 2^(64i), mod n
+        let recombine = |limbs: &[Expression<F>]| {
+            limbs
+                .iter()
+                .rev()
+                .fold(Expression::Constant(F::zero()), |acc, limb| {
+                    acc * shift() + limb.clone()
+                })
+        };
+
+        meta.create_gate("mul add", |meta| {
+            let q_mul_add = meta.query_selector(q_mul_add);
+            let mut row = |rotation: i32, n: usize| -> Vec<Expression<F>> {
+                advice[..n]
+                    .iter()
+                    .map(|column| meta.query_advice(*column, Rotation(rotation)))
+                    .collect()
+            };
+            let (a, b, c) = (row(0, NUM_LIMBS), row(1, NUM_LIMBS), row(2, NUM_LIMBS));
+            let (q, r, t) = (row(3, MUL_LIMBS), row(4, NUM_LIMBS), row(5, MUL_LIMBS));
+
+            let p = Expression::Constant(to_field(&modulus()));
+            let native =
+                recombine(&a) * recombine(&b) + recombine(&c) - recombine(&q) * p - recombine(&r);
+
+            let p_prime = to_limbs(&negated_modulus(), MUL_LIMBS);
+            let columns = (0..MUL_LIMBS).map(|k| {
+                let mut column = if k == 0 {
+                    Expression::Constant(F::zero())
+                } else {
+                    t[k - 1].clone()
+                };
+                for i in 0..=k {
+                    let j = k - i;
+                    if i < NUM_LIMBS && j < NUM_LIMBS {
+                        column = column + a[i].clone() * b[j].clone();
+                    }
+                    column = column + q[i].clone() * Expression::Constant(F::from(p_prime[j]));
+                }
+                if k < NUM_LIMBS {
+                    column = column + c[k].clone() - r[k].clone();
+                }
+                (format!("column {}", k), column - t[k].clone() * shift())
+            });
+
+            Constraints::with_selector(
+                q_mul_add,
+                std::iter::once(("native".to_string(), native))
+                    .chain(columns)
+                    .collect::<Vec<_>>(),
+            )
+        });
+
+        meta.create_gate("limb sum", |meta| {
+            let q_sum = meta.query_selector(q_sum);
+            let mut row = |rotation: i32, n: usize| -> Vec<Expression<F>> {
+                advice[..n]
+                    .iter()
+                    .map(|column| meta.query_advice(*column, Rotation(rotation)))
+                    .collect()
+            };
+            let (x, y, z) = (row(0, NUM_LIMBS), row(1, NUM_LIMBS), row(2, NUM_LIMBS));
+            let carries = row(3, NUM_LIMBS - 1);
+
+            let one = Expression::Constant(F::one());
+            let booleans = carries.iter().map(|c| {
+                (
+                    "boolean carry".to_string(),
+                    c.clone() * (one.clone() - c.clone()),
+                )
+            });
+            // no carry out of the last limb: the sum fits in four limbs
+            let limbs = (0..NUM_LIMBS).map(|i| {
+                let carry_in = if i == 0 {
+                    Expression::Constant(F::zero())
+                } else {
+                    carries[i - 1].clone()
+                };
+                let carry_out = if i + 1 < NUM_LIMBS {
+                    carries[i].clone() * shift()
+                } else {
+                    Expression::Constant(F::zero())
+                };
+                (
+                    format!("limb {}", i),
+                    x[i].clone() + y[i].clone() + carry_in - z[i].clone() - carry_out,
+                )
+            });
+
+            Constraints::with_selector(q_sum, booleans.chain(limbs).collect::<Vec<_>>())
+        });
+
+        FqConfig {
+            advice,
+            q_mul_add,
+            q_sum,
+            range_check,
+        }
+    }
+
+    pub fn load_table(&self, layouter: &mut impl Layouter<F>) -> Result<(), Error> {
+        self.config.range_check.load_table(layouter)
+    }
+
+    fn range_check(
+        &self,
+        mut layouter: impl Layouter<F>,
+        cells: &[AssignedCell<F, F>],
+        num_bits: &[usize],
+    ) -> Result<(), Error> {
+        for (i, (cell, num_bits)) in cells.iter().zip(num_bits).enumerate() {
+            self.config.range_check.assign(
+                layouter.namespace(|| format!("limb {}", i)),
+                cell.clone(),
+                *num_bits,
+            )?;
+        }
+        Ok(())
+    }
+
+    fn assign_row(
+        &self,
+        region: &mut Region<'_, F>,
+        row: usize,
+        values: &[Value<F>],
+    ) -> Result<Vec<AssignedCell<F, F>>, Error> {
+        values
+            .iter()
+            .zip(self.config.advice)
+            .enumerate()
+            .map(|(i, (value, column))| {
+                region.assign_advice(|| format!("limb {}", i), column, row, || *value)
+            })
+            .collect()
+    }
+
+    fn copy_row(
+        &self,
+        region: &mut Region<'_, F>,
+        row: usize,
+        x: &AssignedFq<F>,
+    ) -> Result<(), Error> {
+        for (i, (limb, column)) in x.limbs.iter().zip(self.config.advice).enumerate() {
+            limb.copy_advice(|| format!("limb {}", i), region, column, row)?;
+        }
+        Ok(())
+    }
+
+    fn constant_row(
+        &self,
+        region: &mut Region<'_, F>,
+        row: usize,
+        x: &BigUint,
+    ) -> Result<Vec<AssignedCell<F, F>>, Error> {
+        to_limbs(x, NUM_LIMBS)
+            .into_iter()
+            .zip(self.config.advice)
+            .enumerate()
+            .map(|(i, (limb, column))| {
+                region.assign_advice_from_constant(
+                    || format!("limb {}", i),
+                    column,
+                    row,
+                    F::from(limb),
+                )
+            })
+            .collect()
+    }
+
+    /// Witnesses `x`, which must be below `2^256`, and range-checks its limbs.
+    pub fn witness(
+        &self,
+        mut layouter: impl Layouter<F>,
+        x: Value<BigUint>,
+    ) -> Result<AssignedFq<F>, Error> {
+        let limbs = x.map(|x| to_limbs(&x, NUM_LIMBS)).transpose_vec(NUM_LIMBS);
+        let limbs = layouter.assign_region(
+            || "witness",
+            |mut region| {
+                let values: Vec<_> = limbs.iter().map(|limb| limb.map(F::from)).collect();
+                self.assign_row(&mut region, 0, &values)
+            },
+        )?;
+        self.range_check(
+            layouter.namespace(|| "range check"),
+            &limbs,
+            &[LIMB_BITS; NUM_LIMBS],
+        )?;
+
+        Ok(AssignedFq {
+            limbs: limbs.try_into().unwrap(),
+        })
+    }
+
+    /// `x` as fixed limbs. There's nothing to range-check.
+    pub fn constant(
+        &self,
+        mut layouter: impl Layouter<F>,
+        x: &BigUint,
+    ) -> Result<AssignedFq<F>, Error> {
+        let limbs = layouter.assign_region(
+            || "constant",
+            |mut region| self.constant_row(&mut region, 0, x),
+        )?;
+        Ok(AssignedFq {
+            limbs: limbs.try_into().unwrap(),
+        })
+    }
+
+    /// `a * b + c mod p`, below `2^256` but not necessarily reduced.
+    pub fn mul_add(
+        &self,
+        mut layouter: impl Layouter<F>,
+        a: &AssignedFq<F>,
+        b: &AssignedFq<F>,
+        c: &AssignedFq<F>,
+    ) -> Result<AssignedFq<F>, Error> {
+        let witness = a
+            .value()
+            .zip(b.value())
+            .zip(c.value())
+            .map(|((a, b), c)| MulAddWitness::<F>::new(&a, &b, &c));
+        let column = |select: fn(&MulAddWitness<F>) -> &Vec<F>, n| -> Vec<Value<F>> {
+            (0..n)
+                .map(|i| witness.as_ref().map(|w| select(w)[i]))
+                .collect()
+        };
+        let quotient = column(|w| &w.quotient, MUL_LIMBS);
+        let remainder = column(|w| &w.remainder, NUM_LIMBS);
+        let carries = column(|w| &w.carries, MUL_LIMBS);
+
+        let (q, r, t) = layouter.assign_region(
+            || "a * b + c",
+            |mut region| {
+                self.config.q_mul_add.enable(&mut region, 0)?;
+                self.copy_row(&mut region, 0, a)?;
+                self.copy_row(&mut region, 1, b)?;
+                self.copy_row(&mut region, 2, c)?;
+                let q = self.assign_row(&mut region, 3, &quotient)?;
+                let r = self.assign_row(&mut region, 4, &remainder)?;
+                let t = self.assign_row(&mut region, 5, &carries)?;
+                Ok((q, r, t))
+            },
+        )?;
+
+        let mut quotient_bits = [LIMB_BITS; MUL_LIMBS];
+        quotient_bits[NUM_LIMBS] = QUOTIENT_TOP_BITS;
+        self.range_check(layouter.namespace(|| "q"), &q, &quotient_bits)?;
+        self.range_check(layouter.namespace(|| "r"), &r, &[LIMB_BITS; NUM_LIMBS])?;
+        self.range_check(
+            layouter.namespace(|| "carries"),
+            &t,
+            &[CARRY_BITS; MUL_LIMBS],
+        )?;
+
+        Ok(AssignedFq {
+            limbs: r.try_into().unwrap(),
+        })
+    }
+
+    pub fn add(
+        &self,
+        mut layouter: impl Layouter<F>,
+        a: &AssignedFq<F>,
+        b: &AssignedFq<F>,
+    ) -> Result<AssignedFq<F>, Error> {
+        let one = self.constant(layouter.namespace(|| "one"), &BigUint::from(1u64))?;
+        self.mul_add(layouter.namespace(|| "a * 1 + b"), a, &one, b)
+    }
+
+    pub fn mul(
+        &self,
+        mut layouter: impl Layouter<F>,
+        a: &AssignedFq<F>,
+        b: &AssignedFq<F>,
+    ) -> Result<AssignedFq<F>, Error> {
+        let zero = self.constant(layouter.namespace(|| "zero"), &BigUint::default())?;
+        self.mul_add(layouter.namespace(|| "a * b + 0"), a, b, &zero)
+    }
+
+    /// `a mod p`, in its canonical form below `p`.
+    pub fn reduce(
+        &self,
+        mut layouter: impl Layouter<F>,
+        a: &AssignedFq<F>,
+    ) -> Result<AssignedFq<F>, Error> {
+        let one = self.constant(layouter.namespace(|| "one"), &BigUint::from(1u64))?;
+        let zero = self.constant(layouter.namespace(|| "zero"), &BigUint::default())?;
+        let r = self.mul_add(layouter.namespace(|| "a * 1 + 0"), a, &one, &zero)?;
+
+        // r + d = p - 1 with both below 2^256 as integers, so r < p
+        let d = self.witness(
+            layouter.namespace(|| "d"),
+            r.value().map(|r| modulus() - 1u64 - r),
+        )?;
+        layouter.assign_region(
+            || "r + d = p - 1",
+            |mut region| {
+                self.config.q_sum.enable(&mut region, 0)?;
+                self.copy_row(&mut region, 0, &r)?;
+                self.copy_row(&mut region, 1, &d)?;
+                self.constant_row(&mut region, 2, &(modulus() - 1u64))?;
+
+                let (x, y) = (r.value(), d.value());
+                let carries = x.zip(y).map(|(x, y)| {
+                    let (x, y) = (to_limbs(&x, NUM_LIMBS), to_limbs(&y, NUM_LIMBS));
+                    let mut carry = 0u128;
+                    (0..NUM_LIMBS - 1)
+                        .map(|i| {
+                            carry = (x[i] as u128 + y[i] as u128 + carry) >> LIMB_BITS;
+                            F::from_u128(carry)
+                        })
+                        .collect::<Vec<_>>()
+                });
+                self.assign_row(&mut region, 3, &carries.transpose_vec(NUM_LIMBS - 1))
+            },
+        )?;
+
+        Ok(r)
+    }
+
+    /// Constrains `a = b mod p`: their canonical forms have the same limbs.
+    pub fn assert_equal(
+        &self,
+        mut layouter: impl Layouter<F>,
+        a: &AssignedFq<F>,
+        b: &AssignedFq<F>,
+    ) -> Result<(), Error> {
+        let a = self.reduce(layouter.namespace(|| "reduce a"), a)?;
+        let b = self.reduce(layouter.namespace(|| "reduce b"), b)?;
+        layouter.assign_region(
+            || "a = b",
+            |mut region| {
+                for (a, b) in a.limbs.iter().zip(b.limbs.iter()) {
+                    region.constrain_equal(a.cell(), b.cell())?;
+                }
+                Ok(())
+            },
+        )
+    }
+}
+
+/// Proves `(a + b) * b = c` in BN254's base field, for private `a` and `b` and a public
+/// `c`, given as its four limbs. Neither the inputs nor `c` have to be reduced.
+#[derive(Default)]
+pub struct MyCircuit<F: FieldExt> {
+    pub a: Value<BigUint>,
+    pub b: Value<BigUint>,
+    pub c: Value<BigUint>,
+    _marker: PhantomData<F>,
+}
+
+impl<F: FieldExt> MyCircuit<F> {
+    pub fn new(a: BigUint, b: BigUint, c: BigUint) -> Self {
+        Self {
+            a: Value::known(a),
+            b: Value::known(b),
+            c: Value::known(c),
+            _marker: PhantomData,
+        }
+    }
+
+    /// The public input: the limbs of `c`.
+    pub fn instance(c: &BigUint) -> Vec<Vec<F>> {
+        vec![to_limbs(c, NUM_LIMBS).into_iter().map(F::from).collect()]
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct MyConfig<F: FieldExt> {
+    fq: FqConfig<F>,
+    instance: Column<Instance>,
+}
+
+impl<F: FieldExt + PrimeFieldBits> Circuit<F> for MyCircuit<F> {
+    type Config = MyConfig<F>;
+    type FloorPlanner = V1;
+
+    fn without_witnesses(&self) -> Self {
+        Self::default()
+    }
+
+    fn configure(meta: &mut ConstraintSystem<F>) -> Self::Config {
+        let advice = [(); MUL_LIMBS].map(|_| meta.advice_column());
+        let instance = meta.instance_column();
+        meta.enable_equality(instance);
+
+        MyConfig {
+            fq: FqChip::configure(meta, advice),
+            instance,
+        }
+    }
+
+    fn synthesize(
+        &self,
+        config: Self::Config,
+        mut layouter: impl Layouter<F>,
+    ) -> Result<(), Error> {
+        let chip = FqChip::construct(config.fq.clone());
+        chip.load_table(&mut layouter)?;
+
+        let a = chip.witness(layouter.namespace(|| "a"), self.a.clone())?;
+        let b = chip.witness(layouter.namespace(|| "b"), self.b.clone())?;
+        let c = chip.witness(layouter.namespace(|| "c"), self.c.clone())?;
+
+        let sum = chip.add(layouter.namespace(|| "a + b"), &a, &b)?;
+        let product = chip.mul(layouter.namespace(|| "(a + b) * b"), &sum, &b)?;
+        chip.assert_equal(layouter.namespace(|| "(a + b) * b = c"), &product, &c)?;
+
+        for (i, limb) in c.limbs.iter().enumerate() {
+            layouter.constrain_instance(limb.cell(), config.instance, i)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use halo2_proofs::pasta::Fp;
+
+    use super::*;
+    use crate::dev_tools::{
+        failure::{assert_satisfied, verify},
+        min_k::min_k,
+    };
+
+    fn random_below(bound: &BigUint) -> BigUint {
+        BigUint::from_bytes_le(&rand::random::<[u8; 32]>()) % bound
+    }
+
+    #[test]
+    fn test_matches_native() {
+        let p = modulus();
+        let (a, b) = (random_below(&p), random_below(&p));
+        let c = (&a + &b) * &b % &p;
+
+        let circuit = MyCircuit::<Fp>::new(a, b, c.clone());
+        assert_satisfied(min_k(&circuit), &circuit, MyCircuit::instance(&c));
+    }
+
+    #[test]
+    fn test_unreduced() {
+        let p = modulus();
+        // the largest inputs four limbs can hold, and an output that is off by p
+        let max = (BigUint::from(1u64) << (LIMB_BITS * NUM_LIMBS)) - 1u64;
+        let c = (&max + &max) * &max % &p + &p;
+
+        let circuit = MyCircuit::<Fp>::new(max.clone(), max, c.clone());
+        assert_satisfied(min_k(&circuit), &circuit, MyCircuit::instance(&c));
+    }
+
+    #[test]
+    fn test_wrong_output() {
+        let p = modulus();
+        let (a, b) = (random_below(&p), random_below(&p));
+        let c = ((&a + &b) * &b + 1u64) % &p;
+
+        let circuit = MyCircuit::<Fp>::new(a, b, c.clone());
+        let report = verify(min_k(&circuit), &circuit, MyCircuit::instance(&c)).unwrap_err();
+        assert_eq!(report.groups[0].title, "Permutation");
+    }
+
+    #[test]
+    fn test_witness_bounds() {
+        // the range checks must hold for the worst case, all limbs at 2^64 - 1
+        let max = (BigUint::from(1u64) << (LIMB_BITS * NUM_LIMBS)) - 1u64;
+        let witness = MulAddWitness::<Fp>::new(&max, &max, &max);
+
+        let fits = |x: &Fp, bits: usize| {
+            x.get_lower_128() >> bits == 0 && Fp::from_u128(x.get_lower_128()) == *x
+        };
+        assert!(fits(&witness.quotient[NUM_LIMBS], QUOTIENT_TOP_BITS));
+        assert!(witness.carries.iter().all(|t| fits(t, CARRY_BITS)));
+    }
+
+    #[cfg(feature = "dev-graph")]
+    #[test]
+    fn print_fq() {
+        use crate::dev_tools::graph::render_all;
+
+        let circuit = MyCircuit::<Fp>::default();
+        render_all("bn254-fq", min_k(&circuit), &circuit);
+    }
+}
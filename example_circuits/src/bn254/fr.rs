@@ -0,0 +1,225 @@
+//! The BN254 scalar field, as a circuit field for our halo2.
+//!
+//! halo2's BN254 support lives in the PSE fork, whose `halo2curves::bn256::Fr` implements
+//! the fork's `FieldExt`, not the `pasta_curves` one our halo2 asks for. So the field is
+//! generated here with `ff`'s derive macro, and the few extra things `FieldExt` wants
+//! (constants for the FFT and square roots, wide reduction) are filled in by hand.
+//!
+//! `ff_derive` drops its own `MODULUS`, `R`, `GENERATOR`, ... constants into this
+//! module, so everything else is kept inside the impls.
+
+use ff::{Field, PrimeField};
+use halo2_proofs::arithmetic::{FieldExt, Group, SqrtRatio};
+
+/// `r = 21888242871839275222246405745257275088548364400416034343698204186575808495617`,
+/// the order of the BN254 groups: the field Ethereum's PLONK and Groth16 circuits are
+/// written over. `r - 1 = 2^28 * t`, and `7` generates the multiplicative group.
+#[derive(PrimeField)]
+#[PrimeFieldModulus = "21888242871839275222246405745257275088548364400416034343698204186575808495617"]
+#[PrimeFieldGenerator = "7"]
+#[PrimeFieldReprEndianness = "little"]
+pub struct Fr([u64; 4]);
+
+impl Fr {
+    /// The canonical little-endian limbs.
+    fn to_limbs(self) -> [u64; 4] {
+        let repr = self.to_repr();
+        let bytes = repr.as_ref();
+        [0, 1, 2, 3].map(|i| u64::from_le_bytes(bytes[8 * i..8 * (i + 1)].try_into().unwrap()))
+    }
+}
+
+impl From<bool> for Fr {
+    fn from(bit: bool) -> Self {
+        if bit {
+            Self::one()
+        } else {
+            Self::zero()
+        }
+    }
+}
+
+impl Group for Fr {
+    type Scalar = Fr;
+
+    fn group_zero() -> Self {
+        Self::zero()
+    }
+
+    fn group_add(&mut self, rhs: &Self) {
+        *self += *rhs;
+    }
+
+    fn group_sub(&mut self, rhs: &Self) {
+        *self -= *rhs;
+    }
+
+    fn group_scale(&mut self, by: &Self::Scalar) {
+        *self *= *by;
+    }
+}
+
+impl SqrtRatio for Fr {
+    /// `(t - 1) / 2`
+    const T_MINUS1_OVER2: [u64; 4] = [
+        0xcdcb848a1f0fac9f,
+        0x0c0ac2e9419f4243,
+        0x098d014dc2822db4,
+        0x0000000183227397,
+    ];
+
+    fn get_lower_32(&self) -> u32 {
+        self.to_limbs()[0] as u32
+    }
+}
+
+// The constants are in Montgomery form, `x * 2^256 mod r`, which is what the derived
+// struct holds. The tests below check them against their definitions.
+impl FieldExt for Fr {
+    const MODULUS: &'static str =
+        "0x30644e72e131a029b85045b68181585d2833e84879b9709143e1f593f0000001";
+
+    /// `7^-t`
+    const ROOT_OF_UNITY_INV: Self = Fr([
+        0x05f05c05affb3d96,
+        0xb8e594ebfc3b5137,
+        0x60314620b85bc4c1,
+        0x2a4129bebb6fc591,
+    ]);
+
+    /// `7^(2^28)`
+    const DELTA: Self = Fr([
+        0x9a0c322befd78855,
+        0x46e82d14249b563c,
+        0x5983a663e0b0b7a7,
+        0x22ab452baaa111ad,
+    ]);
+
+    /// `(r + 1) / 2`
+    const TWO_INV: Self = Fr([
+        0x783c14d81ffffffe,
+        0xaf982f6f0c8d1edd,
+        0x8f5f7492fcfd4f45,
+        0x1f37631a3d9cbfac,
+    ]);
+
+    /// `7^((r - 1) / 3)`
+    const ZETA: Self = Fr([
+        0x93e7cede4a0329b3,
+        0x7d4fdca77a96c167,
+        0x8be4ba08b19a750a,
+        0x1cbd5653a5661c25,
+    ]);
+
+    fn from_u128(v: u128) -> Self {
+        let shift = Self::from(1u64 << 32).square();
+        Self::from((v >> 64) as u64) * shift + Self::from(v as u64)
+    }
+
+    fn from_bytes_wide(bytes: &[u8; 64]) -> Self {
+        bytes.iter().rev().fold(Self::zero(), |acc, byte| {
+            acc * Self::from(256u64) + Self::from(*byte as u64)
+        })
+    }
+
+    fn get_lower_128(&self) -> u128 {
+        let limbs = self.to_limbs();
+        limbs[0] as u128 | ((limbs[1] as u128) << 64)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_constants() {
+        let generator = Fr::multiplicative_generator();
+        assert_eq!(generator, Fr::from(7u64));
+
+        // root_of_unity has order exactly 2^S
+        let root = Fr::root_of_unity();
+        assert_eq!(root.pow_vartime([1u64 << Fr::S]), Fr::one());
+        assert_eq!(root.pow_vartime([1u64 << (Fr::S - 1)]), -Fr::one());
+        assert_eq!(root * Fr::ROOT_OF_UNITY_INV, Fr::one());
+
+        assert_eq!(Fr::DELTA, generator.pow_vartime([1u64 << Fr::S]));
+        assert_eq!(Fr::TWO_INV * Fr::from(2u64), Fr::one());
+        assert_ne!(Fr::ZETA, Fr::one());
+        assert_eq!(Fr::ZETA.pow_vartime([3u64]), Fr::one());
+
+        // generator^t is the root of unity, with t = 2 * T_MINUS1_OVER2 + 1
+        let t = generator.pow_vartime(Fr::T_MINUS1_OVER2).square() * generator;
+        assert_eq!(t, root);
+    }
+
+    #[test]
+    fn test_conversions() {
+        let v = u128::MAX - 12345;
+        assert_eq!(Fr::from_u128(v).get_lower_128(), v);
+        assert_eq!(Fr::from_u128(v).get_lower_32(), v as u32);
+
+        // 2^512 - 1 = (2^256 - 1) * (2^256 + 1)
+        let wide = Fr::from_bytes_wide(&[0xff; 64]);
+        let below = Fr::from_bytes_wide(&{
+            let mut bytes = [0; 64];
+            bytes[..32].copy_from_slice(&[0xff; 32]);
+            bytes
+        });
+        assert_eq!(wide, below * (below + Fr::from(2u64)));
+
+        // r wraps around to zero, r - 1 to -1
+        let modulus = hex_bytes(&<Fr as FieldExt>::MODULUS[2..]);
+        assert_eq!(Fr::from_bytes_wide(&modulus), Fr::zero());
+        let mut minus_one = modulus;
+        minus_one[0] -= 1;
+        assert_eq!(Fr::from_bytes_wide(&minus_one), -Fr::one());
+    }
+
+    #[test]
+    fn test_sqrt_ratio() {
+        let x = Fr::from(12345u64);
+        let (is_square, root) = Fr::sqrt_ratio(&x.square(), &Fr::one());
+        assert!(bool::from(is_square));
+        assert_eq!(root.square(), x.square());
+
+        // the generator is not a square
+        let (is_square, _) = Fr::sqrt_alt(&Fr::multiplicative_generator());
+        assert!(!bool::from(is_square));
+    }
+
+    // kzg hands our scalars to arkworks as bytes, which only works if both sides
+    // agree on the field
+    #[test]
+    fn test_same_field_as_ark() {
+        use ark_ff::{BigInteger, PrimeField as _};
+        use rand::rngs::OsRng;
+
+        use super::super::kzg::to_ark;
+
+        let modulus = hex_bytes(&<Fr as FieldExt>::MODULUS[2..]);
+        assert_eq!(ark_bn254::Fr::MODULUS.to_bytes_le(), modulus[..32]);
+
+        let from_ark = |x: ark_bn254::Fr| {
+            let mut repr = FrRepr::default();
+            repr.as_mut()
+                .copy_from_slice(&x.into_bigint().to_bytes_le());
+            Fr::from_repr(repr).unwrap()
+        };
+        for _ in 0..100 {
+            let (x, y) = (Fr::random(OsRng), Fr::random(OsRng));
+            assert_eq!(from_ark(to_ark(x)), x);
+            assert_eq!(to_ark(x * y), to_ark(x) * to_ark(y));
+            assert_eq!(to_ark(x + y), to_ark(x) + to_ark(y));
+        }
+    }
+
+    /// The 64 little-endian bytes of a big-endian hex string.
+    fn hex_bytes(hex: &str) -> [u8; 64] {
+        let mut bytes = [0; 64];
+        for (i, chunk) in hex.as_bytes().rchunks(2).enumerate() {
+            bytes[i] = u8::from_str_radix(std::str::from_utf8(chunk).unwrap(), 16).unwrap();
+        }
+        bytes
+    }
+}
@@ -0,0 +1,280 @@
+//! KZG polynomial commitments on BN254, as in the notes: commit to `f` as `[f(τ)]_1`, and
+//! prove `f(z) = y` with `[q(τ)]_1` for the quotient `q = (f - y) / (X - z)`, which only
+//! exists when `f(z) = y`. The verifier checks the division at `τ` with one pairing
+//! equation:
+//!
+//!     e([f(τ)]_1 - [y]_1, [1]_2) = e([q(τ)]_1, [τ]_2 - [z]_2)
+//!
+//! Scalars and polynomials are over our [`Fr`], so they can come straight out of a
+//! circuit; the curve points and the pairing are arkworks'.
+
+use ark_bn254::{Bn254, Fr as ArkFr, G1Affine, G1Projective, G2Affine};
+use ark_ec::{pairing::Pairing, AffineRepr, CurveGroup, VariableBaseMSM};
+use ark_ff::PrimeField as _;
+use ff::{Field, PrimeField};
+
+use super::fr::Fr;
+
+/// `f(point)`, for `f` given by its coefficients, lowest degree first.
+pub fn evaluate(poly: &[Fr], point: Fr) -> Fr {
+    poly.iter()
+        .rev()
+        .fold(Fr::zero(), |acc, coeff| acc * point + coeff)
+}
+
+/// `(f - f(z)) / (X - z)`, by synthetic division. The remainder `f(z)` is dropped.
+fn divide_by_linear(poly: &[Fr], point: Fr) -> Vec<Fr> {
+    let mut quotient = vec![Fr::zero(); poly.len().saturating_sub(1)];
+    let mut acc = Fr::zero();
+    for i in (1..poly.len()).rev() {
+        acc = acc * point + poly[i];
+        quotient[i - 1] = acc;
+    }
+    quotient
+}
+
+pub(super) fn to_ark(x: Fr) -> ArkFr {
+    ArkFr::from_le_bytes_mod_order(x.to_repr().as_ref())
+}
+
+/// Powers of `x`: `1, x, x^2, ..., x^(n-1)`.
+fn powers(x: Fr, n: usize) -> impl Iterator<Item = Fr> {
+    std::iter::successors(Some(Fr::one()), move |power| Some(*power * x)).take(n)
+}
+
+/// A claim `f(point) = value`, with its proof `[q(τ)]_1`.
+#[derive(Debug, Clone, Copy)]
+pub struct Opening {
+    pub point: Fr,
+    pub value: Fr,
+    pub proof: G1Affine,
+}
+
+/// Claims `f_i(point) = values[i]` for several polynomials, with one proof for all of
+/// them.
+#[derive(Debug, Clone)]
+pub struct BatchOpening {
+    pub point: Fr,
+    pub values: Vec<Fr>,
+    pub proof: G1Affine,
+}
+
+/// The structured reference string: `[τ^i]_1` for every degree up to the maximum, and
+/// `[1]_2, [τ]_2` for the verifier.
+#[derive(Debug, Clone)]
+pub struct Srs {
+    pub g1: Vec<G1Affine>,
+    pub g2: G2Affine,
+    pub tau_g2: G2Affine,
+}
+
+impl Srs {
+    /// The powers of a `τ` we know, for polynomials up to `max_degree`. Knowing `τ` lets
+    /// us open anything to any value, so this is only good for tests: a real setup is a
+    /// ceremony that throws `τ` away.
+    pub fn setup(tau: Fr, max_degree: usize) -> Self {
+        let g1 = powers(tau, max_degree + 1)
+            .map(|power| (G1Affine::generator() * to_ark(power)).into_affine())
+            .collect();
+        Self {
+            g1,
+            g2: G2Affine::generator(),
+            tau_g2: (G2Affine::generator() * to_ark(tau)).into_affine(),
+        }
+    }
+
+    pub fn max_degree(&self) -> usize {
+        self.g1.len() - 1
+    }
+
+    /// `sum_i c_i [τ^i]_1 = [f(τ)]_1`, without ever knowing `τ`.
+    pub fn commit(&self, poly: &[Fr]) -> G1Affine {
+        assert!(
+            poly.len() <= self.g1.len(),
+            "degree {} is larger than the setup's {}",
+            poly.len() - 1,
+            self.max_degree()
+        );
+        let scalars: Vec<_> = poly.iter().copied().map(to_ark).collect();
+        G1Projective::msm(&self.g1[..poly.len()], &scalars)
+            .unwrap()
+            .into_affine()
+    }
+
+    pub fn open(&self, poly: &[Fr], point: Fr) -> Opening {
+        Opening {
+            point,
+            value: evaluate(poly, point),
+            proof: self.commit(&divide_by_linear(poly, point)),
+        }
+    }
+
+    pub fn verify(&self, commitment: &G1Affine, opening: &Opening) -> bool {
+        let lhs = commitment.into_group() - G1Affine::generator() * to_ark(opening.value);
+        let rhs = self.tau_g2.into_group() - self.g2 * to_ark(opening.point);
+        Bn254::pairing(lhs, self.g2) == Bn254::pairing(opening.proof, rhs)
+    }
+
+    /// Opens several polynomials at the same point with one proof, for the combination
+    /// `sum_i γ^i f_i`: its quotient is `sum_i γ^i q_i`. `γ` is the verifier's random
+    /// challenge, so the prover can't make the errors of the single claims cancel out.
+    pub fn open_batch(&self, polys: &[&[Fr]], point: Fr, gamma: Fr) -> BatchOpening {
+        let len = polys.iter().map(|poly| poly.len()).max().unwrap_or(0);
+        let mut combined = vec![Fr::zero(); len];
+        for (poly, power) in polys.iter().zip(powers(gamma, polys.len())) {
+            for (acc, coeff) in combined.iter_mut().zip(poly.iter()) {
+                *acc += power * coeff;
+            }
+        }
+
+        BatchOpening {
+            point,
+            values: polys.iter().map(|poly| evaluate(poly, point)).collect(),
+            proof: self.commit(&divide_by_linear(&combined, point)),
+        }
+    }
+
+    /// Commitments are linear, so the combined commitment and value are computed from the
+    /// single ones and checked like one opening.
+    pub fn verify_batch(
+        &self,
+        commitments: &[G1Affine],
+        opening: &BatchOpening,
+        gamma: Fr,
+    ) -> bool {
+        assert_eq!(commitments.len(), opening.values.len());
+        let scale = |(point, power): (&G1Affine, Fr)| *point * to_ark(power);
+        let commitment: G1Projective = commitments
+            .iter()
+            .zip(powers(gamma, commitments.len()))
+            .map(scale)
+            .sum();
+        let value = opening
+            .values
+            .iter()
+            .zip(powers(gamma, opening.values.len()))
+            .map(|(value, power)| *value * power)
+            .sum();
+
+        let combined = Opening {
+            point: opening.point,
+            value,
+            proof: opening.proof,
+        };
+        self.verify(&commitment.into_affine(), &combined)
+    }
+
+    /// Checks openings at different points with two pairings instead of two each. Every
+    /// single check rearranges to
+    ///
+    ///     e(C_i - [y_i]_1 + z_i π_i, [1]_2) = e(π_i, [τ]_2)
+    ///
+    /// and a random combination with powers of `r` adds them up on both sides.
+    pub fn verify_multi(&self, claims: &[(G1Affine, Opening)], r: Fr) -> bool {
+        let mut lhs = G1Projective::default();
+        let mut rhs = G1Projective::default();
+        for ((commitment, opening), power) in claims.iter().zip(powers(r, claims.len())) {
+            let power = to_ark(power);
+            let proof = opening.proof.into_group();
+            lhs += (commitment.into_group() - G1Affine::generator() * to_ark(opening.value)
+                + proof * to_ark(opening.point))
+                * power;
+            rhs += proof * power;
+        }
+        Bn254::pairing(lhs, self.g2) == Bn254::pairing(rhs, self.tau_g2)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rand::rngs::OsRng;
+
+    use super::*;
+
+    fn random_poly(degree: usize) -> Vec<Fr> {
+        (0..=degree).map(|_| Fr::random(OsRng)).collect()
+    }
+
+    #[test]
+    fn test_commitment_is_evaluation_at_tau() {
+        let tau = Fr::random(OsRng);
+        let srs = Srs::setup(tau, 15);
+        let poly = random_poly(15);
+
+        let expected = (G1Affine::generator() * to_ark(evaluate(&poly, tau))).into_affine();
+        assert_eq!(srs.commit(&poly), expected);
+    }
+
+    #[test]
+    fn test_open() {
+        let srs = Srs::setup(Fr::random(OsRng), 15);
+        let poly = random_poly(15);
+        let commitment = srs.commit(&poly);
+
+        let opening = srs.open(&poly, Fr::random(OsRng));
+        assert!(srs.verify(&commitment, &opening));
+
+        let wrong_value = Opening {
+            value: opening.value + Fr::one(),
+            ..opening
+        };
+        let wrong_point = Opening {
+            point: opening.point + Fr::one(),
+            ..opening
+        };
+        assert!(!srs.verify(&commitment, &wrong_value));
+        assert!(!srs.verify(&commitment, &wrong_point));
+        assert!(!srs.verify(&srs.commit(&random_poly(15)), &opening));
+    }
+
+    #[test]
+    fn test_open_at_root() {
+        // f = (X - 3)(X + 1) = X^2 - 2X - 3 vanishes at 3
+        let srs = Srs::setup(Fr::random(OsRng), 4);
+        let poly = [-Fr::from(3u64), -Fr::from(2u64), Fr::one()];
+
+        let opening = srs.open(&poly, Fr::from(3u64));
+        assert_eq!(opening.value, Fr::zero());
+        // the proof commits to q = X + 1
+        assert_eq!(opening.proof, srs.commit(&[Fr::one(), Fr::one()]));
+        assert!(srs.verify(&srs.commit(&poly), &opening));
+    }
+
+    #[test]
+    fn test_batch_same_point() {
+        let srs = Srs::setup(Fr::random(OsRng), 15);
+        let polys = [random_poly(15), random_poly(7), random_poly(0)];
+        let polys: Vec<&[Fr]> = polys.iter().map(|poly| poly.as_slice()).collect();
+        let commitments: Vec<_> = polys.iter().map(|poly| srs.commit(poly)).collect();
+        let gamma = Fr::random(OsRng);
+
+        let mut opening = srs.open_batch(&polys, Fr::random(OsRng), gamma);
+        assert!(srs.verify_batch(&commitments, &opening, gamma));
+
+        opening.values[1] += Fr::one();
+        assert!(!srs.verify_batch(&commitments, &opening, gamma));
+    }
+
+    #[test]
+    fn test_multi_point() {
+        let srs = Srs::setup(Fr::random(OsRng), 15);
+        let mut claims: Vec<_> = (0..4)
+            .map(|_| {
+                let poly = random_poly(15);
+                (srs.commit(&poly), srs.open(&poly, Fr::random(OsRng)))
+            })
+            .collect();
+        let r = Fr::random(OsRng);
+        assert!(srs.verify_multi(&claims, r));
+
+        claims[2].1.value += Fr::one();
+        assert!(!srs.verify_multi(&claims, r));
+    }
+
+    #[test]
+    #[should_panic(expected = "larger than the setup")]
+    fn test_degree_bound() {
+        let srs = Srs::setup(Fr::random(OsRng), 7);
+        let _ = srs.commit(&random_poly(8));
+    }
+}
@@ -0,0 +1,112 @@
+//! Prints the cost of every example circuit in the crate.
+//!
+//! cargo run --bin circuit_stats              # all circuits
+//! cargo run --bin circuit_stats -- ex2       # only circuits whose name contains "ex2"
+
+use fibonacci::{
+    bitwise, bn254, decompose_range_check, dev_tools::stats::CircuitStats, ecc, merkle, mimc,
+    poseidon, range_check, schnorr, sha256,
+};
+use halo2_proofs::{
+    circuit::Value,
+    pasta::{pallas, Fp},
+    plonk::Error,
+};
+
+type Measure = Box<dyn Fn() -> Result<CircuitStats, Error>>;
+
+fn main() -> Result<(), Error> {
+    let filter = std::env::args().nth(1).unwrap_or_default();
+
+    let circuits: Vec<(&str, Measure)> = vec![
+        (
+            "range_check::ex1 (RANGE = 8)",
+            Box::new(|| CircuitStats::measure(&range_check::ex1::MyCircuit::<Fp, 8>::default())),
+        ),
+        (
+            "range_check::ex2 (RANGE = 8, LOOKUP_RANGE = 256)",
+            Box::new(|| {
+                CircuitStats::measure(&range_check::ex2::MyCircuit::<Fp, 8, 256>::default())
+            }),
+        ),
+        (
+            "range_check::ex3 (NUM_BITS = 8, RANGE = 256)",
+            Box::new(|| {
+                CircuitStats::measure(&range_check::ex3::MyCircuit::<Fp, 8, 256>::default())
+            }),
+        ),
+        (
+            "decompose_range_check::ex1 (64 bits, LOOKUP_RANGE = 256)",
+            Box::new(|| {
+                CircuitStats::measure(&decompose_range_check::ex1::MyCircuit::<Fp, 256> {
+                    value: Value::unknown(),
+                    num_bits: 64,
+                })
+            }),
+        ),
+        (
+            "bitwise (64-bit XOR)",
+            Box::new(|| {
+                CircuitStats::measure(&bitwise::MyCircuit::<Fp, 64>::new(
+                    bitwise::BitOp::Xor,
+                    0,
+                    0,
+                ))
+            }),
+        ),
+        (
+            "bn254::fq (non-native (a + b) * b = c)",
+            Box::new(|| CircuitStats::measure(&bn254::fq::MyCircuit::<Fp>::default())),
+        ),
+        (
+            "ecc (fixed-base scalar mul on Pallas)",
+            Box::new(|| CircuitStats::measure(&ecc::MyCircuit::<pallas::Affine>::default())),
+        ),
+        (
+            "ecc::windowed (variable-base mul, double-and-add)",
+            Box::new(|| {
+                CircuitStats::measure(&ecc::windowed::MyCircuit::<pallas::Affine> {
+                    point: Value::unknown(),
+                    scalar: Value::unknown(),
+                    method: ecc::windowed::Method::DoubleAndAdd,
+                })
+            }),
+        ),
+        (
+            "ecc::windowed (variable-base mul, 2-bit windows)",
+            Box::new(|| {
+                CircuitStats::measure(&ecc::windowed::MyCircuit::<pallas::Affine> {
+                    point: Value::unknown(),
+                    scalar: Value::unknown(),
+                    method: ecc::windowed::Method::Windowed,
+                })
+            }),
+        ),
+        (
+            "poseidon (2-element message)",
+            Box::new(|| CircuitStats::measure(&poseidon::MyCircuit::<Fp, 2>::default())),
+        ),
+        (
+            "mimc (2-element message)",
+            Box::new(|| CircuitStats::measure(&mimc::MyCircuit::<Fp, 2>::default())),
+        ),
+        (
+            "merkle (depth 8)",
+            Box::new(|| CircuitStats::measure(&merkle::MyCircuit::<Fp, 8>::default())),
+        ),
+        (
+            "schnorr (Pallas, Poseidon challenge)",
+            Box::new(|| CircuitStats::measure(&schnorr::MyCircuit::default())),
+        ),
+        (
+            "sha256 (1 block)",
+            Box::new(|| CircuitStats::measure(&sha256::MyCircuit::<Fp, 1>::default())),
+        ),
+    ];
+
+    for (name, measure) in circuits.iter().filter(|(name, _)| name.contains(&filter)) {
+        println!("== {} ==\n{}\n", name, measure()?);
+    }
+
+    Ok(())
+}
@@ -1 +1 @@
-mod ex1;
\ No newline at end of file
+pub mod ex1;
@@ -1,131 +1,143 @@
+use std::marker::PhantomData;
 
-
+use ff::PrimeFieldBits;
 use halo2_proofs::{
     arithmetic::FieldExt,
-    circuit::{AssignedCell, Layouter, Value},
-    plonk::{
-        Advice, Assigned, Column, ConstraintSystem, Constraints, Error, Expression, Selector,
-        TableColumn,
-    },
+    circuit::{floor_planner::V1, AssignedCell, Layouter, Value},
+    plonk::{Advice, Assigned, Circuit, Column, ConstraintSystem, Error, Expression, Selector},
     poly::Rotation,
 };
 
 mod table;
 
-use table::*
-
+use table::*;
+
+/// This gadget range-constrains an element witnessed in the circuit to be `num_bits` bits.
+///
+/// Given an element `value`, we use a running sum to break it into K-bit chunks,
+/// where K = log2(LOOKUP_RANGE), and look every chunk up in a K-bit table.
+///
+///     value = c_0 + 2^K * c_1  + 2^{2K} * c_2 + ... + 2^{(C-1)K} * c_{C-1}
+///
+/// z_0 = value, z_{i+1} = (z_i - c_i) * 2^{-K}, and the last term z_C must be 0.
+///
+///     | running_sum |  q_decompose  |  table_value  |
+///     -----------------------------------------------
+///     |     z_0     |       1       |       0       |
+///     |     z_1     |       1       |       1       |
+///     |     ...     |      ...      |      ...      |
+///     |   z_{C-1}   |       1       |      ...      |
+///     |     z_C     |       0       |      ...      |
+///
+#[derive(Debug, Clone)]
 pub struct DecomposeConfig<F: FieldExt, const LOOKUP_RANGE: usize> {
     running_sum: Column<Advice>,
     q_decompose: Selector,
-    table: RangeTableConfig<F, LOOKUP_RANGE>,
-    _marker: std::marker::PhantomData<F>,
+    table: RangeCheckTable<F, LOOKUP_RANGE>,
+    _marker: PhantomData<F>,
 }
 
-impl<F: FieldExt, const LOOKUP_RANGE: usize> DecomposeConfig<F, LOOKUP_RANGE> {
+impl<F: FieldExt + PrimeFieldBits, const LOOKUP_RANGE: usize> DecomposeConfig<F, LOOKUP_RANGE> {
+    /// number of bits in one chunk of the running sum
+    const LOOKUP_NUM_BITS: usize = LOOKUP_RANGE.trailing_zeros() as usize;
+
     pub fn configure(
         meta: &mut ConstraintSystem<F>,
     ) -> Self {
+        assert!(LOOKUP_RANGE.is_power_of_two());
+
         let running_sum = meta.advice_column();
         let q_decompose = meta.complex_selector();
 
-        let table = RangeTableConfig::configure(meta);
-        
+        //we need to fix a column for constraint constant step used to enforce z_C == 0;
+        let constant = meta.fixed_column();
+        meta.enable_constant(constant);
+        //similarily we need to enable 'running sum' to participate in the parmutation
+        meta.enable_equality(running_sum);
+
+        let table = RangeCheckTable::configure(meta);
+
         meta.lookup(|meta| {
             let q_decompose = meta.query_selector(q_decompose);
             let z_curr = meta.query_advice(running_sum, Rotation::cur());
             let z_next = meta.query_advice(running_sum, Rotation::next());
 
-            //we need to fix a column for constraint constant step used to enforce z_C == 0;
-            let constant = meta.fixed_column();
-            meta.enable_contant(constant);
-            //similarily we need to enable 'running sum' to participate in the parmutation 
-            meta.enable_equality(running_sum);
-
-            let lookup_num_bits = 
-                log2_ceil(LOOKUP_RANGE as u64);
-            let chunk = z_curr - z_next * Expression::Constant(F::from_u64(1u64<< lookup_num_bits));
-
-            let not_q_decompose = Expression::Constant(F::one()) - q_decompose.clone();
-            let default_chunk = Expression::Constant(F::zero());
-
-            let expr = not_q_decompose * default_chunk + q_decompose * chunk;
+            // c_i = z_i - 2^K * z_{i+1}
+            let chunk = z_curr - z_next * Expression::Constant(F::from(1u64 << Self::LOOKUP_NUM_BITS));
 
+            // when q_decompose = 0 we look up 0, which is always in the table
             vec![
-                (q_decompose * chunk, table.value) 
+                (q_decompose * chunk, table.value)
             ]
-        })
+        });
 
         Self {
             running_sum,
             q_decompose,
             table,
-            _marker: std::marker::PhantomData,
+            _marker: PhantomData,
         }
-        
+
+    }
+
+    pub fn load_table(&self, layouter: &mut impl Layouter<F>) -> Result<(), Error> {
+        self.table.load(layouter)
     }
 
-    fn assign(
+    /// Constrains the cell to `num_bits` bits. Works for plain `F` cells as well as
+    /// `Assigned<F>` ones, so other chips can range-check their own cells with it.
+    pub fn assign<V>(
         &self,
         mut layouter: impl Layouter<F>,
         // this is assigned cell not normal value, this means this value is used before
-        value: AssignedCell<Assigned<F>, F>,
+        value: AssignedCell<V, F>,
         num_bits: usize,
-    ) -> Result< (), Error> {
+    ) -> Result< (), Error>
+    where
+        V: Clone,
+        for<'v> Assigned<F>: From<&'v V>,
+    {
+        assert_eq!(num_bits % Self::LOOKUP_NUM_BITS, 0);
+        let num_chunks = num_bits / Self::LOOKUP_NUM_BITS;
+
         layouter.assign_region(|| "Decompose value", |mut region| {
             let mut offset = 0;
 
             // 0. copy in the witness value
-            let mut z= value.copy_advice(|| "copy value to init running sum", 
-                &mut region, 
-                self.running_sum, 
-                offset)?;
+            let mut z= value.copy_advice(|| "copy value to init running sum",
+                &mut region,
+                self.running_sum,
+                offset)?.cell();
+            offset += 1;
 
             //1 compute the interstitial running sum values(z_1, z_2, ..., z_C)
             // transpose: ->  Value<Vec<Assigned<F>> -> Vec<Value<Assigned<F>>
-            let lookup_num_bits = log2_ceil(LOOKUP_RANGE as u64 );
-            let running_sum = value.value().map(|&v| compute_running_sum(v, num_bits, lookup_num_bits)).transpose_vec(num_bits/lookup_num_bits);
+            let running_sum = value
+                .value()
+                .map(|v| compute_running_sum(Assigned::from(v), num_bits, Self::LOOKUP_NUM_BITS))
+                .transpose_vec(num_chunks);
 
             //2 assign the running sum values
             for z_i in running_sum.into_iter() {
-                z = region.assign_advice(|| format!("assign z_{}", offset), self.running_sum, offset, || z_i)?;
-                offset += 1;    
+                z = region.assign_advice(|| format!("assign z_{}", offset), self.running_sum, offset, || z_i)?.cell();
+                offset += 1;
             }
 
             //3. enable selector on each row of the running sum
-            for row in (0..(num_bits/lookup_num_bits)) {
+            for row in 0..num_chunks {
                 self.q_decompose.enable(&mut region, row)?;
             }
 
             //4. constrain the final rumnning sum 'z_c' == 0
-            ///constrain constant: assume that the circuit has a fixed column available where we can witness `constant`.
-            /// Returns an error if the cell is in a column where equality has not been enabled.
-            /// 
-            region.constrain_contstant(z_i.cell(), F::zero());
-
-
-
-
-
+            //constrain constant: assume that the circuit has a fixed column available where we can witness `constant`.
+            // Returns an error if the cell is in a column where equality has not been enabled.
+            region.constrain_constant(z, F::zero())
         })
     }
 
 
 }
 
-
-fn compute_running_sum<F: FieldExt + PrimeFieldBits, const LOOKUP_NUM_BITS: usize> (
-    value: Assigned<F>,
-    num_bits: usize,
-) -> Vec<Assigned<F>> {
-
-}
-
-#[test]
-
-fn test_here(){
-    println!("Hello, world!");
-}
-
 fn lebs2ip(bits: &[bool]) -> u64 {
     assert!(bits.len() <= 64);
     bits.iter()
@@ -138,7 +150,7 @@ fn compute_running_sum<F: FieldExt + PrimeFieldBits>(
     value: Assigned<F>,
     num_bits: usize,
     lookup_num_bits: usize,
-) -> Vec<Assigned<F>> {  
+) -> Vec<Assigned<F>> {
     let mut running_sum = vec![];
     let mut z = value;
 
@@ -150,112 +162,151 @@ fn compute_running_sum<F: FieldExt + PrimeFieldBits>(
         .by_vals()
         .take(num_bits)
         .collect();
-    for chunk in value.chunks(LOOKUP_NUM_BITS) {
+    for chunk in value.chunks(lookup_num_bits) {
         let chunk = Assigned::from(F::from(lebs2ip(chunk)));
         // z_{i+1} = (z_i - c_i) * 2^{-K}:
-        z = (z - chunk) * Assigned::from(F::from(1u64 << LOOKUP_NUM_BITS)).invert();
+        z = (z - chunk) * Assigned::from(F::from(1u64 << lookup_num_bits)).invert();
         running_sum.push(z);
     }
 
-    assert_eq!(running_sum.len(), num_bits / LOOKUP_NUM_BITS);
+    assert_eq!(running_sum.len(), num_bits / lookup_num_bits);
     running_sum
 }
 
 
-#[cfg(test)]
-mod tests{
-    use halo2_proofs::{circuit::floor_planner::V1, dev::MockProver, pasta::Fp};
-    use rand;
+/// #derive[Default] should only be used when the circuit is having witness
+/// values in the input. But if some structural value like 'num_bits' is
+/// there then it makes sense to have a custom constructor
 
-    use super::*;
-
-    /// #derive[Default] should only be used when the circuit is having witness
-    /// values in the input. But if some structural value like 'num_bits' is
-    /// there then it makes sense to have a custom constructor
+pub struct MyCircuit<F:FieldExt, const RANGE: usize> {
+    pub value: Value<Assigned<F>>,
+    pub num_bits: usize,
+}
 
-    struct MyCircuit<F:FieldExt, const  NUM_BITS: usize, const RANGE: usize> {
-        value: Value<Assigned<F>>,
-        num_bits: usize,
-    }
-    
 
-    impl<F: FieldExtm + PrimeFieldBits, const NUM_BITS: usize, const RANGE: usize>
-         Circuit<F> for MyCircuit<F, NUM_BITS, RANGE> 
-    {
+impl<F: FieldExt + PrimeFieldBits, const RANGE: usize>
+     Circuit<F> for MyCircuit<F, RANGE>
+{
 
-        type Config = DecomposeConfig<F, NUM_BITS, RANGE>;
-        ///Halo2 has two floor planners
-        /// simple floor planner: single pass floor planner, it lays out regions as you go one
-        /// V1: dual pass floor planner, onece to select region shapes and sencond time to slide thoseregions around
-        /// 
+    type Config = DecomposeConfig<F, RANGE>;
+    ///Halo2 has two floor planners
+    /// simple floor planner: single pass floor planner, it lays out regions as you go one
+    /// V1: dual pass floor planner, onece to select region shapes and sencond time to slide thoseregions around
+    ///
 
-        type FloorPlanner = V1;
+    type FloorPlanner = V1;
 
-        /// Why we have without_witnesses()?
-        /// we use the circuit with out witness in the first pass of the layouter
-        /// only shapes are relevant at that time not the witness values
+    /// Why we have without_witnesses()?
+    /// we use the circuit with out witness in the first pass of the layouter
+    /// only shapes are relevant at that time not the witness values
 
-        fn without_witnesses(&self) -> Self {
-            Self {
-                value: None,
-                num_bits: self.num_bits, //in default it will be zero
-            }
+    fn without_witnesses(&self) -> Self {
+        Self {
+            value: Value::unknown(),
+            num_bits: self.num_bits, //in default it will be zero
         }
+    }
 
-        fn configure(meta: &mut ConstraintSystem<F>) -> Self::Config {
-            DecomposeConfig::configure(meta)
-        }
+    fn configure(meta: &mut ConstraintSystem<F>) -> Self::Config {
+        DecomposeConfig::configure(meta)
+    }
 
-        ///what to do with the values in the circuit
-        fn synthesize(
-            &self,
-            config: Self::Config,
-            mut layouter: impl Layouter<F>,
-        ) -> Result<(), Error> {
-            config.table.load(&mut layouter)?;
-
-            let value = layouter.assign_region(
-                || "witness value",
-                |mut region| {
-                    region.assign_advice(
-                        || "witness value",
-                        config.running_sum,
-                        0,
-                        self.value
-                    )
-                }
-            )
-
-            config.assign(
-                layouter.namespace(|| "decompose"),
-                value,
-                self.num_bits,
-            )?;
-
-            Ok(())
-        }
+    ///what to do with the values in the circuit
+    fn synthesize(
+        &self,
+        config: Self::Config,
+        mut layouter: impl Layouter<F>,
+    ) -> Result<(), Error> {
+        config.table.load(&mut layouter)?;
+
+        let value = layouter.assign_region(
+            || "witness value",
+            |mut region| {
+                region.assign_advice(
+                    || "witness value",
+                    config.running_sum,
+                    0,
+                    || self.value
+                )
+            }
+        )?;
+
+        config.assign(
+            layouter.namespace(|| "decompose"),
+            value,
+            self.num_bits,
+        )?;
 
-        
+        Ok(())
     }
-}
 
 
-#[test]
+}
+
 
-fn test_decompose_1() {
-    let k = 9;
-    const NUM_BITS: usize = 8;
-    const RANGE: usize = 256; // 8-bit value
+#[cfg(test)]
+mod tests{
+    use halo2_proofs::pasta::Fp;
 
-    // Random u64 value
-    let value: u64 = rand::random();
-    let value = Value::known(Assigned::from(Fp::from(value)));
+    use proptest::prelude::*;
 
-    let circuit = MyCircuit::<Fp, NUM_BITS, RANGE> {
-        value,
-        num_bits: 64,
+    use super::*;
+    use crate::dev_tools::{
+        failure::{assert_satisfied, verify},
+        min_k::min_k,
+        strategies::{field_element, near_power_of_two, to_u128},
     };
 
-    let prover = MockProver::run(k, &circuit, vec![]).unwrap();
-    prover.assert_satisfied();
+    #[test]
+    fn test_decompose_1() {
+        const RANGE: usize = 256; // 8-bit chunks
+
+        // Random u64 value
+        let value: u64 = rand::random();
+        let value = Value::known(Assigned::from(Fp::from(value)));
+
+        let circuit = MyCircuit::<Fp, RANGE> {
+            value,
+            num_bits: 64,
+        };
+
+        let k = min_k(&circuit);
+        assert_satisfied(k, &circuit, vec![]);
+    }
+
+    proptest! {
+        #![proptest_config(ProptestConfig::with_cases(64))]
+
+        // the running sum only reaches z_C = 0 when the value fits in `num_bits` bits
+        #[test]
+        fn prop_decompose(
+            num_bits in prop::sample::select(vec![8usize, 16, 64, 128]),
+            value in prop_oneof![
+                field_element::<Fp>(),
+                prop::sample::select(vec![8u64, 16, 64, 128])
+                    .prop_flat_map(|bits| near_power_of_two::<Fp>(bits, 2)),
+            ],
+        ) {
+            let circuit = MyCircuit::<Fp, 256> {
+                value: Value::known(value.into()),
+                num_bits,
+            };
+            let k = min_k(&circuit);
+
+            let in_range = to_u128(value).map_or(false, |v| num_bits == 128 || v >> num_bits == 0);
+            prop_assert_eq!(verify(k, &circuit, vec![]).is_ok(), in_range);
+        }
+    }
+
+    #[cfg(feature = "dev-graph")]
+    #[test]
+    fn print_decompose_1() {
+        use crate::dev_tools::graph::render_all;
+
+        let circuit = MyCircuit::<Fp, 256> {
+            value: Value::unknown(),
+            num_bits: 64,
+        };
+        render_all("decompose-1", min_k(&circuit), &circuit);
+    }
 }
@@ -1,4 +1,4 @@
-use std::{marker::PhantomData, os::unix::raw::off_t};
+use std::marker::PhantomData;
 
 use halo2_proofs::{plonk::{Error, TableColumn, ConstraintSystem}, arithmetic::FieldExt, circuit::{Layouter, Value}};
 
@@ -32,11 +32,13 @@ pub(super) struct RangeCheckTable<F: FieldExt, const RANGE: usize> {
         layouter.assign_table(|| "load range-check table", |mut table| {
             let mut offset = 0;
             //for some NUM BITS we want to load all the values into the row
+            //every chunk of the running sum is looked up here, so the table holds
+            //the chunks themselves (not their squares as in range_check::ex2)
             for i in 0..(RANGE) {
-                table.assign_cell(|| "assign cell", self.value, offset, || Value::known(F::from((i*i) as u64)))?;
+                table.assign_cell(|| "assign cell", self.value, offset, || Value::known(F::from(i as u64)))?;
                 offset += 1;
             }
             Ok(())
         })
     }
- }
\ No newline at end of file
+ }
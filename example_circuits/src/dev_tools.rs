@@ -0,0 +1,13 @@
+//! Helpers for inspecting the example circuits while we learn how they are laid out.
+
+pub mod failure;
+#[cfg(feature = "dev-graph")]
+pub mod graph;
+pub mod min_k;
+pub mod permutation;
+pub mod prover;
+mod recorder;
+pub mod stats;
+#[cfg(test)]
+pub(crate) mod strategies;
+pub mod transcript;
@@ -0,0 +1,365 @@
+use std::fmt;
+
+use halo2_proofs::{
+    arithmetic::FieldExt,
+    dev::{metadata, FailureLocation, MockProver, VerifyFailure},
+    plonk::{Circuit, ConstraintSystem, Expression, Selector},
+};
+
+use super::{
+    min_k::min_k,
+    recorder::{cell_key, format_expression, format_value, Recorder},
+};
+
+/// `MockProver` failures grouped by the gate constraint, lookup or permutation that
+/// failed, with the cell values at every failing location and the likely causes.
+///
+/// ```text
+/// Constraint 0 ('range_check') in gate 0 ('Range check'): 1 failure
+///   polynomial: Selector(0, true) * A0@0 * (0 + -A0@0) * ...
+///   - in Region 0 ('Assign value') at offset 0 (row 0)
+///       Column('Advice', 0)@0 = 0x8
+///       evaluates to 0x4ec00
+///   likely causes:
+///     the values assigned here do not satisfy the gate ...
+/// ```
+#[derive(Debug)]
+pub struct FailureReport {
+    pub groups: Vec<FailureGroup>,
+}
+
+#[derive(Debug)]
+pub struct FailureGroup {
+    /// The constraint, lookup or permutation, as `MockProver` names it.
+    pub title: String,
+    /// The failing constraint, or the lookup inputs, as a polynomial over the queried cells.
+    pub polynomial: Option<String>,
+    /// One entry per failing location.
+    pub failures: Vec<String>,
+    pub hints: Vec<&'static str>,
+}
+
+/// Runs `MockProver` and explains its failures.
+///
+/// Panics if `MockProver` can't run the circuit at all, e.g. when `k` is too small.
+pub fn verify<F: FieldExt, C: Circuit<F>>(
+    k: u32,
+    circuit: &C,
+    instance: Vec<Vec<F>>,
+) -> Result<(), FailureReport> {
+    let prover = MockProver::run(k, circuit, instance.clone()).unwrap_or_else(|err| {
+        panic!(
+            "MockProver could not run the circuit with k = {}: {:?} (the circuit needs k >= {})",
+            k,
+            err,
+            min_k(circuit)
+        )
+    });
+    prover
+        .verify()
+        .map_err(|failures| FailureReport::new(k, circuit, instance, &failures))
+}
+
+/// A drop-in replacement for `MockProver::assert_satisfied` that panics with a
+/// [`FailureReport`] instead of the raw `VerifyFailure`s.
+pub fn assert_satisfied<F: FieldExt, C: Circuit<F>>(k: u32, circuit: &C, instance: Vec<Vec<F>>) {
+    if let Err(report) = verify(k, circuit, instance) {
+        panic!("{}", report);
+    }
+}
+
+impl FailureReport {
+    pub fn new<F: FieldExt, C: Circuit<F>>(
+        k: u32,
+        circuit: &C,
+        instance: Vec<Vec<F>>,
+        failures: &[VerifyFailure],
+    ) -> Self {
+        let (recorder, cs) =
+            Recorder::record(k, circuit, instance).expect("circuit should synthesize");
+
+        let mut report = FailureReport { groups: vec![] };
+        for failure in failures {
+            report.add(&recorder, &cs, failure);
+        }
+        report
+    }
+
+    fn group(&mut self, title: String, polynomial: Option<String>) -> &mut FailureGroup {
+        if let Some(i) = self.groups.iter().position(|group| group.title == title) {
+            return &mut self.groups[i];
+        }
+        self.groups.push(FailureGroup {
+            title,
+            polynomial,
+            failures: vec![],
+            hints: vec![],
+        });
+        self.groups.last_mut().unwrap()
+    }
+
+    fn add<F: FieldExt>(
+        &mut self,
+        recorder: &Recorder<F>,
+        cs: &ConstraintSystem<F>,
+        failure: &VerifyFailure,
+    ) {
+        let (title, polynomial, lines, hint) = match failure {
+            VerifyFailure::ConstraintNotSatisfied {
+                constraint,
+                location,
+                cell_values,
+            } => {
+                let poly = find_constraint(cs, constraint)
+                    .map(|(gate, i)| &cs.gates()[gate].polynomials()[i]);
+                let row = find_row(recorder, location);
+
+                let mut lines = vec![format_location(location, row)];
+                for (cell, value) in cell_values {
+                    lines.push(format!("    {} = {}", cell, value));
+                }
+                if let (Some(poly), Some(row)) = (poly, row) {
+                    if let Some(value) = recorder.evaluate(poly, row) {
+                        lines.push(format!("    evaluates to {}", format_value(value)));
+                    }
+                }
+
+                let hint = match location {
+                    FailureLocation::OutsideRegion { .. } => {
+                        "the gate is active on a row outside every region: a selector is \
+                         enabled on a row where nothing was assigned"
+                    }
+                    FailureLocation::InRegion { .. } => {
+                        "the values assigned here do not satisfy the gate: check the witness, \
+                         or whether the gate's selector should be enabled on this row at all"
+                    }
+                };
+                (
+                    constraint.to_string(),
+                    poly.map(format_expression),
+                    lines,
+                    hint,
+                )
+            }
+            VerifyFailure::CellNotAssigned {
+                gate,
+                region,
+                gate_offset,
+                column,
+                offset,
+            } => (
+                gate.to_string(),
+                None,
+                vec![format!(
+                    "in {}, the gate enabled at offset {} queries {} at offset {}, which is not assigned",
+                    region, gate_offset, column, offset
+                )],
+                "the selector is enabled on a row where the gate reaches a cell the region \
+                 never assigns (e.g. Rotation::next() on the last row): assign it, or move \
+                 the selector",
+            ),
+            VerifyFailure::ConstraintPoisoned { constraint } => (
+                constraint.to_string(),
+                None,
+                vec!["the constraint was evaluated on a poisoned (unusable) row".to_string()],
+                "a gate is enabled on one of the last rows, which are reserved for blinding: \
+                 increase k",
+            ),
+            VerifyFailure::Lookup {
+                lookup_index,
+                location,
+            } => {
+                let lookup = &cs.lookups()[*lookup_index];
+                let row = find_row(recorder, location);
+
+                let mut lines = vec![format_location(location, row)];
+                let mut hint = "the input is not a row of the table: the value is out of \
+                                range, or the table is missing that row (is it loaded?)";
+                if let Some(row) = row {
+                    let inputs: Vec<_> = lookup
+                        .input_expressions()
+                        .iter()
+                        .map(|expr| {
+                            recorder
+                                .evaluate(expr, row)
+                                .map_or("unassigned".to_string(), format_value)
+                        })
+                        .collect();
+                    lines.push(format!("    input = ({}) is not in the table", inputs.join(", ")));
+
+                    let selectors: Vec<_> = lookup
+                        .input_expressions()
+                        .iter()
+                        .flat_map(queried_selectors)
+                        .collect();
+                    if !selectors.is_empty()
+                        && selectors
+                            .iter()
+                            .all(|selector| !recorder.selector_enabled(selector, row))
+                    {
+                        hint = "no selector of this lookup is enabled on this row, so the \
+                                default input is looked up: it must be a row of the table too";
+                    }
+                }
+
+                let inputs: Vec<_> = lookup
+                    .input_expressions()
+                    .iter()
+                    .map(format_expression)
+                    .collect();
+                (
+                    format!("Lookup {}", lookup_index),
+                    Some(format!("({})", inputs.join(", "))),
+                    lines,
+                    hint,
+                )
+            }
+            VerifyFailure::Permutation { column, location } => {
+                let row = find_row(recorder, location);
+                let mut lines = vec![format!("{} {}", column, format_location(location, row))];
+
+                let equality_column = cs
+                    .permutation()
+                    .get_columns()
+                    .into_iter()
+                    .find(|c| metadata::Column::from(*c) == *column);
+                if let (Some(equality_column), Some(row)) = (equality_column, row) {
+                    let cell = cell_key(equality_column, row);
+                    let value = |cell| {
+                        recorder
+                            .cell(cell)
+                            .map_or("unassigned".to_string(), format_value)
+                    };
+                    lines.push(format!("    value = {}", value(cell)));
+                    for (left, right) in recorder.copies.iter() {
+                        let other = if *left == cell {
+                            right
+                        } else if *right == cell {
+                            left
+                        } else {
+                            continue;
+                        };
+                        lines.push(format!(
+                            "    copied with {:?} {} row {} = {}",
+                            other.0,
+                            other.1,
+                            other.2,
+                            value(*other)
+                        ));
+                    }
+                }
+                (
+                    "Permutation".to_string(),
+                    None,
+                    lines,
+                    "two cells joined by a copy constraint hold different values: check \
+                     copy_advice/constrain_equal, and the public inputs passed to the prover \
+                     for constrain_instance",
+                )
+            }
+        };
+
+        let group = self.group(title, polynomial);
+        group
+            .failures
+            .extend(lines.into_iter().enumerate().map(|(i, line)| {
+                // the first line is the location, the others belong to it
+                if i == 0 {
+                    format!("- {}", line)
+                } else {
+                    format!("  {}", line)
+                }
+            }));
+        if !group.hints.contains(&hint) {
+            group.hints.push(hint);
+        }
+    }
+}
+
+impl fmt::Display for FailureReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "the circuit is not satisfied:")?;
+        for group in self.groups.iter() {
+            let count = group.failures.iter().filter(|l| l.starts_with('-')).count();
+            writeln!(
+                f,
+                "\n{}: {} failure{}",
+                group.title,
+                count,
+                if count == 1 { "" } else { "s" }
+            )?;
+            if let Some(polynomial) = &group.polynomial {
+                writeln!(f, "  polynomial: {}", polynomial)?;
+            }
+            for line in group.failures.iter() {
+                writeln!(f, "  {}", line)?;
+            }
+            writeln!(f, "  likely causes:")?;
+            for hint in group.hints.iter() {
+                writeln!(f, "    {}", hint)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Finds the gate index and polynomial index of `constraint`.
+fn find_constraint<F: FieldExt>(
+    cs: &ConstraintSystem<F>,
+    constraint: &metadata::Constraint,
+) -> Option<(usize, usize)> {
+    cs.gates().iter().enumerate().find_map(|(g, gate)| {
+        (0..gate.polynomials().len())
+            .find(|&i| {
+                let candidate = metadata::Constraint::from((
+                    metadata::Gate::from((g, gate.name())),
+                    i,
+                    gate.constraint_name(i),
+                ));
+                candidate == *constraint
+            })
+            .map(|i| (g, i))
+    })
+}
+
+/// Resolves a failure location to an absolute row, using the regions in the order
+/// they were assigned (the same order `MockProver` numbers them in).
+fn find_row<F: FieldExt>(recorder: &Recorder<F>, location: &FailureLocation) -> Option<usize> {
+    match location {
+        FailureLocation::OutsideRegion { row } => Some(*row),
+        FailureLocation::InRegion { region, offset } => recorder
+            .regions
+            .iter()
+            .enumerate()
+            .find(|(i, info)| metadata::Region::from((*i, info.name.as_str())) == *region)
+            .and_then(|(_, info)| info.rows)
+            .map(|(start, _)| start + offset),
+    }
+}
+
+fn format_location(location: &FailureLocation, row: Option<usize>) -> String {
+    match (location, row) {
+        (FailureLocation::InRegion { .. }, Some(row)) => format!("{} (row {})", location, row),
+        _ => location.to_string(),
+    }
+}
+
+fn queried_selectors<F: FieldExt>(expr: &Expression<F>) -> Vec<Selector> {
+    expr.evaluate(
+        &|_| vec![],
+        &|selector| vec![selector],
+        &|_| vec![],
+        &|_| vec![],
+        &|_| vec![],
+        &|a| a,
+        &|mut a, b| {
+            a.extend(b);
+            a
+        },
+        &|mut a, b| {
+            a.extend(b);
+            a
+        },
+        &|a, _| a,
+    )
+}
@@ -0,0 +1,86 @@
+use std::{fs, path::PathBuf};
+
+use halo2_proofs::{
+    arithmetic::FieldExt,
+    dev::{circuit_dot_graph, CircuitGates, CircuitLayout},
+    plonk::Circuit,
+};
+use plotters::prelude::*;
+
+use super::permutation::PermutationReport;
+
+/// Environment variable that overrides where the diagrams are written.
+pub const OUTPUT_DIR_VAR: &str = "CIRCUIT_GRAPH_DIR";
+
+/// Returns (and creates) the directory the diagrams are written to:
+/// `$CIRCUIT_GRAPH_DIR` if set, `target/circuit-graphs` otherwise.
+pub fn output_dir() -> PathBuf {
+    let dir = std::env::var_os(OUTPUT_DIR_VAR)
+        .map(PathBuf::from)
+        .unwrap_or_else(|| PathBuf::from("target/circuit-graphs"));
+    fs::create_dir_all(&dir).unwrap();
+    dir
+}
+
+/// Draws the layout of `circuit` (regions, selectors, table columns) to `<name>-layout.png`.
+pub fn render_layout<F: FieldExt, C: Circuit<F>>(name: &str, k: u32, circuit: &C) -> PathBuf {
+    let path = output_dir().join(format!("{}-layout.png", name));
+
+    let root = BitMapBackend::new(&path, (1024, 3096)).into_drawing_area();
+    root.fill(&WHITE).unwrap();
+    let root = root
+        .titled(&format!("{} layout", name), ("sans-serif", 60))
+        .unwrap();
+
+    CircuitLayout::default()
+        // copy constraints are drawn in red
+        .mark_equality_cells(true)
+        .show_equality_constraints(true)
+        .render(k, circuit, &root)
+        .unwrap();
+    root.present().unwrap();
+
+    path
+}
+
+/// Writes the DOT graph of the circuit's gadget namespaces to `<name>.dot`.
+///
+/// Render it with `dot -Tpng <name>.dot -o <name>.png`.
+pub fn render_dot<F: FieldExt, C: Circuit<F>>(name: &str, circuit: &C) -> PathBuf {
+    let path = output_dir().join(format!("{}.dot", name));
+    fs::write(&path, circuit_dot_graph(circuit)).unwrap();
+    path
+}
+
+/// Writes the gates of the circuit, as polynomials over the queried cells, to `<name>-gates.txt`.
+pub fn write_gates<F: FieldExt, C: Circuit<F>>(name: &str) -> PathBuf {
+    let path = output_dir().join(format!("{}-gates.txt", name));
+    fs::write(&path, CircuitGates::collect::<F, C>().to_string()).unwrap();
+    path
+}
+
+/// Writes the permutation cycles of `circuit` to `<name>-permutation.txt` and
+/// `<name>-permutation.dot`, returning the paths in that order.
+///
+/// Unlike the others this needs the instance, since the copies to instance cells are
+/// part of the cycles.
+pub fn write_permutation<F: FieldExt, C: Circuit<F>>(
+    name: &str,
+    k: u32,
+    circuit: &C,
+    instance: Vec<Vec<F>>,
+) -> (PathBuf, PathBuf) {
+    let report = PermutationReport::extract(k, circuit, instance).unwrap();
+    let text = output_dir().join(format!("{}-permutation.txt", name));
+    fs::write(&text, report.to_string()).unwrap();
+    let dot = output_dir().join(format!("{}-permutation.dot", name));
+    fs::write(&dot, report.to_dot()).unwrap();
+    (text, dot)
+}
+
+/// Writes the layout, DOT graph and gates of `circuit` into [`output_dir`].
+pub fn render_all<F: FieldExt, C: Circuit<F>>(name: &str, k: u32, circuit: &C) {
+    render_layout(name, k, circuit);
+    render_dot(name, circuit);
+    write_gates::<F, C>(name);
+}
@@ -0,0 +1,252 @@
+use std::collections::{HashMap, HashSet};
+
+use halo2_proofs::{
+    arithmetic::FieldExt,
+    circuit::Value,
+    plonk::{
+        Advice, Any, Assigned, Assignment, Circuit, Column, ConstraintSystem, Error, Fixed,
+        FloorPlanner, Instance, Selector,
+    },
+};
+
+/// How many rows a circuit needs, and the smallest `k` that fits them.
+///
+/// halo2 reserves the last `blinding_factors + 1` rows of every column for
+/// blinding, so a circuit with `rows` used rows needs
+/// `2^k >= rows + blinding_factors + 1` (and at least `cs.minimum_rows()`).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CircuitSize {
+    /// Rows touched by regions: advice/fixed cells, selectors, constants and copies.
+    pub rows: usize,
+    /// Rows of the lookup tables loaded with `assign_table`.
+    pub table_rows: usize,
+    /// Rows at the bottom of every column that the prover fills with randomness.
+    pub blinding_factors: usize,
+    /// The minimum `k` to pass to `MockProver::run` (or keygen).
+    pub k: u32,
+}
+
+impl CircuitSize {
+    /// Runs `configure` and `synthesize` of the circuit through a backend that only
+    /// counts rows, without evaluating any witness.
+    pub fn measure<F: FieldExt, C: Circuit<F>>(circuit: &C) -> Result<Self, Error> {
+        let mut cs = ConstraintSystem::default();
+        let config = C::configure(&mut cs);
+
+        let mut counter = RowCounter::default();
+        C::FloorPlanner::synthesize(&mut counter, circuit, config, cs.constants().clone())?;
+
+        let (rows, table_rows) = counter.rows();
+        let blinding_factors = cs.blinding_factors();
+
+        // the last usable row is followed by l_last and the blinding rows
+        let needed = std::cmp::max(rows, table_rows) + blinding_factors + 1;
+        let needed = std::cmp::max(needed, cs.minimum_rows());
+        let k = needed.next_power_of_two().trailing_zeros();
+
+        Ok(Self {
+            rows,
+            table_rows,
+            blinding_factors,
+            k,
+        })
+    }
+}
+
+/// Returns the smallest `k` the circuit can be run with.
+///
+/// Panics if the circuit fails to synthesize.
+pub fn min_k<F: FieldExt, C: Circuit<F>>(circuit: &C) -> u32 {
+    CircuitSize::measure(circuit)
+        .expect("circuit should synthesize")
+        .k
+}
+
+/// An `Assignment` backend that ignores all values and only remembers the
+/// furthest row touched in each column.
+#[derive(Default)]
+struct RowCounter {
+    /// One past the last row assigned in each column.
+    columns: HashMap<Column<Any>, usize>,
+    /// One past the last row on which any selector is enabled.
+    selectors: usize,
+    /// Columns that were filled by a table layouter.
+    tables: HashSet<Column<Any>>,
+}
+
+impl RowCounter {
+    fn record(&mut self, column: Column<Any>, row: usize) {
+        let end = self.columns.entry(column).or_default();
+        *end = std::cmp::max(*end, row + 1);
+    }
+
+    /// Returns `(rows, table_rows)`.
+    fn rows(&self) -> (usize, usize) {
+        let mut rows = self.selectors;
+        let mut table_rows = 0;
+        for (column, end) in self.columns.iter() {
+            if self.tables.contains(column) {
+                table_rows = std::cmp::max(table_rows, *end);
+            } else {
+                rows = std::cmp::max(rows, *end);
+            }
+        }
+        (rows, table_rows)
+    }
+}
+
+impl<F: FieldExt> Assignment<F> for RowCounter {
+    fn enter_region<NR, N>(&mut self, _: N)
+    where
+        NR: Into<String>,
+        N: FnOnce() -> NR,
+    {
+        // Regions don't matter, only the rows they end up on.
+    }
+
+    fn exit_region(&mut self) {}
+
+    fn enable_selector<A, AR>(&mut self, _: A, _: &Selector, row: usize) -> Result<(), Error>
+    where
+        A: FnOnce() -> AR,
+        AR: Into<String>,
+    {
+        self.selectors = std::cmp::max(self.selectors, row + 1);
+        Ok(())
+    }
+
+    fn query_instance(&self, _: Column<Instance>, _: usize) -> Result<Value<F>, Error> {
+        Ok(Value::unknown())
+    }
+
+    fn assign_advice<V, VR, A, AR>(
+        &mut self,
+        _: A,
+        column: Column<Advice>,
+        row: usize,
+        _: V,
+    ) -> Result<(), Error>
+    where
+        V: FnOnce() -> Value<VR>,
+        VR: Into<Assigned<F>>,
+        A: FnOnce() -> AR,
+        AR: Into<String>,
+    {
+        self.record(column.into(), row);
+        Ok(())
+    }
+
+    fn assign_fixed<V, VR, A, AR>(
+        &mut self,
+        _: A,
+        column: Column<Fixed>,
+        row: usize,
+        _: V,
+    ) -> Result<(), Error>
+    where
+        V: FnOnce() -> Value<VR>,
+        VR: Into<Assigned<F>>,
+        A: FnOnce() -> AR,
+        AR: Into<String>,
+    {
+        self.record(column.into(), row);
+        Ok(())
+    }
+
+    fn copy(
+        &mut self,
+        left_column: Column<Any>,
+        left_row: usize,
+        right_column: Column<Any>,
+        right_row: usize,
+    ) -> Result<(), Error> {
+        // copies into the instance column (constrain_instance) also use up rows there
+        self.record(left_column, left_row);
+        self.record(right_column, right_row);
+        Ok(())
+    }
+
+    fn fill_from_row(
+        &mut self,
+        column: Column<Fixed>,
+        _: usize,
+        _: Value<Assigned<F>>,
+    ) -> Result<(), Error> {
+        // only the table layouter pads a column with its default value
+        self.tables.insert(column.into());
+        Ok(())
+    }
+
+    fn push_namespace<NR, N>(&mut self, _: N)
+    where
+        NR: Into<String>,
+        N: FnOnce() -> NR,
+    {
+    }
+
+    fn pop_namespace(&mut self, _: Option<String>) {}
+}
+
+#[cfg(test)]
+mod tests {
+    use halo2_proofs::{
+        circuit::{Layouter, SimpleFloorPlanner},
+        dev::MockProver,
+        pasta::Fp,
+    };
+
+    use super::*;
+
+    /// Assigns `ROWS` values one below the other in a single advice column.
+    #[derive(Default)]
+    struct MyCircuit<const ROWS: usize>;
+
+    impl<const ROWS: usize> Circuit<Fp> for MyCircuit<ROWS> {
+        type Config = Column<Advice>;
+        type FloorPlanner = SimpleFloorPlanner;
+
+        fn without_witnesses(&self) -> Self {
+            Self
+        }
+
+        fn configure(meta: &mut ConstraintSystem<Fp>) -> Self::Config {
+            meta.advice_column()
+        }
+
+        fn synthesize(
+            &self,
+            config: Self::Config,
+            mut layouter: impl Layouter<Fp>,
+        ) -> Result<(), Error> {
+            layouter.assign_region(
+                || "rows",
+                |mut region| {
+                    for offset in 0..ROWS {
+                        region.assign_advice(
+                            || "value",
+                            config,
+                            offset,
+                            || Value::known(Fp::from(offset as u64)),
+                        )?;
+                    }
+                    Ok(())
+                },
+            )
+        }
+    }
+
+    #[test]
+    fn test_min_k() {
+        let size = CircuitSize::measure(&MyCircuit::<20>).unwrap();
+        assert_eq!(size.rows, 20);
+        assert_eq!(size.table_rows, 0);
+
+        let k = size.k;
+        // the circuit fits in k...
+        let prover = MockProver::run(k, &MyCircuit::<20>, vec![]).unwrap();
+        prover.assert_satisfied();
+
+        // ...but not in k - 1
+        assert!(MockProver::run(k - 1, &MyCircuit::<20>, vec![]).is_err());
+    }
+}
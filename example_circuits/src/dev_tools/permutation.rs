@@ -0,0 +1,454 @@
+use std::{
+    collections::{BTreeMap, HashMap, HashSet},
+    fmt,
+};
+
+use halo2_proofs::{
+    arithmetic::FieldExt,
+    plonk::{Any, Circuit, Column, Error},
+};
+
+use super::recorder::{format_value, CellKey, Recorder};
+
+/// The copy constraints of a circuit as the permutation argument sees them: the
+/// equality-enabled columns, and the cycles of `σ` through the cells that are copied.
+///
+/// `copy_advice`, `constrain_equal`, `constrain_instance` and `assign_advice_from_constant`
+/// all end up as copies between two cells, and halo2's keygen merges them into cycles:
+/// every cell of a cycle must hold the same value. `MockProver` keeps its permutation to
+/// itself, so this synthesizes the circuit again with the [`Recorder`] and builds the
+/// cycles the same way.
+///
+/// ```text
+/// permutation over [Instance 0, Fixed 0, Advice 0, Advice 1]: 6 cycles, 14 cells
+/// ...
+/// cycle 1:
+///   Advice 0 @ 0 = 0x2  (region 0 'load private', offset 0)
+///   Advice 0 @ 3 = 0x2  (region 3 'mul', offset 0)
+/// ...
+/// ```
+#[derive(Debug, Clone)]
+pub struct PermutationReport {
+    pub columns: Vec<Column<Any>>,
+    /// In the order their first cell was copied.
+    pub cycles: Vec<Cycle>,
+}
+
+#[derive(Debug, Clone)]
+pub struct Cycle {
+    /// In the order `σ` goes around: every cell maps to the next one, the last to the first.
+    pub cells: Vec<CopiedCell>,
+    /// Whether every cell holds the same value, i.e. whether the permutation argument holds
+    /// on this cycle.
+    pub consistent: bool,
+}
+
+#[derive(Debug, Clone)]
+pub struct CopiedCell {
+    pub column_type: Any,
+    pub column_index: usize,
+    pub row: usize,
+    pub region: Option<CellRegion>,
+    /// `None` for an advice cell that was never assigned.
+    pub value: Option<String>,
+}
+
+#[derive(Debug, Clone)]
+pub struct CellRegion {
+    pub index: usize,
+    pub name: String,
+    pub offset: usize,
+}
+
+impl CopiedCell {
+    pub fn name(&self) -> String {
+        format!(
+            "{:?} {} @ {}",
+            self.column_type, self.column_index, self.row
+        )
+    }
+}
+
+impl PermutationReport {
+    pub fn extract<F: FieldExt, C: Circuit<F>>(
+        k: u32,
+        circuit: &C,
+        instance: Vec<Vec<F>>,
+    ) -> Result<Self, Error> {
+        let (recorder, cs) = Recorder::record(k, circuit, instance)?;
+
+        let cycles = cycles(&recorder.copies)
+            .into_iter()
+            .map(|cycle| {
+                let values: Vec<_> = cycle.iter().map(|cell| recorder.cell(*cell)).collect();
+                let consistent =
+                    values[0].is_some() && values.iter().all(|value| *value == values[0]);
+                let cells = cycle
+                    .iter()
+                    .zip(values)
+                    .map(|(cell, value)| CopiedCell {
+                        column_type: cell.0,
+                        column_index: cell.1,
+                        row: cell.2,
+                        region: recorder.cell_regions.get(cell).map(|index| {
+                            let region = &recorder.regions[*index];
+                            CellRegion {
+                                index: *index,
+                                name: region.name.clone(),
+                                offset: cell.2 - region.rows.map_or(0, |(start, _)| start),
+                            }
+                        }),
+                        value: value.map(format_value),
+                    })
+                    .collect();
+                Cycle { cells, consistent }
+            })
+            .collect();
+
+        Ok(PermutationReport {
+            columns: cs.permutation().get_columns(),
+            cycles,
+        })
+    }
+
+    /// The cycles as a DOT graph: an edge from every cell to the next one around its
+    /// cycle, cells grouped by region, and the cycles whose values differ in red.
+    ///
+    /// Render it with `dot -Tpng permutation.dot -o permutation.png`.
+    pub fn to_dot(&self) -> String {
+        let mut regions: BTreeMap<usize, (&str, Vec<&CopiedCell>)> = BTreeMap::new();
+        let mut outside = vec![];
+        for cell in self.cycles.iter().flat_map(|cycle| &cycle.cells) {
+            match &cell.region {
+                Some(region) => regions
+                    .entry(region.index)
+                    .or_insert((&region.name, vec![]))
+                    .1
+                    .push(cell),
+                None => outside.push(cell),
+            }
+        }
+
+        let node = |cell: &CopiedCell| {
+            let value = cell.value.as_deref().unwrap_or("unassigned");
+            let offset = cell.region.as_ref().map_or(String::new(), |region| {
+                format!("\\noffset {}", region.offset)
+            });
+            format!(
+                "\"{}\" [label=\"{}\\n= {}{}\"];",
+                cell.name(),
+                cell.name(),
+                value,
+                offset
+            )
+        };
+
+        let mut dot = vec![
+            "digraph permutation {".to_string(),
+            "    rankdir=LR;".to_string(),
+            "    node [shape=box, fontname=\"monospace\"];".to_string(),
+        ];
+        for (index, (name, cells)) in regions {
+            dot.push(format!("    subgraph cluster_{} {{", index));
+            dot.push(format!(
+                "        label=\"region {}: {}\";",
+                index,
+                name.replace('"', "\\\"")
+            ));
+            dot.extend(
+                cells
+                    .into_iter()
+                    .map(|cell| format!("        {}", node(cell))),
+            );
+            dot.push("    }".to_string());
+        }
+        dot.extend(
+            outside
+                .into_iter()
+                .map(|cell| format!("    {}", node(cell))),
+        );
+        for cycle in &self.cycles {
+            let color = if cycle.consistent { "" } else { " [color=red]" };
+            for (i, cell) in cycle.cells.iter().enumerate() {
+                let next = &cycle.cells[(i + 1) % cycle.cells.len()];
+                dot.push(format!(
+                    "    \"{}\" -> \"{}\"{};",
+                    cell.name(),
+                    next.name(),
+                    color
+                ));
+            }
+        }
+        dot.push("}".to_string());
+        dot.join("\n") + "\n"
+    }
+}
+
+impl fmt::Display for PermutationReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let columns: Vec<_> = self
+            .columns
+            .iter()
+            .map(|column| format!("{:?} {}", column.column_type(), column.index()))
+            .collect();
+        let cells: usize = self.cycles.iter().map(|cycle| cycle.cells.len()).sum();
+        write!(
+            f,
+            "permutation over [{}]: {} cycles, {} cells",
+            columns.join(", "),
+            self.cycles.len(),
+            cells
+        )?;
+
+        for (i, cycle) in self.cycles.iter().enumerate() {
+            writeln!(f)?;
+            write!(
+                f,
+                "cycle {}{}:",
+                i,
+                if cycle.consistent {
+                    ""
+                } else {
+                    ", values differ"
+                }
+            )?;
+            for cell in &cycle.cells {
+                write!(
+                    f,
+                    "\n  {} = {}",
+                    cell.name(),
+                    cell.value.as_deref().unwrap_or("unassigned")
+                )?;
+                if let Some(region) = &cell.region {
+                    write!(
+                        f,
+                        "  (region {} '{}', offset {})",
+                        region.index, region.name, region.offset
+                    )?;
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+/// The cycles of `σ` through the copied cells, in the order their first cell was copied.
+///
+/// Every cell starts as a cycle of its own, and a copy between two cycles merges them by
+/// swapping where its two cells point to, like halo2's keygen.
+fn cycles(copies: &[(CellKey, CellKey)]) -> Vec<Vec<CellKey>> {
+    let mut mapping: HashMap<CellKey, CellKey> = HashMap::new();
+    let mut label: HashMap<CellKey, CellKey> = HashMap::new();
+    let mut order = vec![];
+    for (left, right) in copies {
+        for cell in [left, right] {
+            if !mapping.contains_key(cell) {
+                mapping.insert(*cell, *cell);
+                label.insert(*cell, *cell);
+                order.push(*cell);
+            }
+        }
+        if label[left] == label[right] {
+            continue;
+        }
+
+        let merged = label[left];
+        let mut cell = *right;
+        loop {
+            label.insert(cell, merged);
+            cell = mapping[&cell];
+            if cell == *right {
+                break;
+            }
+        }
+        let (left_next, right_next) = (mapping[left], mapping[right]);
+        mapping.insert(*left, right_next);
+        mapping.insert(*right, left_next);
+    }
+
+    let mut seen = HashSet::new();
+    let mut cycles = vec![];
+    for start in order {
+        if seen.contains(&start) {
+            continue;
+        }
+        let mut cycle = vec![];
+        let mut cell = start;
+        loop {
+            seen.insert(cell);
+            cycle.push(cell);
+            cell = mapping[&cell];
+            if cell == start {
+                break;
+            }
+        }
+        cycles.push(cycle);
+    }
+    cycles
+}
+
+#[cfg(test)]
+mod tests {
+    use halo2_proofs::{
+        circuit::{AssignedCell, Layouter, SimpleFloorPlanner, Value},
+        pasta::Fp,
+        plonk::{Advice, ConstraintSystem, Fixed, Instance, Selector},
+        poly::Rotation,
+    };
+
+    use super::*;
+
+    /// `FieldChip` from `examples/ex1.rs`: `c = constant * a^2 * b^2`, with a mul gate over
+    /// two rows and `c` exposed as the public input.
+    #[derive(Default)]
+    struct MyCircuit {
+        constant: Fp,
+        a: Value<Fp>,
+        b: Value<Fp>,
+    }
+
+    impl Circuit<Fp> for MyCircuit {
+        type Config = ([Column<Advice>; 2], Column<Instance>, Selector);
+        type FloorPlanner = SimpleFloorPlanner;
+
+        fn without_witnesses(&self) -> Self {
+            Self {
+                constant: self.constant,
+                ..Self::default()
+            }
+        }
+
+        fn configure(meta: &mut ConstraintSystem<Fp>) -> Self::Config {
+            let advice = [meta.advice_column(), meta.advice_column()];
+            let instance = meta.instance_column();
+            let constant: Column<Fixed> = meta.fixed_column();
+            meta.enable_equality(instance);
+            meta.enable_constant(constant);
+            for column in advice {
+                meta.enable_equality(column);
+            }
+
+            let s_mul = meta.selector();
+            meta.create_gate("mul", |meta| {
+                let lhs = meta.query_advice(advice[0], Rotation::cur());
+                let rhs = meta.query_advice(advice[1], Rotation::cur());
+                let out = meta.query_advice(advice[0], Rotation::next());
+                vec![meta.query_selector(s_mul) * (lhs * rhs - out)]
+            });
+            (advice, instance, s_mul)
+        }
+
+        fn synthesize(
+            &self,
+            (advice, instance, s_mul): Self::Config,
+            mut layouter: impl Layouter<Fp>,
+        ) -> Result<(), Error> {
+            let mut load = |name: &'static str, value: Value<Fp>| {
+                layouter.assign_region(
+                    || name,
+                    |mut region| region.assign_advice(|| name, advice[0], 0, || value),
+                )
+            };
+            let a = load("load private", self.a)?;
+            let b = load("load private", self.b)?;
+            let constant = layouter.assign_region(
+                || "load constant",
+                |mut region| {
+                    region.assign_advice_from_constant(|| "constant", advice[0], 0, self.constant)
+                },
+            )?;
+
+            let mut mul = |a: &AssignedCell<Fp, Fp>, b: &AssignedCell<Fp, Fp>| {
+                layouter.assign_region(
+                    || "mul",
+                    |mut region| {
+                        s_mul.enable(&mut region, 0)?;
+                        a.copy_advice(|| "lhs", &mut region, advice[0], 0)?;
+                        b.copy_advice(|| "rhs", &mut region, advice[1], 0)?;
+                        region.assign_advice(
+                            || "lhs * rhs",
+                            advice[0],
+                            1,
+                            || a.value().copied() * b.value(),
+                        )
+                    },
+                )
+            };
+            let ab = mul(&a, &b)?;
+            let absq = mul(&ab, &ab)?;
+            let c = mul(&constant, &absq)?;
+
+            layouter.constrain_instance(c.cell(), instance, 0)
+        }
+    }
+
+    fn circuit() -> MyCircuit {
+        MyCircuit {
+            constant: Fp::from(7),
+            a: Value::known(Fp::from(2)),
+            b: Value::known(Fp::from(3)),
+        }
+    }
+
+    #[test]
+    fn test_cycles() {
+        let report = PermutationReport::extract(4, &circuit(), vec![vec![Fp::from(252)]]).unwrap();
+        assert_eq!(report.columns.len(), 4);
+
+        // the constant (copied from its fixed cell when its region is laid out), a, b, ab
+        // (copied twice), absq, and c
+        let sizes: Vec<_> = report
+            .cycles
+            .iter()
+            .map(|cycle| cycle.cells.len())
+            .collect();
+        assert_eq!(sizes, vec![3, 2, 2, 3, 2, 2]);
+        assert!(report.cycles.iter().all(|cycle| cycle.consistent));
+
+        let constant = &report.cycles[0];
+        assert!(constant
+            .cells
+            .iter()
+            .any(|cell| cell.column_type == Any::Fixed && cell.region.is_none()));
+        assert!(constant
+            .cells
+            .iter()
+            .all(|cell| cell.value.as_deref() == Some("0x7")));
+        let c = &report.cycles[5];
+        assert!(c.cells.iter().any(|cell| cell.column_type == Any::Instance));
+        assert!(c
+            .cells
+            .iter()
+            .all(|cell| cell.value.as_deref() == Some("0xfc")));
+
+        let text = report.to_string();
+        assert!(text.starts_with(
+            "permutation over [Instance 0, Fixed 0, Advice 0, Advice 1]: 6 cycles, 14 cells"
+        ));
+        assert!(text.contains("Instance 0 @ 0 = 0xfc"));
+        assert!(text.contains("(region 3 'mul', offset 0)"));
+
+        let dot = report.to_dot();
+        assert_eq!(dot.matches(" -> ").count(), 14);
+        assert!(dot.contains("label=\"region 3: mul\""));
+        assert!(!dot.contains("color=red"));
+    }
+
+    #[test]
+    fn test_wrong_instance() {
+        let report = PermutationReport::extract(4, &circuit(), vec![vec![Fp::from(253)]]).unwrap();
+        let broken: Vec<_> = report
+            .cycles
+            .iter()
+            .filter(|cycle| !cycle.consistent)
+            .collect();
+        assert_eq!(broken.len(), 1);
+        assert!(broken[0]
+            .cells
+            .iter()
+            .any(|cell| cell.column_type == Any::Instance));
+
+        assert!(report.to_string().contains("cycle 5, values differ:"));
+        assert_eq!(report.to_dot().matches("[color=red]").count(), 2);
+    }
+}
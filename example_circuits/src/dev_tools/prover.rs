@@ -0,0 +1,43 @@
+//! The setup and proving steps every real-prover test and benchmark starts with.
+
+use halo2_proofs::{
+    arithmetic::CurveAffine,
+    plonk::{create_proof, keygen_pk, keygen_vk, Circuit, Error, ProvingKey},
+    poly::commitment::Params,
+    transcript::{Blake2bWrite, Challenge255},
+};
+use rand::RngCore;
+
+use super::min_k::min_k;
+
+/// Params for the smallest `k` that fits `circuit`, and its proving key. Only the shape of
+/// `circuit` is used, not its witness.
+pub fn setup<C: CurveAffine, ConcreteCircuit: Circuit<C::Scalar>>(
+    circuit: &ConcreteCircuit,
+) -> (Params<C>, ProvingKey<C>) {
+    let empty = circuit.without_witnesses();
+    let params = Params::new(min_k(&empty));
+    let vk = keygen_vk(&params, &empty).unwrap();
+    let pk = keygen_pk(&params, vk, &empty).unwrap();
+    (params, pk)
+}
+
+/// `create_proof` for one circuit into a Blake2b transcript, returning the proof bytes.
+pub fn prove<C: CurveAffine, ConcreteCircuit: Circuit<C::Scalar>>(
+    params: &Params<C>,
+    pk: &ProvingKey<C>,
+    circuit: &ConcreteCircuit,
+    instance: &[&[C::Scalar]],
+    rng: impl RngCore,
+) -> Result<Vec<u8>, Error> {
+    let mut transcript = Blake2bWrite::<_, _, Challenge255<_>>::init(vec![]);
+    create_proof(
+        params,
+        pk,
+        std::slice::from_ref(circuit),
+        &[instance],
+        rng,
+        &mut transcript,
+    )?;
+    Ok(transcript.finalize())
+}
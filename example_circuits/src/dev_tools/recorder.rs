@@ -0,0 +1,298 @@
+use std::collections::{HashMap, HashSet};
+
+use halo2_proofs::{
+    arithmetic::FieldExt,
+    circuit::Value,
+    plonk::{
+        Advice, Any, Assigned, Assignment, Circuit, Column, ConstraintSystem, Error, Expression,
+        Fixed, FloorPlanner, Instance, Selector,
+    },
+};
+
+/// A cell of the circuit: column type, column index and row.
+pub(crate) type CellKey = (Any, usize, usize);
+
+/// A region as seen by the `Assignment` backend, in the order `assign_region` was called.
+#[derive(Debug, Clone)]
+pub(crate) struct RegionInfo {
+    pub(crate) name: String,
+    /// First and last row used by the region.
+    pub(crate) rows: Option<(usize, usize)>,
+}
+
+/// An `Assignment` backend that keeps every value a circuit assigns, so that we can
+/// evaluate gates and follow copy constraints outside of `MockProver`.
+pub(crate) struct Recorder<F: FieldExt> {
+    pub(crate) n: usize,
+    pub(crate) regions: Vec<RegionInfo>,
+    current_region: Option<usize>,
+    /// Advice and fixed cells; instance cells are read from `instance`.
+    pub(crate) cells: HashMap<CellKey, F>,
+    /// The region each advice and fixed cell was assigned in. Constants the floor planner
+    /// places at the end are in none.
+    pub(crate) cell_regions: HashMap<CellKey, usize>,
+    pub(crate) selectors: HashMap<Selector, HashSet<usize>>,
+    /// Copy constraints in the order they were added.
+    pub(crate) copies: Vec<(CellKey, CellKey)>,
+    pub(crate) instance: Vec<Vec<F>>,
+}
+
+impl<F: FieldExt> Recorder<F> {
+    /// Configures and synthesizes `circuit` with `2^k` rows, returning the recorded
+    /// assignment along with the constraint system it was configured with.
+    pub(crate) fn record<C: Circuit<F>>(
+        k: u32,
+        circuit: &C,
+        instance: Vec<Vec<F>>,
+    ) -> Result<(Self, ConstraintSystem<F>), Error> {
+        let mut cs = ConstraintSystem::default();
+        let config = C::configure(&mut cs);
+
+        let mut recorder = Recorder {
+            n: 1 << k,
+            regions: vec![],
+            current_region: None,
+            cells: HashMap::new(),
+            cell_regions: HashMap::new(),
+            selectors: HashMap::new(),
+            copies: vec![],
+            instance,
+        };
+        C::FloorPlanner::synthesize(&mut recorder, circuit, config, cs.constants().clone())?;
+
+        Ok((recorder, cs))
+    }
+
+    /// Index of the region that contains `row` in one of its columns, if any.
+    pub(crate) fn region_at(&self, row: usize) -> Option<usize> {
+        self.regions.iter().position(|region| {
+            region
+                .rows
+                .map_or(false, |(start, end)| start <= row && row <= end)
+        })
+    }
+
+    /// Value of a cell; unassigned fixed and instance cells are zero, unassigned advice
+    /// cells are `None`.
+    pub(crate) fn cell(&self, (column_type, index, row): CellKey) -> Option<F> {
+        match column_type {
+            Any::Instance => Some(
+                self.instance
+                    .get(index)
+                    .and_then(|column| column.get(row))
+                    .copied()
+                    .unwrap_or_else(F::zero),
+            ),
+            Any::Fixed => Some(
+                self.cells
+                    .get(&(column_type, index, row))
+                    .copied()
+                    .unwrap_or_else(F::zero),
+            ),
+            Any::Advice => self.cells.get(&(column_type, index, row)).copied(),
+        }
+    }
+
+    fn rotate(&self, row: usize, rotation: i32) -> usize {
+        (row as i32 + rotation).rem_euclid(self.n as i32) as usize
+    }
+
+    pub(crate) fn selector_enabled(&self, selector: &Selector, row: usize) -> bool {
+        self.selectors
+            .get(selector)
+            .map_or(false, |rows| rows.contains(&row))
+    }
+
+    /// Evaluates `expr` with the gate anchored at `row`. Returns `None` if the
+    /// expression queries an unassigned advice cell.
+    pub(crate) fn evaluate(&self, expr: &Expression<F>, row: usize) -> Option<F> {
+        expr.evaluate(
+            &|constant| Some(constant),
+            &|selector| {
+                Some(if self.selector_enabled(&selector, row) {
+                    F::one()
+                } else {
+                    F::zero()
+                })
+            },
+            &|query| {
+                let row = self.rotate(row, query.rotation().0);
+                self.cell((Any::Fixed, query.column_index(), row))
+            },
+            &|query| {
+                let row = self.rotate(row, query.rotation().0);
+                self.cell((Any::Advice, query.column_index(), row))
+            },
+            &|query| {
+                let row = self.rotate(row, query.rotation().0);
+                self.cell((Any::Instance, query.column_index(), row))
+            },
+            &|a| a.map(|a| -a),
+            &|a, b| a.zip(b).map(|(a, b)| a + b),
+            &|a, b| a.zip(b).map(|(a, b)| a * b),
+            &|a, scalar| a.map(|a| a * scalar),
+        )
+    }
+
+    fn touch(&mut self, row: usize) {
+        if let Some(index) = self.current_region {
+            let rows = &mut self.regions[index].rows;
+            *rows = Some(match *rows {
+                Some((start, end)) => (start.min(row), end.max(row)),
+                None => (row, row),
+            });
+        }
+    }
+
+    fn assign<VR: Into<Assigned<F>>>(&mut self, key: CellKey, to: Value<VR>) {
+        self.touch(key.2);
+        if let Some(index) = self.current_region {
+            self.cell_regions.insert(key, index);
+        }
+        let mut value = None;
+        to.map(|v| {
+            let v: Assigned<F> = v.into();
+            value = Some(v.evaluate());
+        });
+        if let Some(value) = value {
+            self.cells.insert(key, value);
+        }
+    }
+}
+
+/// `(column type, index, row)` of a column/row pair.
+pub(crate) fn cell_key<C: Into<Column<Any>>>(column: C, row: usize) -> CellKey {
+    let column = column.into();
+    (*column.column_type(), column.index(), row)
+}
+
+impl<F: FieldExt> Assignment<F> for Recorder<F> {
+    fn enter_region<NR, N>(&mut self, name_fn: N)
+    where
+        NR: Into<String>,
+        N: FnOnce() -> NR,
+    {
+        self.current_region = Some(self.regions.len());
+        self.regions.push(RegionInfo {
+            name: name_fn().into(),
+            rows: None,
+        });
+    }
+
+    fn exit_region(&mut self) {
+        self.current_region = None;
+    }
+
+    fn enable_selector<A, AR>(&mut self, _: A, selector: &Selector, row: usize) -> Result<(), Error>
+    where
+        A: FnOnce() -> AR,
+        AR: Into<String>,
+    {
+        self.touch(row);
+        self.selectors.entry(*selector).or_default().insert(row);
+        Ok(())
+    }
+
+    fn query_instance(&self, column: Column<Instance>, row: usize) -> Result<Value<F>, Error> {
+        Ok(Value::known(self.cell(cell_key(column, row)).unwrap()))
+    }
+
+    fn assign_advice<V, VR, A, AR>(
+        &mut self,
+        _: A,
+        column: Column<Advice>,
+        row: usize,
+        to: V,
+    ) -> Result<(), Error>
+    where
+        V: FnOnce() -> Value<VR>,
+        VR: Into<Assigned<F>>,
+        A: FnOnce() -> AR,
+        AR: Into<String>,
+    {
+        self.assign(cell_key(column, row), to());
+        Ok(())
+    }
+
+    fn assign_fixed<V, VR, A, AR>(
+        &mut self,
+        _: A,
+        column: Column<Fixed>,
+        row: usize,
+        to: V,
+    ) -> Result<(), Error>
+    where
+        V: FnOnce() -> Value<VR>,
+        VR: Into<Assigned<F>>,
+        A: FnOnce() -> AR,
+        AR: Into<String>,
+    {
+        self.assign(cell_key(column, row), to());
+        Ok(())
+    }
+
+    fn copy(
+        &mut self,
+        left_column: Column<Any>,
+        left_row: usize,
+        right_column: Column<Any>,
+        right_row: usize,
+    ) -> Result<(), Error> {
+        self.copies.push((
+            cell_key(left_column, left_row),
+            cell_key(right_column, right_row),
+        ));
+        Ok(())
+    }
+
+    fn fill_from_row(
+        &mut self,
+        _: Column<Fixed>,
+        _: usize,
+        _: Value<Assigned<F>>,
+    ) -> Result<(), Error> {
+        // the padding repeats a value that is already in the table
+        Ok(())
+    }
+
+    fn push_namespace<NR, N>(&mut self, _: N)
+    where
+        NR: Into<String>,
+        N: FnOnce() -> NR,
+    {
+    }
+
+    fn pop_namespace(&mut self, _: Option<String>) {}
+}
+
+/// Formats a field element the way `MockProver` does: small values as `0`, `1`, `-1`,
+/// everything else as hex without leading zeroes.
+pub(crate) fn format_value<F: FieldExt>(v: F) -> String {
+    if v == F::zero() {
+        "0".into()
+    } else if v == F::one() {
+        "1".into()
+    } else if v == -F::one() {
+        "-1".into()
+    } else {
+        let s = format!("{:?}", v);
+        let s = s.strip_prefix("0x").unwrap_or(&s);
+        format!("0x{}", s.trim_start_matches('0'))
+    }
+}
+
+/// Renders `expr` with cells written as `A<column>@<rotation>` (`F`/`I` for fixed and
+/// instance columns).
+pub(crate) fn format_expression<F: FieldExt>(expr: &Expression<F>) -> String {
+    expr.evaluate(
+        &|constant| format_value(constant),
+        &|selector| format!("{:?}", selector),
+        &|query| format!("F{}@{}", query.column_index(), query.rotation().0),
+        &|query| format!("A{}@{}", query.column_index(), query.rotation().0),
+        &|query| format!("I{}@{}", query.column_index(), query.rotation().0),
+        &|a| format!("-{}", a),
+        &|a, b| format!("({} + {})", a, b),
+        &|a, b| format!("{} * {}", a, b),
+        &|a, scalar| format!("{} * {}", a, format_value(scalar)),
+    )
+}
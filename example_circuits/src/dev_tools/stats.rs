@@ -0,0 +1,250 @@
+use std::{
+    collections::{BTreeSet, HashMap, HashSet},
+    fmt,
+};
+
+use halo2_proofs::{
+    arithmetic::FieldExt,
+    plonk::{Any, Circuit, Column, ConstraintSystem, Error, Expression},
+    poly::Rotation,
+};
+
+use super::min_k::CircuitSize;
+
+/// What a circuit costs: its columns, gates, lookups, permutation and proof size.
+///
+/// ```text
+/// let stats = CircuitStats::measure(&MyCircuit::<Fp, 8>::default()).unwrap();
+/// println!("{}", stats);
+/// ```
+#[derive(Debug, Clone)]
+pub struct CircuitStats {
+    /// Rows used and the minimum `k`, see [`CircuitSize`].
+    pub size: CircuitSize,
+    pub advice_columns: usize,
+    /// Fixed columns that are not lookup tables (e.g. the `enable_constant` column).
+    pub fixed_columns: usize,
+    pub instance_columns: usize,
+    pub table_columns: usize,
+    /// Selectors before halo2 combines them into fixed columns during keygen.
+    pub selectors: usize,
+    pub gates: Vec<GateStats>,
+    pub lookups: Vec<LookupStats>,
+    /// Columns that take part in the permutation argument.
+    pub equality_columns: Vec<Column<Any>>,
+    /// Degree of the whole constraint system (gates, lookups and permutation).
+    pub max_degree: usize,
+    /// Estimated size in bytes of a proof for one instance of the circuit at `size.k`.
+    pub proof_size: usize,
+}
+
+#[derive(Debug, Clone)]
+pub struct GateStats {
+    pub name: String,
+    pub constraints: Vec<String>,
+    /// Degree of the highest-degree constraint, selector included.
+    pub degree: usize,
+}
+
+#[derive(Debug, Clone)]
+pub struct LookupStats {
+    /// Number of `(input, table)` pairs in the lookup.
+    pub inputs: usize,
+    /// Degree the lookup argument adds to the constraint system.
+    pub degree: usize,
+}
+
+impl CircuitStats {
+    pub fn measure<F: FieldExt, C: Circuit<F>>(circuit: &C) -> Result<Self, Error> {
+        let size = CircuitSize::measure(circuit)?;
+
+        let mut cs = ConstraintSystem::default();
+        C::configure(&mut cs);
+
+        let gates = cs
+            .gates()
+            .iter()
+            .map(|gate| GateStats {
+                name: gate.name().to_string(),
+                constraints: (0..gate.polynomials().len())
+                    .map(|i| gate.constraint_name(i).to_string())
+                    .collect(),
+                degree: gate
+                    .polynomials()
+                    .iter()
+                    .map(|p| p.degree())
+                    .max()
+                    .unwrap_or(0),
+            })
+            .collect();
+
+        // table columns are the fixed columns queried on the table side of a lookup
+        let mut tables = BTreeSet::new();
+        let lookups = cs
+            .lookups()
+            .iter()
+            .map(|lookup| {
+                for expr in lookup.table_expressions() {
+                    tables.extend(fixed_columns(expr));
+                }
+                let degree = |exprs: &[Expression<F>]| {
+                    exprs.iter().map(|e| e.degree()).max().unwrap_or(1).max(1)
+                };
+                LookupStats {
+                    inputs: lookup.input_expressions().len(),
+                    degree: 2
+                        + degree(lookup.input_expressions())
+                        + degree(lookup.table_expressions()),
+                }
+            })
+            .collect();
+
+        let stats = Self {
+            advice_columns: cs.num_advice_columns(),
+            fixed_columns: cs.num_fixed_columns() - tables.len(),
+            instance_columns: cs.num_instance_columns(),
+            table_columns: tables.len(),
+            selectors: cs.num_selectors(),
+            gates,
+            lookups,
+            equality_columns: cs.permutation().get_columns(),
+            max_degree: cs.degree(),
+            proof_size: proof_size(&cs, size.k as usize),
+            size,
+        };
+        Ok(stats)
+    }
+}
+
+impl fmt::Display for CircuitStats {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(
+            f,
+            "k = {} (rows: {}, table rows: {}, blinding factors: {})",
+            self.size.k, self.size.rows, self.size.table_rows, self.size.blinding_factors
+        )?;
+        writeln!(
+            f,
+            "columns: {} advice, {} fixed, {} instance, {} table, {} selectors",
+            self.advice_columns,
+            self.fixed_columns,
+            self.instance_columns,
+            self.table_columns,
+            self.selectors
+        )?;
+        writeln!(f, "gates (max degree {}):", self.max_degree)?;
+        for gate in self.gates.iter() {
+            writeln!(
+                f,
+                "  {}: degree {} [{}]",
+                gate.name,
+                gate.degree,
+                gate.constraints.join(", ")
+            )?;
+        }
+        writeln!(f, "lookups:")?;
+        for (i, lookup) in self.lookups.iter().enumerate() {
+            writeln!(
+                f,
+                "  #{}: {} inputs, degree {}",
+                i, lookup.inputs, lookup.degree
+            )?;
+        }
+        let equality: Vec<_> = self
+            .equality_columns
+            .iter()
+            .map(|column| format!("{:?} {}", column.column_type(), column.index()))
+            .collect();
+        writeln!(f, "equality-enabled columns: [{}]", equality.join(", "))?;
+        write!(f, "estimated proof size: {} bytes", self.proof_size)
+    }
+}
+
+/// Indices of the fixed columns queried in `expr`.
+fn fixed_columns<F: FieldExt>(expr: &Expression<F>) -> Vec<usize> {
+    expr.evaluate(
+        &|_| vec![],
+        &|_| vec![],
+        &|query| vec![query.column_index()],
+        &|_| vec![],
+        &|_| vec![],
+        &|a| a,
+        &|mut a, b| {
+            a.extend(b);
+            a
+        },
+        &|mut a, b| {
+            a.extend(b);
+            a
+        },
+        &|a, _| a,
+    )
+}
+
+/// Estimates the proof size the same way `halo2_proofs::dev::CircuitCost` does,
+/// counting a commitment and a scalar as one field element each (32 bytes for Pasta).
+///
+/// Selectors are counted as one fixed column each, so this is an upper bound:
+/// halo2 may combine several selectors into one fixed column during keygen.
+fn proof_size<F: FieldExt>(cs: &ConstraintSystem<F>, k: usize) -> usize {
+    let element = F::Repr::default().as_ref().len();
+
+    // every distinct set of rotations a column is opened at is one point set
+    let mut column_queries: HashMap<Column<Any>, HashSet<i32>> = HashMap::new();
+    let queries = cs
+        .advice_queries()
+        .iter()
+        .map(|(c, r)| (Column::<Any>::from(*c), *r))
+        .chain(cs.instance_queries().iter().map(|(c, r)| ((*c).into(), *r)))
+        .chain(cs.fixed_queries().iter().map(|(c, r)| ((*c).into(), *r)))
+        .chain(
+            cs.permutation()
+                .get_columns()
+                .into_iter()
+                .map(|c| (c, Rotation::cur())),
+        );
+    for (column, rotation) in queries {
+        column_queries.entry(column).or_default().insert(rotation.0);
+    }
+    let mut point_sets: HashSet<Vec<i32>> = column_queries
+        .into_values()
+        .map(|rotations| {
+            let mut rotations: Vec<_> = rotations.into_iter().collect();
+            rotations.sort_unstable();
+            rotations
+        })
+        .collect();
+    // lookup and permutation products, permuted input and permuted table
+    point_sets.insert(vec![0, 1]);
+    point_sets.insert(vec![-1, 0]);
+    point_sets.insert(vec![0]);
+
+    let max_degree = cs.degree();
+    let lookups = cs.lookups().len();
+    let permutation_columns = cs.permutation().get_columns().len();
+    let chunk_size = max_degree - 2;
+    let chunks = (permutation_columns + chunk_size - 1) / chunk_size;
+    if permutation_columns > chunk_size {
+        point_sets.insert(vec![-((cs.blinding_factors() + 1) as i32), 0, 1]);
+    }
+
+    let commitments = cs.num_advice_columns() // advice
+        + 3 * lookups // permuted input, permuted table, product
+        + chunks // permutation products
+        + max_degree // vanishing: random poly and the pieces of h(X)
+        + 1 // multiopen f commitment
+        + 1 + 2 * k; // IPA: s_poly and the L, R of every round
+
+    let evaluations = cs.instance_queries().len()
+        + cs.advice_queries().len()
+        + cs.fixed_queries().len()
+        + cs.num_selectors()
+        + 5 * lookups
+        + (3 * chunks).saturating_sub(1)
+        + permutation_columns
+        + 1 // random poly
+        + point_sets.len()
+        + 2; // IPA: a and xi
+
+    (commitments + evaluations) * element
+}
@@ -0,0 +1,45 @@
+//! `proptest` strategies for the property tests of the range-check gadgets.
+
+use halo2_proofs::arithmetic::FieldExt;
+use proptest::prelude::*;
+
+/// Field elements biased towards the places a range check gets wrong: small values,
+/// values around every power of two, values just below the modulus (`-1`, `-2`, ...)
+/// and uniformly random elements.
+pub(crate) fn field_element<F: FieldExt>() -> impl Strategy<Value = F> {
+    prop_oneof![
+        (0u64..1024).prop_map(F::from),
+        (0u64..255, -3i64..=3)
+            .prop_map(|(bits, delta)| power_of_two::<F>(bits) + signed::<F>(delta)),
+        (1u64..1024).prop_map(|v| -F::from(v)),
+        (any::<[u8; 32]>(), any::<[u8; 32]>()).prop_map(|(lo, hi)| {
+            let mut bytes = [0u8; 64];
+            bytes[..32].copy_from_slice(&lo);
+            bytes[32..].copy_from_slice(&hi);
+            F::from_bytes_wide(&bytes)
+        }),
+    ]
+}
+
+/// Field elements within `delta` of `2^bits`.
+pub(crate) fn near_power_of_two<F: FieldExt>(bits: u64, delta: i64) -> impl Strategy<Value = F> {
+    (-delta..=delta).prop_map(move |d| power_of_two::<F>(bits) + signed::<F>(d))
+}
+
+/// `v` as an integer, if it is smaller than `2^128`.
+pub(crate) fn to_u128<F: FieldExt>(v: F) -> Option<u128> {
+    let lower = v.get_lower_128();
+    (F::from_u128(lower) == v).then(|| lower)
+}
+
+pub(crate) fn power_of_two<F: FieldExt>(bits: u64) -> F {
+    F::from(2u64).pow_vartime([bits])
+}
+
+fn signed<F: FieldExt>(v: i64) -> F {
+    if v < 0 {
+        -F::from(v.unsigned_abs())
+    } else {
+        F::from(v as u64)
+    }
+}
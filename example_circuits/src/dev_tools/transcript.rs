@@ -0,0 +1,265 @@
+use std::{fmt, io};
+
+use halo2_proofs::{
+    arithmetic::CurveAffine,
+    plonk::{create_proof, verify_proof, Circuit, Error, ProvingKey, SingleVerifier, VerifyingKey},
+    poly::commitment::Params,
+    transcript::{
+        Blake2bRead, Blake2bWrite, Challenge255, EncodedChallenge, Transcript, TranscriptRead,
+        TranscriptWrite,
+    },
+};
+use rand::rngs::OsRng;
+
+/// One message of a Fiat–Shamir transcript.
+///
+/// Both sides absorb the verifying key and the instance commitments themselves; everything
+/// else the prover writes into the proof and the verifier reads back, so with the same
+/// circuit, instance and proof the two logs are identical.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Entry<C: CurveAffine> {
+    /// Absorbed without being sent: the verifying key and instance commitments.
+    CommonPoint(C),
+    CommonScalar(C::Scalar),
+    /// Sent by the prover: commitments.
+    Point(C),
+    /// Sent by the prover: evaluations, and the last scalars of the IPA opening.
+    Scalar(C::Scalar),
+    Challenge(C::Scalar),
+}
+
+impl<C: CurveAffine> fmt::Display for Entry<C> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Entry::CommonPoint(point) => write!(f, "common point  {:?}", point),
+            Entry::CommonScalar(scalar) => write!(f, "common scalar {:?}", scalar),
+            Entry::Point(point) => write!(f, "point         {:?}", point),
+            Entry::Scalar(scalar) => write!(f, "scalar        {:?}", scalar),
+            Entry::Challenge(challenge) => write!(f, "challenge     {:?}", challenge),
+        }
+    }
+}
+
+/// Wraps a transcript and logs every message that goes through it, so the prover's and the
+/// verifier's view of a proof can be compared message by message.
+///
+/// ```text
+///    0  common scalar 0x1d3b...   (the verifying key)
+///    1  common point  (0x..., 0x...)   (the instance commitment)
+///    2  point         (0x..., 0x...)   (the first advice commitment)
+///    3  challenge     0x2a7f...   (theta)
+/// ```
+#[derive(Debug)]
+pub struct Inspector<C: CurveAffine, T> {
+    inner: T,
+    log: Vec<Entry<C>>,
+}
+
+impl<C: CurveAffine, T> Inspector<C, T> {
+    pub fn new(inner: T) -> Self {
+        Self { inner, log: vec![] }
+    }
+
+    pub fn log(&self) -> &[Entry<C>] {
+        &self.log
+    }
+
+    pub fn into_parts(self) -> (T, Vec<Entry<C>>) {
+        (self.inner, self.log)
+    }
+}
+
+impl<C: CurveAffine, E: EncodedChallenge<C>, T: Transcript<C, E>> Transcript<C, E>
+    for Inspector<C, T>
+{
+    fn squeeze_challenge(&mut self) -> E {
+        let challenge = self.inner.squeeze_challenge();
+        self.log.push(Entry::Challenge(challenge.get_scalar()));
+        challenge
+    }
+
+    fn common_point(&mut self, point: C) -> io::Result<()> {
+        self.inner.common_point(point)?;
+        self.log.push(Entry::CommonPoint(point));
+        Ok(())
+    }
+
+    fn common_scalar(&mut self, scalar: C::Scalar) -> io::Result<()> {
+        self.inner.common_scalar(scalar)?;
+        self.log.push(Entry::CommonScalar(scalar));
+        Ok(())
+    }
+}
+
+impl<C: CurveAffine, E: EncodedChallenge<C>, T: TranscriptWrite<C, E>> TranscriptWrite<C, E>
+    for Inspector<C, T>
+{
+    fn write_point(&mut self, point: C) -> io::Result<()> {
+        self.inner.write_point(point)?;
+        self.log.push(Entry::Point(point));
+        Ok(())
+    }
+
+    fn write_scalar(&mut self, scalar: C::Scalar) -> io::Result<()> {
+        self.inner.write_scalar(scalar)?;
+        self.log.push(Entry::Scalar(scalar));
+        Ok(())
+    }
+}
+
+impl<C: CurveAffine, E: EncodedChallenge<C>, T: TranscriptRead<C, E>> TranscriptRead<C, E>
+    for Inspector<C, T>
+{
+    fn read_point(&mut self) -> io::Result<C> {
+        let point = self.inner.read_point()?;
+        self.log.push(Entry::Point(point));
+        Ok(point)
+    }
+
+    fn read_scalar(&mut self) -> io::Result<C::Scalar> {
+        let scalar = self.inner.read_scalar()?;
+        self.log.push(Entry::Scalar(scalar));
+        Ok(scalar)
+    }
+}
+
+/// The first message where two transcripts differ. `None` on a side means its log ended
+/// there, e.g. because the verifier couldn't read a point out of the proof.
+#[derive(Debug, Clone)]
+pub struct Divergence<C: CurveAffine> {
+    pub index: usize,
+    pub prover: Option<Entry<C>>,
+    pub verifier: Option<Entry<C>>,
+}
+
+impl<C: CurveAffine> fmt::Display for Divergence<C> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let side = |entry: &Option<Entry<C>>| match entry {
+            Some(entry) => entry.to_string(),
+            None => "(nothing)".to_string(),
+        };
+        write!(
+            f,
+            "transcripts diverge at message {}\n  prover:   {}\n  verifier: {}",
+            self.index,
+            side(&self.prover),
+            side(&self.verifier)
+        )
+    }
+}
+
+/// Compares the prover's and the verifier's logs. Once a message differs every challenge
+/// after it does too, so only the first difference says anything.
+///
+/// `None` while the verification failed means both sides saw the same messages and got the
+/// same challenges: the proof is simply wrong for this circuit and instance.
+pub fn diff<C: CurveAffine>(prover: &[Entry<C>], verifier: &[Entry<C>]) -> Option<Divergence<C>> {
+    (0..prover.len().max(verifier.len()))
+        .find(|i| prover.get(*i) != verifier.get(*i))
+        .map(|index| Divergence {
+            index,
+            prover: prover.get(index).cloned(),
+            verifier: verifier.get(index).cloned(),
+        })
+}
+
+/// `create_proof` with a logged Blake2b transcript, for a circuit with a single instance.
+pub fn prove<C: CurveAffine, ConcreteCircuit: Circuit<C::Scalar>>(
+    params: &Params<C>,
+    pk: &ProvingKey<C>,
+    circuit: ConcreteCircuit,
+    instance: &[&[C::Scalar]],
+) -> Result<(Vec<u8>, Vec<Entry<C>>), Error> {
+    let mut transcript = Inspector::new(Blake2bWrite::<_, _, Challenge255<_>>::init(vec![]));
+    create_proof(params, pk, &[circuit], &[instance], OsRng, &mut transcript)?;
+    let (transcript, log) = transcript.into_parts();
+    Ok((transcript.finalize(), log))
+}
+
+/// `verify_proof` with a logged Blake2b transcript. The log is returned even when the
+/// verification fails, which is when it's interesting.
+pub fn verify<C: CurveAffine>(
+    params: &Params<C>,
+    vk: &VerifyingKey<C>,
+    instance: &[&[C::Scalar]],
+    proof: &[u8],
+) -> (Vec<Entry<C>>, Result<(), Error>) {
+    let mut transcript = Inspector::new(Blake2bRead::<_, _, Challenge255<_>>::init(proof));
+    let strategy = SingleVerifier::new(params);
+    let result = verify_proof(params, vk, strategy, &[instance], &mut transcript);
+    (transcript.into_parts().1, result)
+}
+
+#[cfg(test)]
+mod tests {
+    use halo2_proofs::{
+        circuit::Value,
+        pasta::{EqAffine, Fp},
+    };
+
+    use super::*;
+    use crate::{
+        dev_tools::prover,
+        mimc::{native, MyCircuit},
+    };
+
+    fn setup() -> (Params<EqAffine>, ProvingKey<EqAffine>, MyCircuit<Fp, 2>, Fp) {
+        let message = [Fp::one(), Fp::from(2)];
+        let digest = native::hash(&message, &native::round_constants());
+        let circuit = MyCircuit::<Fp, 2> {
+            message: message.iter().map(|m| Value::known(*m)).collect(),
+        };
+        let (params, pk) = prover::setup(&circuit);
+        (params, pk, circuit, digest)
+    }
+
+    #[test]
+    fn test_same_transcripts() {
+        let (params, pk, circuit, digest) = setup();
+        let (proof, prover) = prove(&params, &pk, circuit, &[&[digest]]).unwrap();
+        let (verifier, result) = verify(&params, pk.get_vk(), &[&[digest]], &proof);
+        assert!(result.is_ok());
+        assert!(diff(&prover, &verifier).is_none());
+
+        // the verifying key, then the commitment to the digest
+        assert!(matches!(prover[0], Entry::CommonScalar(_)));
+        assert!(matches!(prover[1], Entry::CommonPoint(_)));
+        // the IPA opening ends with two scalars
+        assert!(matches!(
+            prover[prover.len() - 2..],
+            [Entry::Scalar(_), Entry::Scalar(_)]
+        ));
+    }
+
+    #[test]
+    fn test_wrong_instance() {
+        let (params, pk, circuit, digest) = setup();
+        let (proof, prover) = prove(&params, &pk, circuit, &[&[digest]]).unwrap();
+        let (verifier, result) = verify(&params, pk.get_vk(), &[&[digest + Fp::one()]], &proof);
+        assert!(result.is_err());
+
+        let divergence = diff(&prover, &verifier).unwrap();
+        assert_eq!(divergence.index, 1);
+        assert!(matches!(divergence.verifier, Some(Entry::CommonPoint(_))));
+
+        let text = divergence.to_string();
+        assert!(text.starts_with("transcripts diverge at message 1\n"));
+        assert!(text.contains("\n  prover:   common point  "));
+        assert!(text.contains("\n  verifier: common point  "));
+    }
+
+    #[test]
+    fn test_tampered_proof() {
+        let (params, pk, circuit, digest) = setup();
+        let (mut proof, prover) = prove(&params, &pk, circuit, &[&[digest]]).unwrap();
+        // the lowest byte of the last scalar, which stays canonical
+        let last = proof.len() - 32;
+        proof[last] ^= 1;
+
+        let (verifier, result) = verify(&params, pk.get_vk(), &[&[digest]], &proof);
+        assert!(result.is_err());
+        let divergence = diff(&prover, &verifier).unwrap();
+        assert_eq!(divergence.index, prover.len() - 1);
+        assert!(matches!(divergence.verifier, Some(Entry::Scalar(_))));
+    }
+}
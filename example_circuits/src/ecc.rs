@@ -0,0 +1,1122 @@
+use std::marker::PhantomData;
+
+use ff::PrimeFieldBits;
+use group::{prime::PrimeCurveAffine, Curve};
+use halo2_proofs::{
+    arithmetic::{CurveAffine, FieldExt},
+    circuit::{floor_planner::V1, AssignedCell, Layouter, Region, Value},
+    plonk::{
+        Advice, Circuit, Column, ConstraintSystem, Constraints, Error, Expression, Fixed, Instance,
+        Selector,
+    },
+    poly::Rotation,
+};
+
+pub mod windowed;
+
+/// A point in the circuit. The identity is `(0, 0)`, which is not on the curve: that
+/// would need `0 = 0^3 + b`, and on Pallas and Vesta there isn't even a point with
+/// `x = 0`, because `b = 5` is not a square.
+#[derive(Debug, Clone)]
+pub struct EcPoint<C: CurveAffine> {
+    pub x: AssignedCell<C::Base, C::Base>,
+    pub y: AssignedCell<C::Base, C::Base>,
+}
+
+/// The coordinates of `p`, with the identity as `(0, 0)`.
+pub fn coordinates<C: CurveAffine>(p: C) -> (C::Base, C::Base) {
+    Option::from(p.coordinates())
+        .map(|c| (*c.x(), *c.y()))
+        .unwrap_or((C::Base::zero(), C::Base::zero()))
+}
+
+/// Point arithmetic on a curve `y^2 = x^3 + b` whose base field is the circuit's field,
+/// so the coordinates are native: Pallas points in a circuit over `Fp` (= the Vesta
+/// scalar field), or Vesta points over `Fq`.
+///
+/// Every operation is one region on four advice columns, with the inputs in the first
+/// row and the result below:
+///
+///     on curve:          | x_p | y_p |     |     |    y_p^2 = x_p^3 + b
+///
+///     add (incomplete):  | x_p | y_p | x_q | y_q |
+///                        | x_r | y_r |     |     |
+///
+///     double:            | x_p | y_p |     |     |
+///                        | x_r | y_r |     |     |
+///
+///     add (complete):    | x_p | y_p | x_q | y_q |
+///                        | x_r | y_r |  λ  |  α  |
+///                        |  β  |  γ  |  δ  |     |
+///
+///     select:            | bit |  x  |  y  |     |    (x, y) = bit ? (X, Y) : (0, 0)
+///
+///     select variable:   | bit | x_p | y_p |     |
+///                        |     |  x  |  y  |     |    (x, y) = bit ? P : (0, 0)
+///
+///     bits:              | z_i | b_i |     |     |    z_i = 2 z_{i+1} + b_i, b_i boolean
+///                        |z_i+1|     |     |     |
+///
+/// The incomplete addition and the doubling don't witness the slope: with
+/// `λ = (y_p - y_q) / (x_p - x_q)` multiplied out, they are degree 3 and 4. The complete
+/// addition witnesses `λ` and the inverses `α = 1/(x_q - x_p)`, `β = 1/x_p`,
+/// `γ = 1/x_q` and `δ = 1/(y_q + y_p)` (zero where there is no inverse) to tell apart the
+/// cases `P = O`, `Q = O`, `P = Q`, `P = -Q` and the general one.
+///
+/// `select` reads `(X, Y)` from two fixed columns and is what the fixed-base
+/// multiplication is made of. The variable-base one is double-and-add over the bits of
+/// a running sum, selecting between `P` and the identity.
+#[derive(Debug, Clone)]
+pub struct EccConfig<C: CurveAffine> {
+    advice: [Column<Advice>; 4],
+    base_x: Column<Fixed>,
+    base_y: Column<Fixed>,
+    q_on_curve: Selector,
+    q_add_incomplete: Selector,
+    q_double: Selector,
+    q_add: Selector,
+    q_select: Selector,
+    q_select_var: Selector,
+    q_bit: Selector,
+    _marker: PhantomData<C>,
+}
+
+#[derive(Debug, Clone)]
+pub struct EccChip<C: CurveAffine> {
+    config: EccConfig<C>,
+}
+
+impl<C: CurveAffine> EccChip<C> {
+    pub fn construct(config: EccConfig<C>) -> Self {
+        Self { config }
+    }
+
+    pub fn configure(
+        meta: &mut ConstraintSystem<C::Base>,
+        advice: [Column<Advice>; 4],
+    ) -> EccConfig<C> {
+        // the formulas below are for `a = 0`
+        assert!(C::a() == C::Base::zero());
+
+        for column in advice {
+            meta.enable_equality(column);
+        }
+        let base_x = meta.fixed_column();
+        let base_y = meta.fixed_column();
+        // for the zero at the end of a scalar's running sum
+        let constant = meta.fixed_column();
+        meta.enable_constant(constant);
+
+        let q_on_curve = meta.selector();
+        let q_add_incomplete = meta.selector();
+        let q_double = meta.selector();
+        let q_add = meta.selector();
+        let q_select = meta.selector();
+        let q_select_var = meta.selector();
+        let q_bit = meta.selector();
+
+        let constant = |v: u64| Expression::Constant(C::Base::from(v));
+
+        meta.create_gate("on curve", |meta| {
+            let q_on_curve = meta.query_selector(q_on_curve);
+            let x = meta.query_advice(advice[0], Rotation::cur());
+            let y = meta.query_advice(advice[1], Rotation::cur());
+
+            Constraints::with_selector(
+                q_on_curve,
+                [(
+                    "y^2 = x^3 + b",
+                    y.clone() * y - (x.clone() * x.clone() * x + Expression::Constant(C::b())),
+                )],
+            )
+        });
+
+        meta.create_gate("add incomplete", |meta| {
+            let q_add_incomplete = meta.query_selector(q_add_incomplete);
+            let x_p = meta.query_advice(advice[0], Rotation::cur());
+            let y_p = meta.query_advice(advice[1], Rotation::cur());
+            let x_q = meta.query_advice(advice[2], Rotation::cur());
+            let y_q = meta.query_advice(advice[3], Rotation::cur());
+            let x_r = meta.query_advice(advice[0], Rotation::next());
+            let y_r = meta.query_advice(advice[1], Rotation::next());
+
+            let dx = x_p.clone() - x_q.clone();
+            let dy = y_p - y_q.clone();
+            Constraints::with_selector(
+                q_add_incomplete,
+                [
+                    // x_r = λ^2 - x_p - x_q
+                    (
+                        "x_r",
+                        (x_r.clone() + x_q.clone() + x_p) * dx.clone() * dx.clone()
+                            - dy.clone() * dy.clone(),
+                    ),
+                    // y_r = λ (x_q - x_r) - y_q
+                    ("y_r", (y_r + y_q) * dx - dy * (x_q - x_r)),
+                ],
+            )
+        });
+
+        meta.create_gate("double", |meta| {
+            let q_double = meta.query_selector(q_double);
+            let x_p = meta.query_advice(advice[0], Rotation::cur());
+            let y_p = meta.query_advice(advice[1], Rotation::cur());
+            let x_r = meta.query_advice(advice[0], Rotation::next());
+            let y_r = meta.query_advice(advice[1], Rotation::next());
+
+            // λ = 3 x_p^2 / (2 y_p)
+            let x_p_sq = x_p.clone() * x_p.clone();
+            Constraints::with_selector(
+                q_double,
+                [
+                    // x_r = λ^2 - 2 x_p
+                    (
+                        "x_r",
+                        constant(4)
+                            * y_p.clone()
+                            * y_p.clone()
+                            * (x_r.clone() + constant(2) * x_p.clone())
+                            - constant(9) * x_p_sq.clone() * x_p_sq.clone(),
+                    ),
+                    // y_r = λ (x_p - x_r) - y_p
+                    (
+                        "y_r",
+                        constant(2) * y_p.clone() * (y_r + y_p)
+                            - constant(3) * x_p_sq * (x_p - x_r),
+                    ),
+                ],
+            )
+        });
+
+        meta.create_gate("add complete", |meta| {
+            let q_add = meta.query_selector(q_add);
+            let x_p = meta.query_advice(advice[0], Rotation::cur());
+            let y_p = meta.query_advice(advice[1], Rotation::cur());
+            let x_q = meta.query_advice(advice[2], Rotation::cur());
+            let y_q = meta.query_advice(advice[3], Rotation::cur());
+            let x_r = meta.query_advice(advice[0], Rotation::next());
+            let y_r = meta.query_advice(advice[1], Rotation::next());
+            let lambda = meta.query_advice(advice[2], Rotation::next());
+            let alpha = meta.query_advice(advice[3], Rotation::next());
+            let beta = meta.query_advice(advice[0], Rotation(2));
+            let gamma = meta.query_advice(advice[1], Rotation(2));
+            let delta = meta.query_advice(advice[2], Rotation(2));
+
+            let one = constant(1);
+            let dx = x_q.clone() - x_p.clone();
+            let sum_y = y_q.clone() + y_p.clone();
+            // the general formulas, when they apply
+            let x_formula =
+                lambda.clone() * lambda.clone() - x_p.clone() - x_q.clone() - x_r.clone();
+            let y_formula =
+                lambda.clone() * (x_p.clone() - x_r.clone()) - y_p.clone() - y_r.clone();
+            // both non-zero unless P or Q is the identity
+            let x_pq = x_p.clone() * x_q.clone();
+            // 1 unless P = -Q (or both are the identity)
+            let if_not_inverse = one.clone() - dx.clone() * alpha.clone() - sum_y.clone() * delta;
+            // 1 - x_p β is 1 only if P = O, and the same for Q
+            let if_p_is_o = one.clone() - x_p.clone() * beta;
+            let if_q_is_o = one.clone() - x_q.clone() * gamma;
+
+            Constraints::with_selector(
+                q_add,
+                [
+                    // λ is the slope of the chord if x_p != x_q ...
+                    (
+                        "chord",
+                        dx.clone() * (dx.clone() * lambda.clone() - (y_q.clone() - y_p.clone())),
+                    ),
+                    // ... and of the tangent if x_p = x_q
+                    (
+                        "tangent",
+                        (one - dx.clone() * alpha)
+                            * (constant(2) * y_p.clone() * lambda
+                                - constant(3) * x_p.clone() * x_p.clone()),
+                    ),
+                    (
+                        "x_r if x_p != x_q",
+                        x_pq.clone() * dx.clone() * x_formula.clone(),
+                    ),
+                    ("y_r if x_p != x_q", x_pq.clone() * dx * y_formula.clone()),
+                    ("x_r if P = Q", x_pq.clone() * sum_y.clone() * x_formula),
+                    ("y_r if P = Q", x_pq * sum_y * y_formula),
+                    ("x_r if P = O", if_p_is_o.clone() * (x_r.clone() - x_q)),
+                    ("y_r if P = O", if_p_is_o * (y_r.clone() - y_q)),
+                    ("x_r if Q = O", if_q_is_o.clone() * (x_r.clone() - x_p)),
+                    ("y_r if Q = O", if_q_is_o * (y_r.clone() - y_p)),
+                    ("x_r if P = -Q", if_not_inverse.clone() * x_r),
+                    ("y_r if P = -Q", if_not_inverse * y_r),
+                ],
+            )
+        });
+
+        meta.create_gate("select", |meta| {
+            let q_select = meta.query_selector(q_select);
+            let bit = meta.query_advice(advice[0], Rotation::cur());
+            let x = meta.query_advice(advice[1], Rotation::cur());
+            let y = meta.query_advice(advice[2], Rotation::cur());
+            let base_x = meta.query_fixed(base_x, Rotation::cur());
+            let base_y = meta.query_fixed(base_y, Rotation::cur());
+
+            Constraints::with_selector(
+                q_select,
+                [
+                    ("bit is boolean", bit.clone() * (constant(1) - bit.clone())),
+                    ("x", x - bit.clone() * base_x),
+                    ("y", y - bit * base_y),
+                ],
+            )
+        });
+
+        meta.create_gate("select variable", |meta| {
+            let q_select_var = meta.query_selector(q_select_var);
+            let bit = meta.query_advice(advice[0], Rotation::cur());
+            let x_p = meta.query_advice(advice[1], Rotation::cur());
+            let y_p = meta.query_advice(advice[2], Rotation::cur());
+            let x = meta.query_advice(advice[1], Rotation::next());
+            let y = meta.query_advice(advice[2], Rotation::next());
+
+            Constraints::with_selector(
+                q_select_var,
+                [
+                    ("bit is boolean", bit.clone() * (constant(1) - bit.clone())),
+                    ("x", x - bit.clone() * x_p),
+                    ("y", y - bit * y_p),
+                ],
+            )
+        });
+
+        meta.create_gate("bits", |meta| {
+            let q_bit = meta.query_selector(q_bit);
+            let z = meta.query_advice(advice[0], Rotation::cur());
+            let bit = meta.query_advice(advice[1], Rotation::cur());
+            let z_next = meta.query_advice(advice[0], Rotation::next());
+
+            Constraints::with_selector(
+                q_bit,
+                [
+                    ("bit is boolean", bit.clone() * (constant(1) - bit.clone())),
+                    ("running sum", z - (constant(2) * z_next + bit)),
+                ],
+            )
+        });
+
+        EccConfig {
+            advice,
+            base_x,
+            base_y,
+            q_on_curve,
+            q_add_incomplete,
+            q_double,
+            q_add,
+            q_select,
+            q_select_var,
+            q_bit,
+            _marker: PhantomData,
+        }
+    }
+
+    /// Witnesses `(x, y)` and checks that it is on the curve, which rules out the identity.
+    fn assign_point(
+        &self,
+        mut layouter: impl Layouter<C::Base>,
+        x: Value<C::Base>,
+        y: Value<C::Base>,
+    ) -> Result<EcPoint<C>, Error> {
+        let config = &self.config;
+
+        layouter.assign_region(
+            || "witness point",
+            |mut region| {
+                config.q_on_curve.enable(&mut region, 0)?;
+                let x = region.assign_advice(|| "x", config.advice[0], 0, || x)?;
+                let y = region.assign_advice(|| "y", config.advice[1], 0, || y)?;
+                Ok(EcPoint { x, y })
+            },
+        )
+    }
+
+    /// Witnesses a point other than the identity.
+    pub fn witness_point(
+        &self,
+        layouter: impl Layouter<C::Base>,
+        point: Value<C>,
+    ) -> Result<EcPoint<C>, Error> {
+        let (x, y) = point.map(coordinates).unzip();
+        self.assign_point(layouter, x, y)
+    }
+
+    /// `P + Q`, for `P, Q != O` with `x_p != x_q`, which rules out `P = ±Q`.
+    pub fn add_incomplete(
+        &self,
+        mut layouter: impl Layouter<C::Base>,
+        p: &EcPoint<C>,
+        q: &EcPoint<C>,
+    ) -> Result<EcPoint<C>, Error> {
+        let config = &self.config;
+
+        // the gate says nothing about the result when x_p = x_q
+        p.x.value()
+            .zip(q.x.value())
+            .error_if_known_and(|(x_p, x_q)| x_p == x_q)?;
+
+        layouter.assign_region(
+            || "add incomplete",
+            |mut region| {
+                config.q_add_incomplete.enable(&mut region, 0)?;
+                let (x_p, y_p, x_q, y_q) = self.copy_inputs(&mut region, p, q)?;
+
+                let r = x_p
+                    .zip(y_p)
+                    .zip(x_q.zip(y_q))
+                    .map(|((x_p, y_p), (x_q, y_q))| {
+                        let lambda = (y_q - y_p) * (x_q - x_p).invert().unwrap();
+                        let x_r = lambda.square() - x_p - x_q;
+                        (x_r, lambda * (x_p - x_r) - y_p)
+                    });
+                self.assign_result(&mut region, r)
+            },
+        )
+    }
+
+    /// `2P`, for `P != O`. There are no points of order 2 on Pallas or Vesta, so `y_p`
+    /// is only 0 for the identity `(0, 0)`, which the gate can't tell from a point and
+    /// whose tangent has no slope.
+    pub fn double(
+        &self,
+        mut layouter: impl Layouter<C::Base>,
+        p: &EcPoint<C>,
+    ) -> Result<EcPoint<C>, Error> {
+        let config = &self.config;
+
+        // like x_p = x_q in the incomplete addition, the gate is no help when y_p = 0
+        p.y.value()
+            .error_if_known_and(|y_p| y_p.is_zero_vartime())?;
+
+        layouter.assign_region(
+            || "double",
+            |mut region| {
+                config.q_double.enable(&mut region, 0)?;
+                let x_p =
+                    p.x.copy_advice(|| "x_p", &mut region, config.advice[0], 0)?;
+                let y_p =
+                    p.y.copy_advice(|| "y_p", &mut region, config.advice[1], 0)?;
+
+                let r = x_p.value().zip(y_p.value()).map(|(x_p, y_p)| {
+                    let lambda =
+                        C::Base::from(3u64) * x_p.square() * (y_p.double()).invert().unwrap();
+                    let x_r = lambda.square() - x_p.double();
+                    (x_r, lambda * (*x_p - x_r) - y_p)
+                });
+                self.assign_result(&mut region, r)
+            },
+        )
+    }
+
+    /// `P + Q` for any `P` and `Q`, including the identity.
+    pub fn add(
+        &self,
+        mut layouter: impl Layouter<C::Base>,
+        p: &EcPoint<C>,
+        q: &EcPoint<C>,
+    ) -> Result<EcPoint<C>, Error> {
+        let config = &self.config;
+
+        layouter.assign_region(
+            || "add complete",
+            |mut region| {
+                config.q_add.enable(&mut region, 0)?;
+                let (x_p, y_p, x_q, y_q) = self.copy_inputs(&mut region, p, q)?;
+
+                let witness = x_p
+                    .zip(y_p)
+                    .zip(x_q.zip(y_q))
+                    .map(|(p, q)| complete_add_witness(p, q));
+                let r = self.assign_result(&mut region, witness.map(|w| (w[0], w[1])))?;
+
+                let advice = config.advice;
+                for (name, i, column, row) in [
+                    ("lambda", 2, advice[2], 1),
+                    ("alpha", 3, advice[3], 1),
+                    ("beta", 4, advice[0], 2),
+                    ("gamma", 5, advice[1], 2),
+                    ("delta", 6, advice[2], 2),
+                ] {
+                    region.assign_advice(|| name, column, row, || witness.map(|w| w[i]))?;
+                }
+                Ok(r)
+            },
+        )
+    }
+
+    /// Constrains `P = Q`.
+    pub fn constrain_equal(
+        &self,
+        mut layouter: impl Layouter<C::Base>,
+        p: &EcPoint<C>,
+        q: &EcPoint<C>,
+    ) -> Result<(), Error> {
+        layouter.assign_region(
+            || "constrain equal",
+            |mut region| {
+                region.constrain_equal(p.x.cell(), q.x.cell())?;
+                region.constrain_equal(p.y.cell(), q.y.cell())
+            },
+        )
+    }
+
+    fn copy_inputs(
+        &self,
+        region: &mut Region<'_, C::Base>,
+        p: &EcPoint<C>,
+        q: &EcPoint<C>,
+    ) -> Result<
+        (
+            Value<C::Base>,
+            Value<C::Base>,
+            Value<C::Base>,
+            Value<C::Base>,
+        ),
+        Error,
+    > {
+        let advice = self.config.advice;
+        let x_p = p.x.copy_advice(|| "x_p", region, advice[0], 0)?;
+        let y_p = p.y.copy_advice(|| "y_p", region, advice[1], 0)?;
+        let x_q = q.x.copy_advice(|| "x_q", region, advice[2], 0)?;
+        let y_q = q.y.copy_advice(|| "y_q", region, advice[3], 0)?;
+        Ok((
+            x_p.value().copied(),
+            y_p.value().copied(),
+            x_q.value().copied(),
+            y_q.value().copied(),
+        ))
+    }
+
+    fn assign_result(
+        &self,
+        region: &mut Region<'_, C::Base>,
+        r: Value<(C::Base, C::Base)>,
+    ) -> Result<EcPoint<C>, Error> {
+        let (x_r, y_r) = r.unzip();
+        let x = region.assign_advice(|| "x_r", self.config.advice[0], 1, || x_r)?;
+        let y = region.assign_advice(|| "y_r", self.config.advice[1], 1, || y_r)?;
+        Ok(EcPoint { x, y })
+    }
+
+    /// `bit ? base : O`, with `base` in the fixed columns.
+    fn select(
+        &self,
+        mut layouter: impl Layouter<C::Base>,
+        bit: Value<bool>,
+        base: C,
+    ) -> Result<EcPoint<C>, Error> {
+        let config = &self.config;
+        let (base_x, base_y) = coordinates(base);
+
+        layouter.assign_region(
+            || "select",
+            |mut region| {
+                config.q_select.enable(&mut region, 0)?;
+                region.assign_fixed(|| "X", config.base_x, 0, || Value::known(base_x))?;
+                region.assign_fixed(|| "Y", config.base_y, 0, || Value::known(base_y))?;
+
+                region.assign_advice(
+                    || "bit",
+                    config.advice[0],
+                    0,
+                    || bit.map(|b| C::Base::from(b as u64)),
+                )?;
+                let x = region.assign_advice(
+                    || "x",
+                    config.advice[1],
+                    0,
+                    || bit.map(|b| if b { base_x } else { C::Base::zero() }),
+                )?;
+                let y = region.assign_advice(
+                    || "y",
+                    config.advice[2],
+                    0,
+                    || bit.map(|b| if b { base_y } else { C::Base::zero() }),
+                )?;
+                Ok(EcPoint { x, y })
+            },
+        )
+    }
+
+    /// `bit ? P : O`.
+    fn select_variable(
+        &self,
+        mut layouter: impl Layouter<C::Base>,
+        bit: &AssignedCell<C::Base, C::Base>,
+        p: &EcPoint<C>,
+    ) -> Result<EcPoint<C>, Error> {
+        let config = &self.config;
+
+        layouter.assign_region(
+            || "select variable",
+            |mut region| {
+                config.q_select_var.enable(&mut region, 0)?;
+                let bit = bit.copy_advice(|| "bit", &mut region, config.advice[0], 0)?;
+                let x_p =
+                    p.x.copy_advice(|| "x_p", &mut region, config.advice[1], 0)?;
+                let y_p =
+                    p.y.copy_advice(|| "y_p", &mut region, config.advice[2], 0)?;
+
+                let x = region.assign_advice(
+                    || "x",
+                    config.advice[1],
+                    1,
+                    || bit.value().zip(x_p.value()).map(|(b, x)| *b * x),
+                )?;
+                let y = region.assign_advice(
+                    || "y",
+                    config.advice[2],
+                    1,
+                    || bit.value().zip(y_p.value()).map(|(b, y)| *b * y),
+                )?;
+                Ok(EcPoint { x, y })
+            },
+        )
+    }
+}
+
+impl<C: CurveAffine> EccChip<C>
+where
+    C::Base: PrimeFieldBits,
+{
+    /// The bits of `scalar`, least significant first, from a running sum
+    /// `z_0 = scalar, z_{i+1} = (z_i - b_i) / 2` that has to end at zero.
+    fn decompose_scalar(
+        &self,
+        mut layouter: impl Layouter<C::Base>,
+        scalar: &AssignedCell<C::Base, C::Base>,
+    ) -> Result<Vec<AssignedCell<C::Base, C::Base>>, Error> {
+        let config = &self.config;
+        let num_bits = C::Base::NUM_BITS as usize;
+        let bits = scalar
+            .value()
+            .map(|s| {
+                s.to_le_bits()
+                    .iter()
+                    .by_vals()
+                    .take(num_bits)
+                    .collect::<Vec<_>>()
+            })
+            .transpose_vec(num_bits);
+        let half = C::Base::from(2u64).invert().unwrap();
+
+        layouter.assign_region(
+            || "decompose scalar",
+            |mut region| {
+                let mut z = scalar.copy_advice(|| "z_0", &mut region, config.advice[0], 0)?;
+                let mut assigned = vec![];
+                for (i, bit) in bits.iter().enumerate() {
+                    config.q_bit.enable(&mut region, i)?;
+                    let b = region.assign_advice(
+                        || format!("b_{}", i),
+                        config.advice[1],
+                        i,
+                        || bit.map(|b| C::Base::from(b as u64)),
+                    )?;
+                    let z_next = z.value().zip(b.value()).map(|(z, b)| (*z - b) * half);
+                    z = region.assign_advice(
+                        || format!("z_{}", i + 1),
+                        config.advice[0],
+                        i + 1,
+                        || z_next,
+                    )?;
+                    assigned.push(b);
+                }
+                region.constrain_constant(z.cell(), C::Base::zero())?;
+                Ok(assigned)
+            },
+        )
+    }
+
+    /// `scalar * P`, reading `scalar` as an integer below the base field modulus `p`:
+    /// double-and-add from the most significant bit, with complete additions throughout
+    /// since the accumulator starts at the identity. That is nine rows per bit, counting
+    /// the decomposition.
+    ///
+    /// The bits are only checked to add up to `scalar` modulo `p`, so a prover could use
+    /// `scalar + p` instead whenever that still fits in the bits. Real gadgets add a
+    /// canonicity check on top; the examples here leave it out.
+    pub fn mul(
+        &self,
+        mut layouter: impl Layouter<C::Base>,
+        scalar: &AssignedCell<C::Base, C::Base>,
+        p: &EcPoint<C>,
+    ) -> Result<EcPoint<C>, Error> {
+        let bits = self.decompose_scalar(layouter.namespace(|| "bits"), scalar)?;
+
+        let mut acc: Option<EcPoint<C>> = None;
+        for (i, bit) in bits.iter().enumerate().rev() {
+            let term =
+                self.select_variable(layouter.namespace(|| format!("select {}", i)), bit, p)?;
+            acc = Some(match acc {
+                None => term,
+                Some(acc) => {
+                    let double =
+                        self.add(layouter.namespace(|| format!("double {}", i)), &acc, &acc)?;
+                    self.add(layouter.namespace(|| format!("add {}", i)), &double, &term)?
+                }
+            });
+        }
+        Ok(acc.unwrap())
+    }
+}
+
+impl<C: CurveAffine> EccChip<C>
+where
+    C::Scalar: PrimeFieldBits,
+{
+    /// `scalar * base` for a fixed `base`: `sum_i b_i * 2^i base` over the bits `b_i` of
+    /// the scalar, with the `2^i base` computed outside the circuit and baked into the
+    /// fixed columns. That is one select and one complete addition per bit.
+    ///
+    /// The bits are not checked to be the canonical encoding of a scalar, but they don't
+    /// need to be: `k` and `k + q` give the same point.
+    pub fn mul_fixed(
+        &self,
+        mut layouter: impl Layouter<C::Base>,
+        base: C,
+        scalar: Value<C::Scalar>,
+    ) -> Result<EcPoint<C>, Error> {
+        let num_bits = C::Scalar::NUM_BITS as usize;
+        let bits = scalar
+            .map(|s| {
+                s.to_le_bits()
+                    .iter()
+                    .by_vals()
+                    .take(num_bits)
+                    .collect::<Vec<_>>()
+            })
+            .transpose_vec(num_bits);
+
+        let mut multiple = base.to_curve();
+        let mut acc: Option<EcPoint<C>> = None;
+        for (i, bit) in bits.into_iter().enumerate() {
+            let term = self.select(
+                layouter.namespace(|| format!("select {}", i)),
+                bit,
+                multiple.to_affine(),
+            )?;
+            acc = Some(match acc {
+                None => term,
+                Some(acc) => self.add(layouter.namespace(|| format!("add {}", i)), &acc, &term)?,
+            });
+            multiple = multiple.double();
+        }
+        Ok(acc.unwrap())
+    }
+}
+
+/// `[x_r, y_r, λ, α, β, γ, δ]` for the complete addition of `(x_p, y_p)` and `(x_q, y_q)`.
+fn complete_add_witness<F: FieldExt>((x_p, y_p): (F, F), (x_q, y_q): (F, F)) -> [F; 7] {
+    let inv = |v: F| v.invert().unwrap_or(F::zero());
+    let is_identity = |x: F, y: F| x.is_zero_vartime() && y.is_zero_vartime();
+
+    let lambda = if x_q != x_p {
+        (y_q - y_p) * inv(x_q - x_p)
+    } else {
+        // zero if P = Q = O, where λ is unconstrained
+        F::from(3u64) * x_p.square() * inv(y_p.double())
+    };
+    let (x_r, y_r) = if is_identity(x_p, y_p) {
+        (x_q, y_q)
+    } else if is_identity(x_q, y_q) {
+        (x_p, y_p)
+    } else if x_q == x_p && y_q + y_p == F::zero() {
+        (F::zero(), F::zero())
+    } else {
+        let x_r = lambda.square() - x_p - x_q;
+        (x_r, lambda * (x_p - x_r) - y_p)
+    };
+    let delta = if x_q == x_p {
+        inv(y_q + y_p)
+    } else {
+        F::zero()
+    };
+
+    [x_r, y_r, lambda, inv(x_q - x_p), inv(x_p), inv(x_q), delta]
+}
+
+/// Proves knowledge of the secret key behind a public key, `PK = sk * G`, with `G` the
+/// generator of the curve and `PK` the public input as `(x, y)`.
+pub struct MyCircuit<C: CurveAffine> {
+    pub secret_key: Value<C::Scalar>,
+}
+
+impl<C: CurveAffine> Default for MyCircuit<C> {
+    fn default() -> Self {
+        Self {
+            secret_key: Value::unknown(),
+        }
+    }
+}
+
+impl<C: CurveAffine> MyCircuit<C> {
+    /// The public input: the coordinates of `public_key`.
+    pub fn instance(public_key: C) -> Vec<Vec<C::Base>> {
+        let (x, y) = coordinates(public_key);
+        vec![vec![x, y]]
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct MyConfig<C: CurveAffine> {
+    ecc: EccConfig<C>,
+    public_key: Column<Instance>,
+}
+
+impl<C: CurveAffine> Circuit<C::Base> for MyCircuit<C>
+where
+    C::Scalar: PrimeFieldBits,
+{
+    type Config = MyConfig<C>;
+    type FloorPlanner = V1;
+
+    fn without_witnesses(&self) -> Self {
+        Self::default()
+    }
+
+    fn configure(meta: &mut ConstraintSystem<C::Base>) -> Self::Config {
+        let advice = [(); 4].map(|_| meta.advice_column());
+        let public_key = meta.instance_column();
+        meta.enable_equality(public_key);
+
+        MyConfig {
+            ecc: EccChip::configure(meta, advice),
+            public_key,
+        }
+    }
+
+    fn synthesize(
+        &self,
+        config: Self::Config,
+        mut layouter: impl Layouter<C::Base>,
+    ) -> Result<(), Error> {
+        let chip = EccChip::construct(config.ecc.clone());
+
+        let public_key = chip.mul_fixed(
+            layouter.namespace(|| "sk * G"),
+            C::generator(),
+            self.secret_key,
+        )?;
+        layouter.constrain_instance(public_key.x.cell(), config.public_key, 0)?;
+        layouter.constrain_instance(public_key.y.cell(), config.public_key, 1)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use ff::{Field, PrimeField};
+    use group::Group;
+    use halo2_proofs::{
+        dev::MockProver,
+        pasta::{pallas, vesta, Fp},
+    };
+    use rand::rngs::OsRng;
+
+    use super::*;
+    use crate::dev_tools::{
+        failure::{assert_satisfied, verify},
+        min_k::min_k,
+    };
+
+    /// Runs every operation on `p` and `q` and exposes the results.
+    #[derive(Default)]
+    struct OpsCircuit {
+        p: Value<pallas::Affine>,
+        q: Value<pallas::Affine>,
+    }
+
+    impl Circuit<Fp> for OpsCircuit {
+        type Config = MyConfig<pallas::Affine>;
+        type FloorPlanner = V1;
+
+        fn without_witnesses(&self) -> Self {
+            Self::default()
+        }
+
+        fn configure(meta: &mut ConstraintSystem<Fp>) -> Self::Config {
+            MyCircuit::<pallas::Affine>::configure(meta)
+        }
+
+        fn synthesize(
+            &self,
+            config: Self::Config,
+            mut layouter: impl Layouter<Fp>,
+        ) -> Result<(), Error> {
+            let chip = EccChip::construct(config.ecc.clone());
+            let p = chip.witness_point(layouter.namespace(|| "P"), self.p)?;
+            let q = chip.witness_point(layouter.namespace(|| "Q"), self.q)?;
+            let minus_p = chip.witness_point(layouter.namespace(|| "-P"), self.p.map(|p| -p))?;
+
+            let sum = chip.add_incomplete(layouter.namespace(|| "P + Q"), &p, &q)?;
+            let complete_sum = chip.add(layouter.namespace(|| "P + Q"), &p, &q)?;
+            let double = chip.double(layouter.namespace(|| "2P"), &p)?;
+            let complete_double = chip.add(layouter.namespace(|| "P + P"), &p, &p)?;
+            let identity = chip.add(layouter.namespace(|| "P - P"), &p, &minus_p)?;
+            let left = chip.add(layouter.namespace(|| "O + Q"), &identity, &q)?;
+            let right = chip.add(layouter.namespace(|| "Q + O"), &q, &identity)?;
+            let both = chip.add(layouter.namespace(|| "O + O"), &identity, &identity)?;
+
+            let results = [
+                sum,
+                complete_sum,
+                double,
+                complete_double,
+                identity,
+                left,
+                right,
+                both,
+            ];
+            for (i, r) in results.iter().enumerate() {
+                layouter.constrain_instance(r.x.cell(), config.public_key, 2 * i)?;
+                layouter.constrain_instance(r.y.cell(), config.public_key, 2 * i + 1)?;
+            }
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn test_ops_match_pasta() {
+        let p = pallas::Point::random(OsRng).to_affine();
+        let q = pallas::Point::random(OsRng).to_affine();
+        let identity = pallas::Affine::identity();
+
+        let expected = [
+            (p + q).to_affine(),
+            (p + q).to_affine(),
+            (p + p).to_affine(),
+            (p + p).to_affine(),
+            identity,
+            q,
+            q,
+            identity,
+        ];
+        let instance = expected
+            .iter()
+            .flat_map(|r| {
+                let (x, y) = coordinates(*r);
+                [x, y]
+            })
+            .collect();
+
+        let circuit = OpsCircuit {
+            p: Value::known(p),
+            q: Value::known(q),
+        };
+        assert_satisfied(min_k(&circuit), &circuit, vec![instance]);
+    }
+
+    /// Witnesses `(x, y)` without knowing whether it is a point.
+    #[derive(Default)]
+    struct PointCircuit {
+        x: Value<Fp>,
+        y: Value<Fp>,
+    }
+
+    impl Circuit<Fp> for PointCircuit {
+        type Config = MyConfig<pallas::Affine>;
+        type FloorPlanner = V1;
+
+        fn without_witnesses(&self) -> Self {
+            Self::default()
+        }
+
+        fn configure(meta: &mut ConstraintSystem<Fp>) -> Self::Config {
+            MyCircuit::<pallas::Affine>::configure(meta)
+        }
+
+        fn synthesize(
+            &self,
+            config: Self::Config,
+            layouter: impl Layouter<Fp>,
+        ) -> Result<(), Error> {
+            EccChip::construct(config.ecc).assign_point(layouter, self.x, self.y)?;
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn test_on_curve() {
+        let (x, y) = coordinates(pallas::Point::random(OsRng).to_affine());
+        let circuit = PointCircuit {
+            x: Value::known(x),
+            y: Value::known(y),
+        };
+        let k = min_k(&circuit);
+        assert_satisfied(k, &circuit, vec![vec![]]);
+
+        for (x, y) in [(x, y + Fp::one()), (Fp::zero(), Fp::zero())] {
+            let circuit = PointCircuit {
+                x: Value::known(x),
+                y: Value::known(y),
+            };
+            let report = verify(k, &circuit, vec![vec![]]).unwrap_err();
+            assert!(report.groups[0].title.contains("on curve"));
+        }
+    }
+
+    /// Doubles `P - P`, which is the identity.
+    #[derive(Default)]
+    struct DoubleIdentityCircuit {
+        p: Value<pallas::Affine>,
+    }
+
+    impl Circuit<Fp> for DoubleIdentityCircuit {
+        type Config = MyConfig<pallas::Affine>;
+        type FloorPlanner = V1;
+
+        fn without_witnesses(&self) -> Self {
+            Self::default()
+        }
+
+        fn configure(meta: &mut ConstraintSystem<Fp>) -> Self::Config {
+            MyCircuit::<pallas::Affine>::configure(meta)
+        }
+
+        fn synthesize(
+            &self,
+            config: Self::Config,
+            mut layouter: impl Layouter<Fp>,
+        ) -> Result<(), Error> {
+            let chip = EccChip::construct(config.ecc);
+            let p = chip.witness_point(layouter.namespace(|| "P"), self.p)?;
+            let minus_p = chip.witness_point(layouter.namespace(|| "-P"), self.p.map(|p| -p))?;
+            let identity = chip.add(layouter.namespace(|| "P - P"), &p, &minus_p)?;
+            chip.double(layouter.namespace(|| "2O"), &identity)?;
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn test_double_identity() {
+        let k = min_k(&DoubleIdentityCircuit::default());
+        let circuit = DoubleIdentityCircuit {
+            p: Value::known(pallas::Point::random(OsRng).to_affine()),
+        };
+        // y = 0: an error from synthesis, not a panic on inverting 2y
+        assert!(matches!(
+            MockProver::run(k, &circuit, vec![vec![]]),
+            Err(Error::Synthesis)
+        ));
+    }
+
+    #[test]
+    fn test_complete_add_witness_matches_pasta() {
+        let p = pallas::Point::random(OsRng).to_affine();
+        let q = pallas::Point::random(OsRng).to_affine();
+        let identity = pallas::Affine::identity();
+
+        for (a, b) in [
+            (p, q),
+            (p, p),
+            (p, -p),
+            (identity, q),
+            (q, identity),
+            (identity, identity),
+        ] {
+            let w = complete_add_witness(coordinates(a), coordinates(b));
+            assert_eq!((w[0], w[1]), coordinates((a + b).to_affine()));
+        }
+    }
+
+    /// `scalar * P` for a variable `P`.
+    #[derive(Default)]
+    struct MulCircuit {
+        p: Value<pallas::Affine>,
+        scalar: Value<Fp>,
+    }
+
+    impl Circuit<Fp> for MulCircuit {
+        type Config = MyConfig<pallas::Affine>;
+        type FloorPlanner = V1;
+
+        fn without_witnesses(&self) -> Self {
+            Self::default()
+        }
+
+        fn configure(meta: &mut ConstraintSystem<Fp>) -> Self::Config {
+            MyCircuit::<pallas::Affine>::configure(meta)
+        }
+
+        fn synthesize(
+            &self,
+            config: Self::Config,
+            mut layouter: impl Layouter<Fp>,
+        ) -> Result<(), Error> {
+            let chip = EccChip::construct(config.ecc.clone());
+            let p = chip.witness_point(layouter.namespace(|| "P"), self.p)?;
+            let scalar = layouter.assign_region(
+                || "scalar",
+                |mut region| {
+                    region.assign_advice(|| "scalar", config.ecc.advice[0], 0, || self.scalar)
+                },
+            )?;
+
+            let r = chip.mul(layouter.namespace(|| "scalar * P"), &scalar, &p)?;
+            layouter.constrain_instance(r.x.cell(), config.public_key, 0)?;
+            layouter.constrain_instance(r.y.cell(), config.public_key, 1)
+        }
+    }
+
+    #[test]
+    fn test_mul_matches_pasta() {
+        let p = pallas::Point::random(OsRng).to_affine();
+        let k = min_k(&MulCircuit::default());
+
+        for scalar in [Fp::random(OsRng), Fp::zero(), Fp::one(), -Fp::one()] {
+            // below p, and p < q, so this is the same integer as a scalar
+            let as_scalar = pallas::Scalar::from_repr(scalar.to_repr()).unwrap();
+            let circuit = MulCircuit {
+                p: Value::known(p),
+                scalar: Value::known(scalar),
+            };
+            assert_satisfied(
+                k,
+                &circuit,
+                MyCircuit::instance((p * as_scalar).to_affine()),
+            );
+        }
+    }
+
+    #[test]
+    fn test_public_key() {
+        let secret_key = pallas::Scalar::random(OsRng);
+        let public_key = (pallas::Point::generator() * secret_key).to_affine();
+
+        let circuit = MyCircuit::<pallas::Affine> {
+            secret_key: Value::known(secret_key),
+        };
+        let k = min_k(&circuit);
+        assert_satisfied(k, &circuit, MyCircuit::instance(public_key));
+
+        let other = (pallas::Point::generator() * (secret_key + pallas::Scalar::one())).to_affine();
+        let report = verify(k, &circuit, MyCircuit::instance(other)).unwrap_err();
+        assert_eq!(report.groups[0].title, "Permutation");
+    }
+
+    #[test]
+    fn test_public_key_vesta() {
+        // the same chip over the other field of the cycle
+        let secret_key = vesta::Scalar::random(OsRng);
+        let public_key = (vesta::Point::generator() * secret_key).to_affine();
+
+        let circuit = MyCircuit::<vesta::Affine> {
+            secret_key: Value::known(secret_key),
+        };
+        assert_satisfied(min_k(&circuit), &circuit, MyCircuit::instance(public_key));
+    }
+
+    #[cfg(feature = "dev-graph")]
+    #[test]
+    fn print_ecc() {
+        use crate::dev_tools::graph::render_all;
+
+        let circuit = MyCircuit::<pallas::Affine>::default();
+        render_all("ecc", min_k(&circuit), &circuit);
+    }
+}
@@ -0,0 +1,456 @@
+use ff::PrimeFieldBits;
+use halo2_proofs::{
+    arithmetic::{CurveAffine, FieldExt},
+    circuit::{floor_planner::V1, AssignedCell, Layouter, Value},
+    plonk::{
+        Circuit, Column, ConstraintSystem, Constraints, Error, Expression, Instance, Selector,
+    },
+    poly::Rotation,
+};
+
+use super::{coordinates, EcPoint, EccChip, EccConfig};
+
+mod table;
+
+use table::WindowTable;
+
+/// Bits per window.
+pub const WINDOW: usize = 2;
+/// The non-zero multiples `[P, 2P, 3P]` a window selects from.
+const MULTIPLES: usize = (1 << WINDOW) - 1;
+
+/// Variable-base scalar multiplication, two bits at a time.
+///
+/// The scalar is split into 2-bit digits with a running sum, as in
+/// `decompose_range_check`:
+///
+///     | z_i | c_i | q_digit |
+///     ------------------------
+///     | z_0 | c_0 |    1    |     z_i = 4 z_{i+1} + c_i
+///     | ... | ... |   ...   |
+///     |  0  |     |    0    |
+///
+/// and each digit picks its multiple out of `[O, P, 2P, 3P]`. The selection looks up
+/// `(c, [c = 1], [c = 2], [c = 3])` in a [`WindowTable`], which range-checks the digit
+/// and gives one-hot coefficients, so the gate itself is linear in the multiples:
+///
+///     |  c  |  e_1  |  e_2  |  e_3  |  <- lookup, q_window
+///     | x_1 |  x_2  |  x_3  |   x   |     x = e_1 x_1 + e_2 x_2 + e_3 x_3
+///     | y_1 |  y_2  |  y_3  |   y   |     y = e_1 y_1 + e_2 y_2 + e_3 y_3
+///
+/// Then, from the most significant window, `acc = 4 acc + c_i P`: two doublings and an
+/// addition per window instead of a doubling and an addition per bit. The digit and the
+/// three one-hot coefficients fill the four advice columns of the ECC chip, which is why
+/// the window is two bits wide.
+#[derive(Debug, Clone)]
+pub struct WindowedConfig<C: CurveAffine> {
+    ecc: EccConfig<C>,
+    q_digit: Selector,
+    q_window: Selector,
+    table: WindowTable<C::Base>,
+}
+
+#[derive(Debug, Clone)]
+pub struct WindowedMulChip<C: CurveAffine> {
+    config: WindowedConfig<C>,
+    ecc: EccChip<C>,
+}
+
+impl<C: CurveAffine> WindowedMulChip<C> {
+    pub fn construct(config: WindowedConfig<C>) -> Self {
+        let ecc = EccChip::construct(config.ecc.clone());
+        Self { config, ecc }
+    }
+
+    pub fn configure(meta: &mut ConstraintSystem<C::Base>, ecc: EccConfig<C>) -> WindowedConfig<C> {
+        let advice = ecc.advice;
+        let q_digit = meta.selector();
+        let q_window = meta.complex_selector();
+        let table = WindowTable::configure(meta);
+
+        meta.create_gate("window digit", |meta| {
+            let q_digit = meta.query_selector(q_digit);
+            let z = meta.query_advice(advice[0], Rotation::cur());
+            let digit = meta.query_advice(advice[1], Rotation::cur());
+            let z_next = meta.query_advice(advice[0], Rotation::next());
+
+            let base = Expression::Constant(C::Base::from(1u64 << WINDOW));
+            Constraints::with_selector(q_digit, [("running sum", z - (base * z_next + digit))])
+        });
+
+        meta.lookup(|meta| {
+            let q_window = meta.query_selector(q_window);
+            let digit = meta.query_advice(advice[0], Rotation::cur());
+
+            let mut inputs = vec![(q_window.clone() * digit, table.digit)];
+            for (j, column) in table.one_hot.iter().enumerate() {
+                let e = meta.query_advice(advice[j + 1], Rotation::cur());
+                inputs.push((q_window.clone() * e, *column));
+            }
+            inputs
+        });
+
+        meta.create_gate("select window", |meta| {
+            let q_window = meta.query_selector(q_window);
+            let mut x = meta.query_advice(advice[MULTIPLES], Rotation::next());
+            let mut y = meta.query_advice(advice[MULTIPLES], Rotation(2));
+            for j in 0..MULTIPLES {
+                let e = meta.query_advice(advice[j + 1], Rotation::cur());
+                x = x - e.clone() * meta.query_advice(advice[j], Rotation::next());
+                y = y - e * meta.query_advice(advice[j], Rotation(2));
+            }
+
+            Constraints::with_selector(q_window, [("x", x), ("y", y)])
+        });
+
+        WindowedConfig {
+            ecc,
+            q_digit,
+            q_window,
+            table,
+        }
+    }
+
+    pub fn ecc(&self) -> &EccChip<C> {
+        &self.ecc
+    }
+
+    pub fn load_table(&self, layouter: &mut impl Layouter<C::Base>) -> Result<(), Error> {
+        self.config.table.load(layouter)
+    }
+
+    /// `c * P`, picked out of `multiples = [P, 2P, 3P]` by the digit `c`.
+    fn select(
+        &self,
+        mut layouter: impl Layouter<C::Base>,
+        digit: &AssignedCell<C::Base, C::Base>,
+        multiples: &[EcPoint<C>; MULTIPLES],
+    ) -> Result<EcPoint<C>, Error> {
+        let config = &self.config;
+        let advice = config.ecc.advice;
+        let c = digit.value().map(|c| c.get_lower_128() as usize);
+
+        layouter.assign_region(
+            || "select window",
+            |mut region| {
+                config.q_window.enable(&mut region, 0)?;
+                digit.copy_advice(|| "c", &mut region, advice[0], 0)?;
+
+                let mut x = Value::known(C::Base::zero());
+                let mut y = Value::known(C::Base::zero());
+                for (j, multiple) in multiples.iter().enumerate() {
+                    let is_j = c.map(|c| c == j + 1);
+                    region.assign_advice(
+                        || format!("e_{}", j + 1),
+                        advice[j + 1],
+                        0,
+                        || is_j.map(|b| C::Base::from(b as u64)),
+                    )?;
+                    let x_j = multiple.x.copy_advice(
+                        || format!("x_{}", j + 1),
+                        &mut region,
+                        advice[j],
+                        1,
+                    )?;
+                    let y_j = multiple.y.copy_advice(
+                        || format!("y_{}", j + 1),
+                        &mut region,
+                        advice[j],
+                        2,
+                    )?;
+
+                    x = x
+                        .zip(is_j)
+                        .zip(x_j.value())
+                        .map(|((x, b), x_j)| if b { *x_j } else { x });
+                    y = y
+                        .zip(is_j)
+                        .zip(y_j.value())
+                        .map(|((y, b), y_j)| if b { *y_j } else { y });
+                }
+                let x = region.assign_advice(|| "x", advice[MULTIPLES], 1, || x)?;
+                let y = region.assign_advice(|| "y", advice[MULTIPLES], 2, || y)?;
+                Ok(EcPoint { x, y })
+            },
+        )
+    }
+}
+
+impl<C: CurveAffine> WindowedMulChip<C>
+where
+    C::Base: PrimeFieldBits,
+{
+    /// The digits of `scalar`, least significant first.
+    fn digits(
+        &self,
+        mut layouter: impl Layouter<C::Base>,
+        scalar: &AssignedCell<C::Base, C::Base>,
+    ) -> Result<Vec<AssignedCell<C::Base, C::Base>>, Error> {
+        let config = &self.config;
+        let advice = config.ecc.advice;
+        let num_windows = (C::Base::NUM_BITS as usize).div_ceil(WINDOW);
+        let digits = scalar
+            .value()
+            .map(|s| {
+                let bits: Vec<_> = s
+                    .to_le_bits()
+                    .iter()
+                    .by_vals()
+                    .take(num_windows * WINDOW)
+                    .collect();
+                bits.chunks(WINDOW)
+                    .map(|window| window.iter().rev().fold(0u64, |acc, b| 2 * acc + *b as u64))
+                    .collect::<Vec<_>>()
+            })
+            .transpose_vec(num_windows);
+        let inv_base = C::Base::from(1u64 << WINDOW).invert().unwrap();
+
+        layouter.assign_region(
+            || "window digits",
+            |mut region| {
+                let mut z = scalar.copy_advice(|| "z_0", &mut region, advice[0], 0)?;
+                let mut assigned = vec![];
+                for (i, digit) in digits.iter().enumerate() {
+                    config.q_digit.enable(&mut region, i)?;
+                    let c = region.assign_advice(
+                        || format!("c_{}", i),
+                        advice[1],
+                        i,
+                        || digit.map(C::Base::from),
+                    )?;
+                    let z_next = z.value().zip(c.value()).map(|(z, c)| (*z - c) * inv_base);
+                    z = region.assign_advice(
+                        || format!("z_{}", i + 1),
+                        advice[0],
+                        i + 1,
+                        || z_next,
+                    )?;
+                    assigned.push(c);
+                }
+                region.constrain_constant(z.cell(), C::Base::zero())?;
+                Ok(assigned)
+            },
+        )
+    }
+
+    /// `scalar * P`, reading `scalar` as an integer like [`EccChip::mul`] does (with the
+    /// same caveat about canonicity).
+    pub fn mul(
+        &self,
+        mut layouter: impl Layouter<C::Base>,
+        scalar: &AssignedCell<C::Base, C::Base>,
+        p: &EcPoint<C>,
+    ) -> Result<EcPoint<C>, Error> {
+        let ecc = &self.ecc;
+        let digits = self.digits(layouter.namespace(|| "digits"), scalar)?;
+
+        // 2P != ±P on a curve of prime order, so the incomplete addition is fine
+        let p2 = ecc.double(layouter.namespace(|| "2P"), p)?;
+        let p3 = ecc.add_incomplete(layouter.namespace(|| "3P"), &p2, p)?;
+        let multiples = [p.clone(), p2, p3];
+
+        let mut acc: Option<EcPoint<C>> = None;
+        for (i, digit) in digits.iter().enumerate().rev() {
+            let term = self.select(
+                layouter.namespace(|| format!("select {}", i)),
+                digit,
+                &multiples,
+            )?;
+            acc = Some(match acc {
+                None => term,
+                Some(mut acc) => {
+                    // the accumulator can still be the identity, so no incomplete doubling
+                    for j in 0..WINDOW {
+                        acc = ecc.add(
+                            layouter.namespace(|| format!("double {}.{}", i, j)),
+                            &acc,
+                            &acc,
+                        )?;
+                    }
+                    ecc.add(layouter.namespace(|| format!("add {}", i)), &acc, &term)?
+                }
+            });
+        }
+        Ok(acc.unwrap())
+    }
+}
+
+/// How [`MyCircuit`] multiplies, so the two methods can be compared.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Method {
+    /// [`EccChip::mul`], a bit at a time.
+    DoubleAndAdd,
+    /// [`WindowedMulChip::mul`], a window at a time.
+    Windowed,
+}
+
+/// Proves `R = s * P` for public `P` and `R` and a private scalar `s`.
+pub struct MyCircuit<C: CurveAffine> {
+    pub point: Value<C>,
+    pub scalar: Value<C::Base>,
+    pub method: Method,
+}
+
+impl<C: CurveAffine> MyCircuit<C> {
+    pub fn new(method: Method, point: C, scalar: C::Base) -> Self {
+        Self {
+            point: Value::known(point),
+            scalar: Value::known(scalar),
+            method,
+        }
+    }
+
+    /// The public input: `(P.x, P.y, R.x, R.y)`.
+    pub fn instance(point: C, result: C) -> Vec<Vec<C::Base>> {
+        let (p_x, p_y) = coordinates(point);
+        let (r_x, r_y) = coordinates(result);
+        vec![vec![p_x, p_y, r_x, r_y]]
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct MyConfig<C: CurveAffine> {
+    windowed: WindowedConfig<C>,
+    instance: Column<Instance>,
+}
+
+impl<C: CurveAffine> Circuit<C::Base> for MyCircuit<C>
+where
+    C::Base: PrimeFieldBits,
+{
+    type Config = MyConfig<C>;
+    type FloorPlanner = V1;
+
+    fn without_witnesses(&self) -> Self {
+        Self {
+            point: Value::unknown(),
+            scalar: Value::unknown(),
+            method: self.method,
+        }
+    }
+
+    fn configure(meta: &mut ConstraintSystem<C::Base>) -> Self::Config {
+        let advice = [(); 4].map(|_| meta.advice_column());
+        let instance = meta.instance_column();
+        meta.enable_equality(instance);
+
+        let ecc = EccChip::configure(meta, advice);
+        MyConfig {
+            windowed: WindowedMulChip::configure(meta, ecc),
+            instance,
+        }
+    }
+
+    fn synthesize(
+        &self,
+        config: Self::Config,
+        mut layouter: impl Layouter<C::Base>,
+    ) -> Result<(), Error> {
+        let chip = WindowedMulChip::construct(config.windowed.clone());
+        chip.load_table(&mut layouter)?;
+
+        let p = chip
+            .ecc()
+            .witness_point(layouter.namespace(|| "P"), self.point)?;
+        let scalar = layouter.assign_region(
+            || "scalar",
+            |mut region| {
+                region.assign_advice(|| "s", config.windowed.ecc.advice[0], 0, || self.scalar)
+            },
+        )?;
+
+        let r = match self.method {
+            Method::DoubleAndAdd => chip
+                .ecc()
+                .mul(layouter.namespace(|| "s * P"), &scalar, &p)?,
+            Method::Windowed => chip.mul(layouter.namespace(|| "s * P"), &scalar, &p)?,
+        };
+
+        for (i, cell) in [&p.x, &p.y, &r.x, &r.y].into_iter().enumerate() {
+            layouter.constrain_instance(cell.cell(), config.instance, i)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use ff::{Field, PrimeField};
+    use group::{Curve, Group};
+    use halo2_proofs::pasta::{pallas, Fp};
+    use rand::rngs::OsRng;
+
+    use super::*;
+    use crate::dev_tools::{
+        failure::{assert_satisfied, verify},
+        min_k::min_k,
+        stats::CircuitStats,
+    };
+
+    fn mul(point: pallas::Affine, scalar: Fp) -> pallas::Affine {
+        // below p, and p < q, so this is the same integer as a scalar
+        (point * pallas::Scalar::from_repr(scalar.to_repr()).unwrap()).to_affine()
+    }
+
+    #[test]
+    fn test_windowed_matches_pasta() {
+        let point = pallas::Point::random(OsRng).to_affine();
+        let k = min_k(&MyCircuit::new(Method::Windowed, point, Fp::zero()));
+
+        for scalar in [
+            Fp::random(OsRng),
+            Fp::zero(),
+            Fp::one(),
+            Fp::from(2),
+            Fp::from(3),
+            -Fp::one(),
+        ] {
+            let circuit = MyCircuit::new(Method::Windowed, point, scalar);
+            assert_satisfied(k, &circuit, MyCircuit::instance(point, mul(point, scalar)));
+        }
+    }
+
+    #[test]
+    fn test_wrong_result() {
+        let point = pallas::Point::random(OsRng).to_affine();
+        let scalar = Fp::random(OsRng);
+        let circuit = MyCircuit::new(Method::Windowed, point, scalar);
+        let k = min_k(&circuit);
+
+        let wrong = mul(point, scalar + Fp::one());
+        let report = verify(k, &circuit, MyCircuit::instance(point, wrong)).unwrap_err();
+        assert_eq!(report.groups[0].title, "Permutation");
+    }
+
+    #[test]
+    fn test_row_count_against_double_and_add() {
+        let point = pallas::Point::random(OsRng).to_affine();
+        let scalar = Fp::random(OsRng);
+        let instance = MyCircuit::instance(point, mul(point, scalar));
+
+        let double_and_add = MyCircuit::new(Method::DoubleAndAdd, point, scalar);
+        let windowed = MyCircuit::new(Method::Windowed, point, scalar);
+        assert_satisfied(min_k(&double_and_add), &double_and_add, instance.clone());
+        assert_satisfied(min_k(&windowed), &windowed, instance);
+
+        let double_and_add = CircuitStats::measure(&double_and_add).unwrap();
+        let windowed = CircuitStats::measure(&windowed).unwrap();
+
+        // 9 rows per bit against 13 per two bits, plus the digits
+        // (`cargo run --bin circuit_stats -- ecc::windowed` prints both reports)
+        assert!(5 * windowed.size.rows < 4 * double_and_add.size.rows);
+    }
+
+    #[cfg(feature = "dev-graph")]
+    #[test]
+    fn print_windowed_mul() {
+        use crate::dev_tools::graph::render_all;
+
+        let circuit = MyCircuit::<pallas::Affine> {
+            point: Value::unknown(),
+            scalar: Value::unknown(),
+            method: Method::Windowed,
+        };
+        render_all("windowed-mul", min_k(&circuit), &circuit);
+    }
+}
@@ -0,0 +1,63 @@
+use std::marker::PhantomData;
+
+use halo2_proofs::{
+    arithmetic::FieldExt,
+    circuit::{Layouter, Value},
+    plonk::{ConstraintSystem, Error, TableColumn},
+};
+
+use super::{MULTIPLES, WINDOW};
+
+/// Every window digit `c` with its one-hot encoding `([c = 1], [c = 2], [c = 3])`.
+///
+///     | digit | one_hot_1 | one_hot_2 | one_hot_3 |
+///     ---------------------------------------------
+///     |   0   |     0     |     0     |     0     |  <- also looked up when the selector is off
+///     |   1   |     1     |     0     |     0     |
+///     |   2   |     0     |     1     |     0     |
+///     |   3   |     0     |     0     |     1     |
+///
+/// Looking up a row both range-checks the digit to `WINDOW` bits and hands the gate the
+/// coefficients that pick `c * P` out of `[P, 2P, 3P]`.
+#[derive(Debug, Clone)]
+pub(super) struct WindowTable<F: FieldExt> {
+    pub(super) digit: TableColumn,
+    pub(super) one_hot: [TableColumn; MULTIPLES],
+    _marker: PhantomData<F>,
+}
+
+impl<F: FieldExt> WindowTable<F> {
+    pub(super) fn configure(meta: &mut ConstraintSystem<F>) -> Self {
+        Self {
+            digit: meta.lookup_table_column(),
+            one_hot: [(); MULTIPLES].map(|_| meta.lookup_table_column()),
+            _marker: PhantomData,
+        }
+    }
+
+    pub(super) fn load(&self, layouter: &mut impl Layouter<F>) -> Result<(), Error> {
+        layouter.assign_table(
+            || "load window table",
+            |mut table| {
+                for digit in 0..1 << WINDOW {
+                    table.assign_cell(
+                        || "digit",
+                        self.digit,
+                        digit,
+                        || Value::known(F::from(digit as u64)),
+                    )?;
+                    for (j, column) in self.one_hot.iter().enumerate() {
+                        let bit = digit == j + 1;
+                        table.assign_cell(
+                            || "one hot",
+                            *column,
+                            digit,
+                            || Value::known(F::from(bit as u64)),
+                        )?;
+                    }
+                }
+                Ok(())
+            },
+        )
+    }
+}
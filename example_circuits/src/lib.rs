@@ -1,2 +1,17 @@
-mod range_check;
-mod decompose_range_check;
\ No newline at end of file
+pub mod range_check;
+pub mod accumulation;
+pub mod batch;
+pub mod bitwise;
+pub mod bn254;
+pub mod decompose_range_check;
+pub mod dev_tools;
+pub mod ecc;
+pub mod merkle;
+pub mod mimc;
+pub mod plonk;
+pub mod plookup;
+pub mod poly;
+pub mod pool;
+pub mod poseidon;
+pub mod schnorr;
+pub mod sha256;
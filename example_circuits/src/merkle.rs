@@ -0,0 +1,301 @@
+use halo2_proofs::{
+    arithmetic::FieldExt,
+    circuit::{floor_planner::V1, AssignedCell, Layouter, Value},
+    plonk::{
+        Advice, Circuit, Column, ConstraintSystem, Constraints, Error, Expression, Instance,
+        Selector,
+    },
+    poly::Rotation,
+};
+
+use crate::poseidon::{PoseidonChip, PoseidonConfig};
+
+pub mod native;
+
+/// Proves that a leaf hashes up to a root along a Merkle path.
+///
+/// Every level puts the current node and its sibling in order before hashing them.
+/// The path bit says whether the node is the right child:
+///
+///     |  node  |  sibling  |  bit  | q_swap |
+///     ---------------------------------------
+///     |   n    |     s     |   b   |   1    |
+///     |  left  |   right   |       |   0    |
+///
+///     b * (1 - b) = 0
+///     left  = n + b * (s - n)
+///     right = s + b * (n - s)
+///
+/// and the parent is `Poseidon(left, right)`, computed with the Poseidon chip on the same
+/// three advice columns.
+#[derive(Debug, Clone)]
+pub struct MerkleConfig<F: FieldExt> {
+    advice: [Column<Advice>; 3],
+    q_swap: Selector,
+    poseidon: PoseidonConfig<F>,
+}
+
+#[derive(Debug, Clone)]
+pub struct MerkleChip<F: FieldExt> {
+    config: MerkleConfig<F>,
+    poseidon: PoseidonChip<F>,
+}
+
+impl<F: FieldExt> MerkleChip<F> {
+    pub fn construct(config: MerkleConfig<F>) -> Self {
+        let poseidon = PoseidonChip::construct(config.poseidon.clone());
+        Self { config, poseidon }
+    }
+
+    pub fn configure(
+        meta: &mut ConstraintSystem<F>,
+        advice: [Column<Advice>; 3],
+    ) -> MerkleConfig<F> {
+        // also enables equality on the advice columns
+        let poseidon = PoseidonChip::configure(meta, advice);
+        let q_swap = meta.selector();
+
+        meta.create_gate("swap", |meta| {
+            let q_swap = meta.query_selector(q_swap);
+            let node = meta.query_advice(advice[0], Rotation::cur());
+            let sibling = meta.query_advice(advice[1], Rotation::cur());
+            let bit = meta.query_advice(advice[2], Rotation::cur());
+            let left = meta.query_advice(advice[0], Rotation::next());
+            let right = meta.query_advice(advice[1], Rotation::next());
+
+            let one = Expression::Constant(F::one());
+            Constraints::with_selector(
+                q_swap,
+                [
+                    ("bit is boolean", bit.clone() * (one - bit.clone())),
+                    (
+                        "left",
+                        left - (node.clone() + bit.clone() * (sibling.clone() - node.clone())),
+                    ),
+                    ("right", right - (sibling.clone() + bit * (node - sibling))),
+                ],
+            )
+        });
+
+        MerkleConfig {
+            advice,
+            q_swap,
+            poseidon,
+        }
+    }
+
+    /// Orders `node` and `sibling` according to `bit` and hashes them into the parent.
+    pub fn hash_level(
+        &self,
+        mut layouter: impl Layouter<F>,
+        node: &AssignedCell<F, F>,
+        sibling: Value<F>,
+        bit: Value<bool>,
+    ) -> Result<AssignedCell<F, F>, Error> {
+        let config = &self.config;
+
+        let (left, right) = layouter.assign_region(
+            || "swap",
+            |mut region| {
+                config.q_swap.enable(&mut region, 0)?;
+
+                let node = node.copy_advice(|| "node", &mut region, config.advice[0], 0)?;
+                region.assign_advice(|| "sibling", config.advice[1], 0, || sibling)?;
+                region.assign_advice(
+                    || "bit",
+                    config.advice[2],
+                    0,
+                    || bit.map(|bit| F::from(bit as u64)),
+                )?;
+
+                let node = node.value().copied();
+                let (left, right) = node
+                    .zip(sibling)
+                    .zip(bit)
+                    .map(|((node, sibling), bit)| {
+                        if bit {
+                            (sibling, node)
+                        } else {
+                            (node, sibling)
+                        }
+                    })
+                    .unzip();
+                let left = region.assign_advice(|| "left", config.advice[0], 1, || left)?;
+                let right = region.assign_advice(|| "right", config.advice[1], 1, || right)?;
+                Ok((left, right))
+            },
+        )?;
+
+        self.poseidon
+            .hash(layouter.namespace(|| "hash"), &[left, right])
+    }
+
+    /// Hashes `leaf` up the path and returns the root.
+    pub fn root(
+        &self,
+        mut layouter: impl Layouter<F>,
+        leaf: AssignedCell<F, F>,
+        siblings: &[Value<F>],
+        bits: &[Value<bool>],
+    ) -> Result<AssignedCell<F, F>, Error> {
+        assert_eq!(siblings.len(), bits.len());
+
+        let mut node = leaf;
+        for (level, (sibling, bit)) in siblings.iter().zip(bits.iter()).enumerate() {
+            node = self.hash_level(
+                layouter.namespace(|| format!("level {}", level)),
+                &node,
+                *sibling,
+                *bit,
+            )?;
+        }
+        Ok(node)
+    }
+}
+
+/// Proves that a private leaf is in the tree whose root is the public input, without
+/// revealing the leaf or its position.
+pub struct MyCircuit<F: FieldExt, const DEPTH: usize> {
+    pub leaf: Value<F>,
+    pub siblings: [Value<F>; DEPTH],
+    pub bits: [Value<bool>; DEPTH],
+}
+
+impl<F: FieldExt, const DEPTH: usize> Default for MyCircuit<F, DEPTH> {
+    fn default() -> Self {
+        Self {
+            leaf: Value::unknown(),
+            siblings: [Value::unknown(); DEPTH],
+            bits: [Value::unknown(); DEPTH],
+        }
+    }
+}
+
+impl<F: FieldExt, const DEPTH: usize> MyCircuit<F, DEPTH> {
+    pub fn new(leaf: F, path: &native::MerklePath<F>) -> Self {
+        assert_eq!(path.siblings.len(), DEPTH);
+        assert_eq!(path.bits.len(), DEPTH);
+        let siblings: Vec<_> = path.siblings.iter().map(|s| Value::known(*s)).collect();
+        let bits: Vec<_> = path.bits.iter().map(|b| Value::known(*b)).collect();
+        Self {
+            leaf: Value::known(leaf),
+            siblings: siblings.try_into().unwrap(),
+            bits: bits.try_into().unwrap(),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct MyConfig<F: FieldExt> {
+    merkle: MerkleConfig<F>,
+    root: Column<Instance>,
+}
+
+impl<F: FieldExt, const DEPTH: usize> Circuit<F> for MyCircuit<F, DEPTH> {
+    type Config = MyConfig<F>;
+    type FloorPlanner = V1;
+
+    fn without_witnesses(&self) -> Self {
+        Self::default()
+    }
+
+    fn configure(meta: &mut ConstraintSystem<F>) -> Self::Config {
+        let advice = [(); 3].map(|_| meta.advice_column());
+        let root = meta.instance_column();
+        meta.enable_equality(root);
+
+        MyConfig {
+            merkle: MerkleChip::configure(meta, advice),
+            root,
+        }
+    }
+
+    fn synthesize(
+        &self,
+        config: Self::Config,
+        mut layouter: impl Layouter<F>,
+    ) -> Result<(), Error> {
+        let chip = MerkleChip::construct(config.merkle.clone());
+
+        let leaf = layouter.assign_region(
+            || "leaf",
+            |mut region| region.assign_advice(|| "leaf", config.merkle.advice[0], 0, || self.leaf),
+        )?;
+
+        let root = chip.root(
+            layouter.namespace(|| "merkle path"),
+            leaf,
+            &self.siblings,
+            &self.bits,
+        )?;
+        layouter.constrain_instance(root.cell(), config.root, 0)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use halo2_proofs::pasta::Fp;
+
+    use super::*;
+    use crate::{
+        dev_tools::{
+            failure::{assert_satisfied, verify},
+            min_k::min_k,
+        },
+        poseidon::native::Spec,
+    };
+    use native::MerkleTree;
+
+    const DEPTH: usize = 3;
+
+    fn tree() -> MerkleTree<Fp> {
+        let leaves: Vec<_> = (0..8).map(|i| Fp::from(1000 + i)).collect();
+        MerkleTree::new(&Spec::default(), DEPTH, &leaves)
+    }
+
+    #[test]
+    fn test_membership() {
+        let tree = tree();
+        let k = min_k(&MyCircuit::<Fp, DEPTH>::default());
+
+        for index in [0, 3, 6, 7] {
+            let circuit = MyCircuit::<Fp, DEPTH>::new(tree.leaf(index), &tree.path(index));
+            assert_satisfied(k, &circuit, vec![vec![tree.root()]]);
+        }
+    }
+
+    #[test]
+    fn test_leaf_not_in_tree() {
+        let tree = tree();
+        let k = min_k(&MyCircuit::<Fp, DEPTH>::default());
+
+        // a leaf with someone else's path
+        let circuit = MyCircuit::<Fp, DEPTH>::new(tree.leaf(2), &tree.path(5));
+        assert!(verify(k, &circuit, vec![vec![tree.root()]]).is_err());
+
+        // the right leaf, with one path bit flipped
+        let mut path = tree.path(5);
+        path.bits[1] = !path.bits[1];
+        let circuit = MyCircuit::<Fp, DEPTH>::new(tree.leaf(5), &path);
+        assert!(verify(k, &circuit, vec![vec![tree.root()]]).is_err());
+    }
+
+    #[test]
+    fn test_wrong_root() {
+        let tree = tree();
+        let k = min_k(&MyCircuit::<Fp, DEPTH>::default());
+
+        let circuit = MyCircuit::<Fp, DEPTH>::new(tree.leaf(4), &tree.path(4));
+        let report = verify(k, &circuit, vec![vec![tree.root() + Fp::one()]]).unwrap_err();
+        assert_eq!(report.groups[0].title, "Permutation");
+    }
+
+    #[cfg(feature = "dev-graph")]
+    #[test]
+    fn print_merkle() {
+        use crate::dev_tools::graph::render_all;
+
+        let circuit = MyCircuit::<Fp, DEPTH>::default();
+        render_all("merkle", min_k(&circuit), &circuit);
+    }
+}
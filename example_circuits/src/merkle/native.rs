@@ -0,0 +1,128 @@
+use halo2_proofs::arithmetic::FieldExt;
+
+use crate::poseidon::native::{hash, Spec};
+
+/// Hash of an inner node: Poseidon over `[left, right]`.
+pub fn hash_nodes<F: FieldExt>(spec: &Spec<F>, left: F, right: F) -> F {
+    hash(spec, &[left, right])
+}
+
+/// A full binary Merkle tree of the given depth, built bottom-up with Poseidon.
+///
+/// `levels[0]` are the leaves (padded with zeroes to `2^depth`) and the last level
+/// holds only the root.
+#[derive(Debug, Clone)]
+pub struct MerkleTree<F: FieldExt> {
+    pub levels: Vec<Vec<F>>,
+}
+
+/// Everything a prover needs to open one leaf.
+#[derive(Debug, Clone)]
+pub struct MerklePath<F: FieldExt> {
+    /// Siblings from the leaf level up to just below the root.
+    pub siblings: Vec<F>,
+    /// `bits[i]` is set when the node at level `i` is a right child, i.e. the
+    /// sibling goes on the left. These are the bits of the leaf index, LSB first.
+    pub bits: Vec<bool>,
+}
+
+impl<F: FieldExt> MerkleTree<F> {
+    pub fn new(spec: &Spec<F>, depth: usize, leaves: &[F]) -> Self {
+        assert!(
+            leaves.len() <= 1 << depth,
+            "too many leaves for depth {}",
+            depth
+        );
+
+        let mut level = leaves.to_vec();
+        level.resize(1 << depth, F::zero());
+
+        let mut levels = vec![level];
+        for _ in 0..depth {
+            let next = levels
+                .last()
+                .unwrap()
+                .chunks(2)
+                .map(|pair| hash_nodes(spec, pair[0], pair[1]))
+                .collect();
+            levels.push(next);
+        }
+        Self { levels }
+    }
+
+    pub fn depth(&self) -> usize {
+        self.levels.len() - 1
+    }
+
+    pub fn root(&self) -> F {
+        self.levels[self.depth()][0]
+    }
+
+    pub fn leaf(&self, index: usize) -> F {
+        self.levels[0][index]
+    }
+
+    pub fn path(&self, index: usize) -> MerklePath<F> {
+        assert!(index < 1 << self.depth());
+
+        let (siblings, bits) = (0..self.depth())
+            .map(|level| {
+                let node = index >> level;
+                (self.levels[level][node ^ 1], node & 1 == 1)
+            })
+            .unzip();
+        MerklePath { siblings, bits }
+    }
+}
+
+impl<F: FieldExt> MerklePath<F> {
+    /// Hashes `leaf` up the path.
+    pub fn root(&self, spec: &Spec<F>, leaf: F) -> F {
+        self.siblings
+            .iter()
+            .zip(self.bits.iter())
+            .fold(leaf, |node, (sibling, is_right)| {
+                if *is_right {
+                    hash_nodes(spec, *sibling, node)
+                } else {
+                    hash_nodes(spec, node, *sibling)
+                }
+            })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use halo2_proofs::pasta::Fp;
+
+    use super::*;
+
+    #[test]
+    fn test_every_path_leads_to_the_root() {
+        let spec = Spec::<Fp>::default();
+        let leaves: Vec<_> = (0..6).map(|i| Fp::from(i * i + 1)).collect();
+        let tree = MerkleTree::new(&spec, 3, &leaves);
+
+        assert_eq!(tree.levels[0].len(), 8);
+        assert_eq!(tree.leaf(7), Fp::zero());
+        for index in 0..8 {
+            let path = tree.path(index);
+            assert_eq!(path.siblings.len(), 3);
+            assert_eq!(path.root(&spec, tree.leaf(index)), tree.root());
+        }
+    }
+
+    #[test]
+    fn test_wrong_leaf_or_path() {
+        let spec = Spec::<Fp>::default();
+        let leaves: Vec<_> = (0..4).map(|i| Fp::from(i + 10)).collect();
+        let tree = MerkleTree::new(&spec, 2, &leaves);
+
+        let path = tree.path(1);
+        assert_ne!(path.root(&spec, tree.leaf(2)), tree.root());
+
+        let mut flipped = path.clone();
+        flipped.bits[0] = !flipped.bits[0];
+        assert_ne!(flipped.root(&spec, tree.leaf(1)), tree.root());
+    }
+}
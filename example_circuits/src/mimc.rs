@@ -0,0 +1,309 @@
+use halo2_proofs::{
+    arithmetic::FieldExt,
+    circuit::{floor_planner::V1, AssignedCell, Layouter, Value},
+    plonk::{
+        Advice, Circuit, Column, ConstraintSystem, Constraints, Error, Fixed, Instance, Selector,
+    },
+    poly::Rotation,
+};
+
+pub mod native;
+
+use native::round_constants;
+
+/// MiMC-Feistel on a single advice column.
+///
+/// Writing the Feistel state as a sequence `z` with `(L_i, R_i) = (z_{i+1}, z_i)`, a round
+/// `L_{i+1} = R_i + (L_i + c_i)^5, R_{i+1} = L_i` becomes
+///
+///     z_{i+2} = z_i + (z_{i+1} + c_i)^5
+///
+/// which is the Fibonacci recurrence of `example1.rs` with an S-box in the middle, and
+/// needs only one column queried at three rotations:
+///
+///     |  z  |  c  | q_round | q_add |
+///     -------------------------------
+///     | R_0 | c_0 |    1    |   0   |
+///     | L_0 | c_1 |    1    |   0   |
+///     | ... | ... |   ...   |  ...  |
+///     | R_r |     |    0    |   0   |
+///     | L_r |     |    0    |   0   |
+///
+/// Absorbing a message element reuses the plain Fibonacci gate `z_{i+2} = z_i + z_{i+1}`:
+///
+///     |  L  |  0  |    0    |   1   |
+///     |  m  |     |    0    |   0   |
+///     | L+m |     |    0    |   0   |
+///
+#[derive(Debug, Clone)]
+pub struct MimcConfig<F: FieldExt> {
+    z: Column<Advice>,
+    round_constant: Column<Fixed>,
+    q_round: Selector,
+    q_add: Selector,
+    constants: Vec<F>,
+}
+
+#[derive(Debug, Clone)]
+pub struct MimcChip<F: FieldExt> {
+    config: MimcConfig<F>,
+}
+
+impl<F: FieldExt> MimcChip<F> {
+    pub fn construct(config: MimcConfig<F>) -> Self {
+        Self { config }
+    }
+
+    pub fn configure(meta: &mut ConstraintSystem<F>, z: Column<Advice>) -> MimcConfig<F> {
+        let round_constant = meta.fixed_column();
+        meta.enable_equality(z);
+        // the zero state the sponge starts from
+        meta.enable_constant(round_constant);
+
+        let q_round = meta.selector();
+        let q_add = meta.selector();
+
+        meta.create_gate("round", |meta| {
+            let q_round = meta.query_selector(q_round);
+            let z_0 = meta.query_advice(z, Rotation::cur());
+            let z_1 = meta.query_advice(z, Rotation::next());
+            let z_2 = meta.query_advice(z, Rotation(2));
+            let c = meta.query_fixed(round_constant, Rotation::cur());
+
+            let t = z_1 + c;
+            let t2 = t.clone() * t.clone();
+            Constraints::with_selector(q_round, [("round", z_2 - (z_0 + t2.clone() * t2 * t))])
+        });
+
+        meta.create_gate("add", |meta| {
+            let q_add = meta.query_selector(q_add);
+            let a = meta.query_advice(z, Rotation::cur());
+            let b = meta.query_advice(z, Rotation::next());
+            let c = meta.query_advice(z, Rotation(2));
+
+            Constraints::with_selector(q_add, [("add", c - (a + b))])
+        });
+
+        MimcConfig {
+            z,
+            round_constant,
+            q_round,
+            q_add,
+            constants: round_constants(),
+        }
+    }
+
+    /// Applies the permutation to `(left, right)`.
+    pub fn permute(
+        &self,
+        mut layouter: impl Layouter<F>,
+        left: &AssignedCell<F, F>,
+        right: &AssignedCell<F, F>,
+    ) -> Result<(AssignedCell<F, F>, AssignedCell<F, F>), Error> {
+        let config = &self.config;
+
+        layouter.assign_region(
+            || "permute",
+            |mut region| {
+                let mut z_0 = right.copy_advice(|| "R_0", &mut region, config.z, 0)?;
+                let mut z_1 = left.copy_advice(|| "L_0", &mut region, config.z, 1)?;
+
+                for (row, c) in config.constants.iter().enumerate() {
+                    config.q_round.enable(&mut region, row)?;
+                    region.assign_fixed(
+                        || format!("c_{}", row),
+                        config.round_constant,
+                        row,
+                        || Value::known(*c),
+                    )?;
+
+                    let z_2 = z_0.value().zip(z_1.value()).map(|(z_0, z_1)| {
+                        let t = *z_1 + c;
+                        *z_0 + t.square().square() * t
+                    });
+                    let z_2 = region.assign_advice(
+                        || format!("z_{}", row + 2),
+                        config.z,
+                        row + 2,
+                        || z_2,
+                    )?;
+                    z_0 = z_1;
+                    z_1 = z_2;
+                }
+
+                Ok((z_1, z_0))
+            },
+        )
+    }
+
+    /// `a + b`, with the Fibonacci gate.
+    pub fn add(
+        &self,
+        mut layouter: impl Layouter<F>,
+        a: &AssignedCell<F, F>,
+        b: &AssignedCell<F, F>,
+    ) -> Result<AssignedCell<F, F>, Error> {
+        let config = &self.config;
+
+        layouter.assign_region(
+            || "add",
+            |mut region| {
+                config.q_add.enable(&mut region, 0)?;
+                let a = a.copy_advice(|| "a", &mut region, config.z, 0)?;
+                let b = b.copy_advice(|| "b", &mut region, config.z, 1)?;
+                region.assign_advice(
+                    || "a + b",
+                    config.z,
+                    2,
+                    || a.value().copied() + b.value().copied(),
+                )
+            },
+        )
+    }
+
+    /// Hashes `message` like [`native::hash`]: one permutation per element.
+    pub fn hash(
+        &self,
+        mut layouter: impl Layouter<F>,
+        message: &[AssignedCell<F, F>],
+    ) -> Result<AssignedCell<F, F>, Error> {
+        let (mut left, mut right) = layouter.assign_region(
+            || "initial state",
+            |mut region| {
+                let left =
+                    region.assign_advice_from_constant(|| "L", self.config.z, 0, F::zero())?;
+                let right =
+                    region.assign_advice_from_constant(|| "R", self.config.z, 1, F::zero())?;
+                Ok((left, right))
+            },
+        )?;
+
+        for (i, m) in message.iter().enumerate() {
+            let absorbed = self.add(layouter.namespace(|| format!("absorb {}", i)), &left, m)?;
+            (left, right) = self.permute(
+                layouter.namespace(|| format!("permute {}", i)),
+                &absorbed,
+                &right,
+            )?;
+        }
+        Ok(left)
+    }
+}
+
+/// Proves knowledge of a message of `L` elements that hashes to the public digest, like
+/// `poseidon::MyCircuit`, so the two hashes can be compared.
+pub struct MyCircuit<F: FieldExt, const L: usize> {
+    pub message: Vec<Value<F>>,
+}
+
+impl<F: FieldExt, const L: usize> Default for MyCircuit<F, L> {
+    fn default() -> Self {
+        Self {
+            message: vec![Value::unknown(); L],
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct MyConfig<F: FieldExt> {
+    mimc: MimcConfig<F>,
+    digest: Column<Instance>,
+}
+
+impl<F: FieldExt, const L: usize> Circuit<F> for MyCircuit<F, L> {
+    type Config = MyConfig<F>;
+    type FloorPlanner = V1;
+
+    fn without_witnesses(&self) -> Self {
+        Self::default()
+    }
+
+    fn configure(meta: &mut ConstraintSystem<F>) -> Self::Config {
+        let z = meta.advice_column();
+        let digest = meta.instance_column();
+        meta.enable_equality(digest);
+
+        MyConfig {
+            mimc: MimcChip::configure(meta, z),
+            digest,
+        }
+    }
+
+    fn synthesize(
+        &self,
+        config: Self::Config,
+        mut layouter: impl Layouter<F>,
+    ) -> Result<(), Error> {
+        let chip = MimcChip::construct(config.mimc.clone());
+
+        let message = layouter.assign_region(
+            || "message",
+            |mut region| {
+                self.message
+                    .iter()
+                    .enumerate()
+                    .map(|(i, m)| {
+                        region.assign_advice(|| format!("m_{}", i), config.mimc.z, i, || *m)
+                    })
+                    .collect::<Result<Vec<_>, _>>()
+            },
+        )?;
+
+        let digest = chip.hash(layouter.namespace(|| "hash"), &message)?;
+        layouter.constrain_instance(digest.cell(), config.digest, 0)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use halo2_proofs::pasta::Fp;
+
+    use super::*;
+    use crate::{
+        dev_tools::{
+            failure::{assert_satisfied, verify},
+            min_k::min_k,
+            stats::CircuitStats,
+        },
+        poseidon,
+    };
+
+    #[test]
+    fn test_hash_matches_native() {
+        let message = [Fp::one(), Fp::from(2)];
+        let digest = native::hash(&message, &round_constants());
+
+        let circuit = MyCircuit::<Fp, 2> {
+            message: message.iter().map(|m| Value::known(*m)).collect(),
+        };
+        let k = min_k(&circuit);
+        assert_satisfied(k, &circuit, vec![vec![digest]]);
+
+        let report = verify(k, &circuit, vec![vec![digest + Fp::one()]]).unwrap_err();
+        assert_eq!(report.groups[0].title, "Permutation");
+    }
+
+    #[test]
+    fn test_row_cost_against_poseidon() {
+        let mimc = CircuitStats::measure(&MyCircuit::<Fp, 2>::default()).unwrap();
+        let poseidon = CircuitStats::measure(&poseidon::MyCircuit::<Fp, 2>::default()).unwrap();
+
+        // one advice column against three, but about six times the rows for a
+        // two-element message: MiMC permutes once per element, with 220 rounds
+        // (`cargo run --bin circuit_stats -- 2-element` prints both reports)
+        assert_eq!(mimc.advice_columns, 1);
+        assert_eq!(poseidon.advice_columns, 3);
+        assert!(mimc.size.rows > 2 * native::ROUNDS);
+        assert!(mimc.size.rows > 5 * poseidon.size.rows);
+        assert_eq!(mimc.max_degree, 6);
+    }
+
+    #[cfg(feature = "dev-graph")]
+    #[test]
+    fn print_mimc() {
+        use crate::dev_tools::graph::render_all;
+
+        let circuit = MyCircuit::<Fp, 2>::default();
+        render_all("mimc", min_k(&circuit), &circuit);
+    }
+}
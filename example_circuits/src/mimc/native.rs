@@ -0,0 +1,98 @@
+use blake2b_simd::Params;
+use halo2_proofs::arithmetic::FieldExt;
+
+/// Rounds of the Feistel network: `2 * ceil(log_5(p))` for a 255-bit field.
+pub const ROUNDS: usize = 220;
+
+/// Round constants of MiMC-Feistel, hashed from their index so nobody picked them.
+///
+/// `c_i = BLAKE2b-512("MiMC-Feistel-x^5", i) mod p`, with `c_0 = 0` as in the MiMC paper.
+pub fn round_constants<F: FieldExt>() -> Vec<F> {
+    (0..ROUNDS)
+        .map(|i| {
+            if i == 0 {
+                return F::zero();
+            }
+            let hash = Params::new()
+                .hash_length(64)
+                .personal(b"MiMC-Feistel-x^5")
+                .hash(&(i as u64).to_le_bytes());
+            F::from_bytes_wide(hash.as_array())
+        })
+        .collect()
+}
+
+/// The MiMC-Feistel permutation with the `x^5` round function and a zero key:
+///
+///     L_{i+1} = R_i + (L_i + c_i)^5
+///     R_{i+1} = L_i
+pub fn permute<F: FieldExt>(left: F, right: F, constants: &[F]) -> (F, F) {
+    constants.iter().fold((left, right), |(left, right), c| {
+        let t = left + c;
+        (right + t.square().square() * t, left)
+    })
+}
+
+/// Sponge with rate 1: add each element to the left word, permute, and output the
+/// left word at the end.
+pub fn hash<F: FieldExt>(message: &[F], constants: &[F]) -> F {
+    let (left, _) = message
+        .iter()
+        .fold((F::zero(), F::zero()), |(left, right), m| {
+            permute(left + m, right, constants)
+        });
+    left
+}
+
+#[cfg(test)]
+mod tests {
+    use halo2_proofs::pasta::Fp;
+
+    use super::*;
+
+    #[test]
+    fn test_round_constants() {
+        let constants = round_constants::<Fp>();
+        assert_eq!(constants.len(), ROUNDS);
+        assert_eq!(constants[0], Fp::zero());
+        assert_eq!(
+            format!("{:?}", constants[1]),
+            "0x2d53cfbda7a5629df136c41c966485cef761f948727f997402de21c95991bfb8"
+        );
+    }
+
+    #[test]
+    fn test_permute_vector() {
+        let constants = round_constants::<Fp>();
+        let (left, right) = permute(Fp::one(), Fp::from(2), &constants);
+        assert_eq!(
+            format!("{:?}", left),
+            "0x2503702bb125839009f5656388545751e6e7a3ae8bba80af130e35e8e77af47d"
+        );
+        assert_eq!(
+            format!("{:?}", right),
+            "0x015a013ab6858da553ead0cc42846d44800f4a00507e01ff3fa2811140fe63b4"
+        );
+    }
+
+    #[test]
+    fn test_hash_vector() {
+        let constants = round_constants::<Fp>();
+        let digest = hash(&[Fp::one(), Fp::from(2)], &constants);
+        assert_eq!(
+            format!("{:?}", digest),
+            "0x2497a6757a853a57a16bc6279839193c28bd6f9c18edc10feafd09282bd055d3"
+        );
+
+        // the inverse network recovers the input, so the Feistel round really is a permutation
+        let (left, right) = permute(Fp::from(3), Fp::from(4), &constants);
+        let (right, left) = constants
+            .iter()
+            .rev()
+            .fold((left, right), |(left, right), c| {
+                let t = right + c;
+                (right, left - t.square().square() * t)
+            });
+        assert_eq!((left, right), (Fp::from(4), Fp::from(3)));
+    }
+}
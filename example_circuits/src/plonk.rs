@@ -0,0 +1,548 @@
+//! PLONK from scratch, as in the notes, to check what halo2 does against the protocol
+//! itself. Every row `i` has three wires `a_i, b_i, c_i` and one gate:
+//!
+//!     qL_i a_i + qR_i b_i + qO_i c_i + qM_i a_i b_i + qC_i + PI_i = 0
+//!
+//! The rows are the points `ω^i` of the subgroup `H` of order `n`, and every column is
+//! the polynomial that interpolates it over `H`. The gate holds on every row iff the left
+//! side vanishes on `H`, i.e. iff it is `t(X) * Z_H(X)` for some quotient `t`, with
+//! `Z_H = X^n - 1`.
+//!
+//! Copy constraints are a permutation `σ` of the `3n` wire positions that goes around
+//! every set of cells that must be equal. Position `(j, i)` is labelled `k_j ω^i`, with
+//! `k_j` picking a different coset of `H` for every column, and the values agree around
+//! every cycle iff
+//!
+//!     prod_{j, i} (w_j(ω^i) + β k_j ω^i + γ) / (w_j(ω^i) + β σ_j(ω^i) + γ) = 1
+//!
+//! for random `β, γ`. The accumulator `z` runs that product row by row: `z(1) = 1` and
+//! `z(ωX)` is `z(X)` times the row's factor, which is again an identity on `H`.
+//!
+//! Two shortcuts against the paper: there is no linearization, every polynomial is opened
+//! at `ζ` and the verifier checks the whole identity on the evaluations, and there are no
+//! blinding factors, so the proofs are not zero-knowledge.
+
+use ark_bn254::G1Affine;
+use ff::{Field, PrimeField};
+use halo2_proofs::arithmetic::FieldExt;
+
+use crate::{
+    bn254::{
+        fr::Fr,
+        kzg::{BatchOpening, Opening, Srs},
+    },
+    poly::{Domain, Polynomial},
+};
+
+use self::transcript::Transcript;
+
+pub mod ex1;
+pub mod transcript;
+
+/// The three wires of a row.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Wire {
+    A,
+    B,
+    C,
+}
+
+/// A wire in a row, which is what copy constraints connect.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Position {
+    pub wire: Wire,
+    pub row: usize,
+}
+
+impl Position {
+    pub fn a(row: usize) -> Self {
+        Self { wire: Wire::A, row }
+    }
+
+    pub fn b(row: usize) -> Self {
+        Self { wire: Wire::B, row }
+    }
+
+    pub fn c(row: usize) -> Self {
+        Self { wire: Wire::C, row }
+    }
+}
+
+/// The selectors of one row.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Gate {
+    pub q_l: Fr,
+    pub q_r: Fr,
+    pub q_o: Fr,
+    pub q_m: Fr,
+    pub q_c: Fr,
+}
+
+impl Gate {
+    /// `a * b = c`
+    pub fn mul() -> Self {
+        Self {
+            q_m: Fr::one(),
+            q_o: -Fr::one(),
+            ..Self::default()
+        }
+    }
+
+    /// `a + b = c`
+    pub fn add() -> Self {
+        Self {
+            q_l: Fr::one(),
+            q_r: Fr::one(),
+            q_o: -Fr::one(),
+            ..Self::default()
+        }
+    }
+
+    /// `a = constant`
+    pub fn constant(constant: Fr) -> Self {
+        Self {
+            q_l: Fr::one(),
+            q_c: -constant,
+            ..Self::default()
+        }
+    }
+
+    /// `a = x` for the public input `x`, which comes in through `PI`.
+    fn public_input() -> Self {
+        Self {
+            q_l: Fr::one(),
+            ..Self::default()
+        }
+    }
+
+    fn evaluate(&self, a: Fr, b: Fr, c: Fr) -> Fr {
+        self.q_l * a + self.q_r * b + self.q_o * c + self.q_m * a * b + self.q_c
+    }
+}
+
+/// The gates row by row and the copies between their wires. The first `num_public` rows
+/// hold the public inputs, `a_i = x_i`.
+#[derive(Debug, Clone, Default)]
+pub struct Circuit {
+    pub gates: Vec<Gate>,
+    pub copies: Vec<(Position, Position)>,
+    pub num_public: usize,
+}
+
+impl Circuit {
+    /// Adds the row of the next public input, before any other gate.
+    pub fn public_input(&mut self) -> usize {
+        assert_eq!(
+            self.gates.len(),
+            self.num_public,
+            "public inputs come first"
+        );
+        self.num_public += 1;
+        self.gate(Gate::public_input())
+    }
+
+    pub fn gate(&mut self, gate: Gate) -> usize {
+        self.gates.push(gate);
+        self.gates.len() - 1
+    }
+
+    pub fn copy(&mut self, left: Position, right: Position) {
+        self.copies.push((left, right));
+    }
+
+    /// Checks the gates and copies on the witness directly, like `MockProver`.
+    pub fn is_satisfied(&self, witness: &[[Fr; 3]], public: &[Fr]) -> bool {
+        let value = |position: Position| witness[position.row][position.wire as usize];
+        let gates = self
+            .gates
+            .iter()
+            .zip(witness)
+            .enumerate()
+            .all(|(i, (gate, [a, b, c]))| {
+                let pi = public.get(i).map_or(Fr::zero(), |x| -*x);
+                gate.evaluate(*a, *b, *c) + pi == Fr::zero()
+            });
+        public.len() == self.num_public
+            && witness.len() == self.gates.len()
+            && gates
+            && self
+                .copies
+                .iter()
+                .all(|(left, right)| value(*left) == value(*right))
+    }
+}
+
+/// `k_j`: `1, δ, δ^2` with `δ` of odd order, so the three columns sit on different cosets of
+/// `H`.
+fn coset_shifts() -> [Fr; 3] {
+    [Fr::one(), Fr::DELTA, Fr::DELTA.square()]
+}
+
+/// `σ_j(ω^i)` for every position: the label of the next position around its copy cycle.
+///
+/// Every position starts as a cycle of its own, and a copy between two cycles merges them
+/// by swapping where the two positions point to, like halo2 does.
+fn permutation(circuit: &Circuit, domain: &Domain<Fr>) -> [Vec<Fr>; 3] {
+    let n = domain.n;
+    let index = |position: &Position| position.wire as usize * n + position.row;
+    let mut mapping: Vec<usize> = (0..3 * n).collect();
+    let mut cycle: Vec<usize> = (0..3 * n).collect();
+
+    for (left, right) in &circuit.copies {
+        let (left, right) = (index(left), index(right));
+        if cycle[left] == cycle[right] {
+            continue;
+        }
+        let mut position = right;
+        loop {
+            cycle[position] = cycle[left];
+            position = mapping[position];
+            if position == right {
+                break;
+            }
+        }
+        mapping.swap(left, right);
+    }
+
+    let shifts = coset_shifts();
+    let powers: Vec<_> = domain.elements().collect();
+    [0, 1, 2].map(|j| {
+        (0..n)
+            .map(|i| mapping[j * n + i])
+            .map(|next| shifts[next / n] * powers[next % n])
+            .collect()
+    })
+}
+
+/// What the verifier knows about the circuit: commitments to the selectors and to `σ`.
+#[derive(Debug, Clone)]
+pub struct VerifyingKey {
+    pub k: u32,
+    pub num_public: usize,
+    pub selectors: [G1Affine; 5],
+    pub sigmas: [G1Affine; 3],
+}
+
+#[derive(Debug, Clone)]
+pub struct ProvingKey {
+    pub vk: VerifyingKey,
+    /// `qL, qR, qO, qM, qC`
+    selectors: [Polynomial<Fr>; 5],
+    sigmas: [Polynomial<Fr>; 3],
+    /// `σ` over `H`, for the accumulator.
+    sigma_values: [Vec<Fr>; 3],
+}
+
+/// Rows padded to `n = 2^k`, with all-zero gates that anything satisfies.
+pub fn setup(srs: &Srs, circuit: &Circuit) -> ProvingKey {
+    let k = circuit.gates.len().next_power_of_two().trailing_zeros();
+    let domain = Domain::new(k);
+
+    let column = |f: fn(&Gate) -> Fr| domain.ifft(&circuit.gates.iter().map(f).collect::<Vec<_>>());
+    let selectors = [
+        column(|gate| gate.q_l),
+        column(|gate| gate.q_r),
+        column(|gate| gate.q_o),
+        column(|gate| gate.q_m),
+        column(|gate| gate.q_c),
+    ];
+    let sigma_values = permutation(circuit, &domain);
+    let sigmas = [0, 1, 2].map(|j| domain.ifft(&sigma_values[j]));
+
+    let vk = VerifyingKey {
+        k,
+        num_public: circuit.num_public,
+        selectors: [0, 1, 2, 3, 4].map(|i| srs.commit(&selectors[i].coeffs)),
+        sigmas: [0, 1, 2].map(|j| srs.commit(&sigmas[j].coeffs)),
+    };
+    ProvingKey {
+        vk,
+        selectors,
+        sigmas,
+        sigma_values,
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Proof {
+    pub wires: [G1Affine; 3],
+    pub z: G1Affine,
+    pub t: G1Affine,
+    /// `a, b, c, qL, qR, qO, qM, qC, σ1, σ2, σ3, z, t` at `ζ`.
+    pub at_zeta: BatchOpening,
+    /// `z` at `ζω`.
+    pub at_zeta_omega: Opening,
+}
+
+/// Both sides start from the circuit and the statement.
+fn transcript(vk: &VerifyingKey, public: &[Fr]) -> Transcript {
+    let mut transcript = Transcript::default();
+    for commitment in vk.selectors.iter().chain(&vk.sigmas) {
+        transcript.absorb_point(commitment);
+    }
+    for x in public {
+        transcript.absorb_scalar(x);
+    }
+    transcript
+}
+
+pub fn prove(srs: &Srs, pk: &ProvingKey, witness: &[[Fr; 3]], public: &[Fr]) -> Proof {
+    let vk = &pk.vk;
+    let domain = Domain::new(vk.k);
+    let n = domain.n;
+    assert!(
+        witness.len() <= n,
+        "{} rows of witness for {} rows",
+        witness.len(),
+        n
+    );
+    assert_eq!(public.len(), vk.num_public);
+    let mut transcript = transcript(vk, public);
+
+    // Round 1: the wires
+    let wire_values = [0, 1, 2].map(|j| {
+        let mut values: Vec<_> = witness.iter().map(|row| row[j]).collect();
+        values.resize(n, Fr::zero());
+        values
+    });
+    let wires = [0, 1, 2].map(|j| domain.ifft(&wire_values[j]));
+    let wire_commitments = [0, 1, 2].map(|j| srs.commit(&wires[j].coeffs));
+    for commitment in &wire_commitments {
+        transcript.absorb_point(commitment);
+    }
+
+    // Round 2: the permutation accumulator
+    let beta = transcript.squeeze();
+    let gamma = transcript.squeeze();
+    let shifts = coset_shifts();
+    let factors = domain.elements().take(n - 1).enumerate().map(|(i, point)| {
+        let mut numerator = Fr::one();
+        let mut denominator = Fr::one();
+        for j in 0..3 {
+            numerator *= wire_values[j][i] + beta * shifts[j] * point + gamma;
+            denominator *= wire_values[j][i] + beta * pk.sigma_values[j][i] + gamma;
+        }
+        numerator * denominator.invert().unwrap()
+    });
+    let z_values: Vec<_> = std::iter::once(Fr::one())
+        .chain(factors.scan(Fr::one(), |z, factor| {
+            *z *= factor;
+            Some(*z)
+        }))
+        .collect();
+    let z = domain.ifft(&z_values);
+    let z_commitment = srs.commit(&z.coeffs);
+    transcript.absorb_point(&z_commitment);
+
+    // Round 3: the quotient, on a coset of a subgroup 4 times larger: the numerator has
+    // degree up to `4n`, and `Z_H` has no zeros off `H`.
+    let alpha = transcript.squeeze();
+    let extended = Domain::new(vk.k + 2);
+    let shift = Fr::multiplicative_generator();
+    let on_coset = |poly: &Polynomial<Fr>| extended.coset_fft(poly, shift);
+
+    let [a, b, c] = [0, 1, 2].map(|j| on_coset(&wires[j]));
+    let [q_l, q_r, q_o, q_m, q_c] = [0, 1, 2, 3, 4].map(|i| on_coset(&pk.selectors[i]));
+    let [s_1, s_2, s_3] = [0, 1, 2].map(|j| on_coset(&pk.sigmas[j]));
+    let z_coset = on_coset(&z);
+    // -sum_i x_i L_i(X), so that a_i + PI_i = 0 on the public input rows
+    let pi = on_coset(&domain.ifft(&public.iter().map(|x| -*x).collect::<Vec<_>>()));
+    let l_0 = on_coset(&domain.lagrange(0));
+
+    let t_values: Vec<_> = extended
+        .elements()
+        .enumerate()
+        .map(|(i, point)| {
+            let x = shift * point;
+            // ω is ω_4n^4, so z(ωX) on the coset is z four points further along
+            let z_next = z_coset[(i + 4) % extended.n];
+            let gate = q_l[i] * a[i]
+                + q_r[i] * b[i]
+                + q_o[i] * c[i]
+                + q_m[i] * a[i] * b[i]
+                + q_c[i]
+                + pi[i];
+            let copies = z_coset[i]
+                * (a[i] + beta * shifts[0] * x + gamma)
+                * (b[i] + beta * shifts[1] * x + gamma)
+                * (c[i] + beta * shifts[2] * x + gamma)
+                - z_next
+                    * (a[i] + beta * s_1[i] + gamma)
+                    * (b[i] + beta * s_2[i] + gamma)
+                    * (c[i] + beta * s_3[i] + gamma);
+            let start = (z_coset[i] - Fr::one()) * l_0[i];
+
+            let numerator = gate + alpha * copies + alpha.square() * start;
+            numerator * domain.evaluate_vanishing(x).invert().unwrap()
+        })
+        .collect();
+    // An honest `t` has degree below `3n`. A cheating one isn't a polynomial at all, and
+    // cutting it off doesn't help it at `ζ`.
+    let mut t = extended.coset_ifft(&t_values, shift);
+    t.coeffs.truncate(3 * n);
+    let t_commitment = srs.commit(&t.coeffs);
+    transcript.absorb_point(&t_commitment);
+
+    // Round 4: the openings
+    let zeta = transcript.squeeze();
+    let polys: Vec<&Polynomial<Fr>> = wires
+        .iter()
+        .chain(&pk.selectors)
+        .chain(&pk.sigmas)
+        .chain([&z, &t])
+        .collect();
+    for poly in &polys {
+        transcript.absorb_scalar(&poly.evaluate(zeta));
+    }
+    let at_zeta_omega = srs.open(&z.coeffs, zeta * domain.omega);
+    transcript.absorb_scalar(&at_zeta_omega.value);
+    let v = transcript.squeeze();
+
+    Proof {
+        wires: wire_commitments,
+        z: z_commitment,
+        t: t_commitment,
+        at_zeta: srs.open_batch(
+            &polys
+                .iter()
+                .map(|poly| poly.coeffs.as_slice())
+                .collect::<Vec<_>>(),
+            zeta,
+            v,
+        ),
+        at_zeta_omega,
+    }
+}
+
+pub fn verify(srs: &Srs, vk: &VerifyingKey, public: &[Fr], proof: &Proof) -> bool {
+    if public.len() != vk.num_public {
+        return false;
+    }
+    let domain = Domain::new(vk.k);
+    let mut transcript = transcript(vk, public);
+
+    for commitment in &proof.wires {
+        transcript.absorb_point(commitment);
+    }
+    let beta = transcript.squeeze();
+    let gamma = transcript.squeeze();
+    transcript.absorb_point(&proof.z);
+    let alpha = transcript.squeeze();
+    transcript.absorb_point(&proof.t);
+    let zeta = transcript.squeeze();
+    for value in &proof.at_zeta.values {
+        transcript.absorb_scalar(value);
+    }
+    transcript.absorb_scalar(&proof.at_zeta_omega.value);
+    let v = transcript.squeeze();
+
+    if proof.at_zeta.point != zeta || proof.at_zeta_omega.point != zeta * domain.omega {
+        return false;
+    }
+    let [a, b, c, q_l, q_r, q_o, q_m, q_c, s_1, s_2, s_3, z, t]: [Fr; 13] =
+        match proof.at_zeta.values.clone().try_into() {
+            Ok(values) => values,
+            Err(_) => return false,
+        };
+    let z_next = proof.at_zeta_omega.value;
+
+    let pi = public.iter().enumerate().fold(Fr::zero(), |acc, (i, x)| {
+        acc - *x * domain.evaluate_lagrange(i, zeta)
+    });
+    let l_0 = domain.evaluate_lagrange(0, zeta);
+
+    let shifts = coset_shifts();
+    let gate = q_l * a + q_r * b + q_o * c + q_m * a * b + q_c + pi;
+    let copies = z
+        * (a + beta * shifts[0] * zeta + gamma)
+        * (b + beta * shifts[1] * zeta + gamma)
+        * (c + beta * shifts[2] * zeta + gamma)
+        - z_next * (a + beta * s_1 + gamma) * (b + beta * s_2 + gamma) * (c + beta * s_3 + gamma);
+    let start = (z - Fr::one()) * l_0;
+    if gate + alpha * copies + alpha.square() * start != t * domain.evaluate_vanishing(zeta) {
+        return false;
+    }
+
+    let commitments: Vec<_> = proof
+        .wires
+        .iter()
+        .chain(&vk.selectors)
+        .chain(&vk.sigmas)
+        .chain([&proof.z, &proof.t])
+        .copied()
+        .collect();
+    srs.verify_batch(&commitments, &proof.at_zeta, v) && srs.verify(&proof.z, &proof.at_zeta_omega)
+}
+
+#[cfg(test)]
+mod tests {
+    use rand::rngs::OsRng;
+
+    use super::*;
+
+    #[test]
+    fn test_permutation() {
+        // a_0 = b_1 = c_2, and a_1 = a_2
+        let mut circuit = Circuit::default();
+        circuit.copy(Position::a(0), Position::b(1));
+        circuit.copy(Position::c(2), Position::b(1));
+        circuit.copy(Position::a(1), Position::a(2));
+        circuit.copy(Position::a(2), Position::a(1));
+
+        let domain = Domain::new(2);
+        let sigmas = permutation(&circuit, &domain);
+        let label = |position: Position| {
+            coset_shifts()[position.wire as usize] * domain.omega.pow_vartime([position.row as u64])
+        };
+        let next = |position: Position| sigmas[position.wire as usize][position.row];
+
+        // a_0 -> b_1 -> c_2 -> a_0
+        assert_eq!(next(Position::a(0)), label(Position::b(1)));
+        assert_eq!(next(Position::b(1)), label(Position::c(2)));
+        assert_eq!(next(Position::c(2)), label(Position::a(0)));
+        assert_eq!(next(Position::a(1)), label(Position::a(2)));
+        assert_eq!(next(Position::a(2)), label(Position::a(1)));
+        assert_eq!(next(Position::c(3)), label(Position::c(3)));
+    }
+
+    #[test]
+    fn test_public_inputs() {
+        // Fibonacci: x_0 and x_1 are the first two numbers, x_2 the tenth
+        let mut circuit = Circuit::default();
+        for _ in 0..3 {
+            circuit.public_input();
+        }
+        let first = circuit.gate(Gate::add());
+        circuit.copy(Position::a(0), Position::a(first));
+        circuit.copy(Position::a(1), Position::b(first));
+        for row in first + 1..first + 8 {
+            circuit.gate(Gate::add());
+            circuit.copy(Position::b(row - 1), Position::a(row));
+            circuit.copy(Position::c(row - 1), Position::b(row));
+        }
+        circuit.copy(Position::c(first + 7), Position::a(2));
+
+        let (mut x, mut y) = (Fr::one(), Fr::one());
+        let mut witness = vec![
+            [Fr::one(), Fr::zero(), Fr::zero()],
+            [Fr::one(), Fr::zero(), Fr::zero()],
+            [Fr::from(55u64), Fr::zero(), Fr::zero()],
+        ];
+        for _ in 0..8 {
+            witness.push([x, y, x + y]);
+            (x, y) = (y, x + y);
+        }
+        let public = [Fr::one(), Fr::one(), Fr::from(55u64)];
+        assert!(circuit.is_satisfied(&witness, &public));
+
+        let srs = Srs::setup(Fr::random(OsRng), 64);
+        let pk = setup(&srs, &circuit);
+        assert_eq!(pk.vk.k, 4);
+        let proof = prove(&srs, &pk, &witness, &public);
+        assert!(verify(&srs, &pk.vk, &public, &proof));
+
+        let wrong = [Fr::one(), Fr::one(), Fr::from(56u64)];
+        assert!(!verify(&srs, &pk.vk, &wrong, &proof));
+        assert!(!verify(&srs, &pk.vk, &public[..2], &proof));
+    }
+}
@@ -0,0 +1,136 @@
+use ff::Field;
+
+use super::{Circuit, Gate, Position};
+use crate::bn254::fr::Fr;
+
+/// `c = constant * a^2 * b^2` from `examples/ex1.rs`, in PLONK rows instead of halo2
+/// regions:
+///
+///     | row |    a     |   b   |    c    |  gate        |
+///     --------------------------------------------------
+///     |  0  |    c     |       |         |  public      |
+///     |  1  |    a     |   b   |   ab    |  mul         |
+///     |  2  |   ab     |  ab   |  absq   |  mul         |
+///     |  3  | constant |       |         |  constant    |
+///     |  4  | constant | absq  |    c    |  mul         |
+///
+/// with copies `c_1 = a_2 = b_2`, `c_2 = b_4`, `a_3 = a_4` and `c_4 = a_0`.
+///
+/// `FieldChip` spends a region on every `load_private` and copies the cell into the mul;
+/// here `a` and `b` are the wires of the first mul directly. `load_constant` is
+/// `assign_advice_from_constant`, a copy from a fixed cell, which here is the `qC` of
+/// row 3. `expose_public` is the copy to row 0.
+pub fn circuit(constant: Fr) -> Circuit {
+    let mut circuit = Circuit::default();
+    let public = circuit.public_input();
+    let ab = circuit.gate(Gate::mul());
+    let absq = circuit.gate(Gate::mul());
+    let load_constant = circuit.gate(Gate::constant(constant));
+    let c = circuit.gate(Gate::mul());
+
+    circuit.copy(Position::c(ab), Position::a(absq));
+    circuit.copy(Position::c(ab), Position::b(absq));
+    circuit.copy(Position::c(absq), Position::b(c));
+    circuit.copy(Position::a(load_constant), Position::a(c));
+    circuit.copy(Position::c(c), Position::a(public));
+    circuit
+}
+
+/// The rows above, and the public `c`.
+pub fn witness(constant: Fr, a: Fr, b: Fr) -> (Vec<[Fr; 3]>, Fr) {
+    let ab = a * b;
+    let absq = ab.square();
+    let c = constant * absq;
+    let witness = vec![
+        [c, Fr::zero(), Fr::zero()],
+        [a, b, ab],
+        [ab, ab, absq],
+        [constant, Fr::zero(), Fr::zero()],
+        [constant, absq, c],
+    ];
+    (witness, c)
+}
+
+#[cfg(test)]
+mod tests {
+    use rand::rngs::OsRng;
+
+    use super::*;
+    use crate::{
+        bn254::kzg::Srs,
+        plonk::{prove, setup, verify},
+    };
+
+    fn setup_ex1() -> (Srs, Circuit) {
+        (Srs::setup(Fr::random(OsRng), 32), circuit(Fr::from(7u64)))
+    }
+
+    #[test]
+    fn test_ex1() {
+        let (srs, circuit) = setup_ex1();
+        let (witness, c) = witness(Fr::from(7u64), Fr::from(2u64), Fr::from(3u64));
+        // the values of examples/ex1.rs: 7 * 2^2 * 3^2
+        assert_eq!(c, Fr::from(252u64));
+        assert!(circuit.is_satisfied(&witness, &[c]));
+
+        let pk = setup(&srs, &circuit);
+        assert_eq!(pk.vk.k, 3);
+        let proof = prove(&srs, &pk, &witness, &[c]);
+        assert!(verify(&srs, &pk.vk, &[c], &proof));
+        assert!(!verify(&srs, &pk.vk, &[c + Fr::one()], &proof));
+    }
+
+    #[test]
+    fn test_broken_gate() {
+        // ab is off by one everywhere it's copied to, so only the first mul fails
+        let (srs, circuit) = setup_ex1();
+        let (a, b) = (Fr::from(2u64), Fr::from(3u64));
+        let (mut witness, _) = witness(Fr::from(7u64), a, b);
+        let ab = a * b + Fr::one();
+        let absq = ab.square();
+        let c = Fr::from(7u64) * absq;
+        witness[0][0] = c;
+        witness[1][2] = ab;
+        witness[2] = [ab, ab, absq];
+        witness[4] = [Fr::from(7u64), absq, c];
+        assert!(!circuit.is_satisfied(&witness, &[c]));
+
+        let pk = setup(&srs, &circuit);
+        let proof = prove(&srs, &pk, &witness, &[c]);
+        assert!(!verify(&srs, &pk.vk, &[c], &proof));
+    }
+
+    #[test]
+    fn test_broken_copy() {
+        // every gate holds, but the public c isn't the one the last mul computes
+        let (srs, circuit) = setup_ex1();
+        let (mut witness, c) = witness(Fr::from(7u64), Fr::from(2u64), Fr::from(3u64));
+        let wrong = c + Fr::one();
+        witness[0][0] = wrong;
+        assert!(!circuit.is_satisfied(&witness, &[wrong]));
+
+        let pk = setup(&srs, &circuit);
+        let proof = prove(&srs, &pk, &witness, &[wrong]);
+        assert!(!verify(&srs, &pk.vk, &[wrong], &proof));
+    }
+
+    #[test]
+    fn test_tampered_proof() {
+        let (srs, circuit) = setup_ex1();
+        let (witness, c) = witness(Fr::from(7u64), Fr::from(2u64), Fr::from(3u64));
+        let pk = setup(&srs, &circuit);
+        let proof = prove(&srs, &pk, &witness, &[c]);
+
+        let mut wrong_evaluation = proof.clone();
+        wrong_evaluation.at_zeta.values[0] += Fr::one();
+        assert!(!verify(&srs, &pk.vk, &[c], &wrong_evaluation));
+
+        let mut wrong_shift = proof.clone();
+        wrong_shift.at_zeta_omega.value += Fr::one();
+        assert!(!verify(&srs, &pk.vk, &[c], &wrong_shift));
+
+        // a different constant is a different circuit
+        let other = setup(&srs, &super::circuit(Fr::from(8u64)));
+        assert!(!verify(&srs, &other.vk, &[c], &proof));
+    }
+}
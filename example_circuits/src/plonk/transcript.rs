@@ -0,0 +1,69 @@
+use ark_bn254::G1Affine;
+use ark_ff::{BigInteger, PrimeField as _};
+use blake2b_simd::{Params, State};
+use ff::PrimeField;
+use halo2_proofs::arithmetic::FieldExt;
+
+use crate::bn254::fr::Fr;
+
+/// Fiat–Shamir: every challenge is a hash of everything the prover sent before it, so the
+/// prover can't pick its messages after seeing the challenge. The verifier absorbs the
+/// same messages in the same order and gets the same challenges.
+#[derive(Debug, Clone)]
+pub struct Transcript {
+    state: State,
+}
+
+impl Default for Transcript {
+    fn default() -> Self {
+        Self {
+            state: Params::new()
+                .hash_length(64)
+                .personal(b"PLONK-transcript")
+                .to_state(),
+        }
+    }
+}
+
+impl Transcript {
+    pub fn absorb_point(&mut self, point: &G1Affine) {
+        self.state.update(&[point.infinity as u8]);
+        self.state.update(&point.x.into_bigint().to_bytes_le());
+        self.state.update(&point.y.into_bigint().to_bytes_le());
+    }
+
+    pub fn absorb_scalar(&mut self, scalar: &Fr) {
+        self.state.update(scalar.to_repr().as_ref());
+    }
+
+    /// A 512-bit hash reduced into `Fr`, which is as good as uniform. The hash is absorbed
+    /// too, so the next challenge is a different one.
+    pub fn squeeze(&mut self) -> Fr {
+        let hash = self.state.finalize();
+        self.state.update(hash.as_bytes());
+        Fr::from_bytes_wide(hash.as_array())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use ff::Field;
+
+    use super::*;
+
+    #[test]
+    fn test_challenges() {
+        let mut prover = Transcript::default();
+        let mut verifier = Transcript::default();
+        prover.absorb_scalar(&Fr::one());
+        verifier.absorb_scalar(&Fr::one());
+
+        let challenge = prover.squeeze();
+        assert_eq!(verifier.squeeze(), challenge);
+        assert_ne!(prover.squeeze(), challenge);
+
+        let mut other = Transcript::default();
+        other.absorb_scalar(&Fr::from(2u64));
+        assert_ne!(other.squeeze(), challenge);
+    }
+}
@@ -0,0 +1,417 @@
+//! Plookup, the lookup argument from the paper, for one column `f` of `n` values and a
+//! table `t` of `n + 1`, so that both fit the subgroup `H` of order `N = n + 1`.
+//!
+//! The prover sorts `s = f ∪ t` in the order of the table. Every `f_i` is in `t` iff every
+//! adjacent pair `(s_i, s_{i+1})` of `s` is either a repeat or an adjacent pair of `t`,
+//! and the grand product checks exactly that, as multisets:
+//!
+//!     (1 + β)^n prod_i (γ + f_i) prod_i (γ(1 + β) + t_i + β t_{i+1})
+//!         = prod_i (γ(1 + β) + s_i + β s_{i+1})
+//!
+//! A repeat `s_i = s_{i+1} = f_j` contributes `(1 + β)(γ + f_j)` on the right, and a pair
+//! of the table contributes its own factor. `s` is too long for `H`, so it is split into
+//! `h1 = s[..N]` and `h2 = s[N - 1..]`, which share one element. That leaves room for
+//! only `n` values in `f`, so the last row of `f` isn't in the product: it is pinned to
+//! the last row of `t` instead.
+//!
+//! halo2's lookup does the same job differently: it permutes the input and the table
+//! into `A'` and `S'` and checks `A'_i = S'_i` or `A'_i = A'_{i-1}` row by row, with
+//! a product argument for each permutation.
+
+use std::collections::HashMap;
+
+use ark_bn254::G1Affine;
+use ff::{Field, PrimeField};
+
+use crate::{
+    bn254::{
+        fr::Fr,
+        kzg::{BatchOpening, Srs},
+    },
+    plonk::transcript::Transcript,
+    poly::{Domain, Polynomial},
+};
+
+/// `0, 1, ..., range - 1`, the table of `range_check::ex2`.
+pub fn range_table(range: u64) -> Vec<Fr> {
+    (0..range).map(Fr::from).collect()
+}
+
+#[derive(Debug, Clone)]
+pub struct VerifyingKey {
+    pub k: u32,
+    pub table: G1Affine,
+}
+
+#[derive(Debug, Clone)]
+pub struct ProvingKey {
+    pub vk: VerifyingKey,
+    table: Polynomial<Fr>,
+    /// `t` over `H`, padded with its last value.
+    table_values: Vec<Fr>,
+}
+
+/// A table of up to `2^k` values, for lookups of up to `2^k - 1`. Repeating the last value
+/// doesn't change the set the table stands for.
+pub fn setup(srs: &Srs, table: &[Fr], k: u32) -> ProvingKey {
+    let domain = Domain::new(k);
+    assert!(
+        !table.is_empty() && table.len() <= domain.n,
+        "a table of {} values for {} rows",
+        table.len(),
+        domain.n
+    );
+    let mut table_values = table.to_vec();
+    table_values.resize(domain.n, *table.last().unwrap());
+
+    let table = domain.ifft(&table_values);
+    ProvingKey {
+        vk: VerifyingKey {
+            k,
+            table: srs.commit(&table.coeffs),
+        },
+        table,
+        table_values,
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Proof {
+    pub f: G1Affine,
+    pub h1: G1Affine,
+    pub h2: G1Affine,
+    pub z: G1Affine,
+    pub q: G1Affine,
+    /// `f, t, h1, h2, z, q` at `ζ`.
+    pub at_zeta: BatchOpening,
+    /// `t, h1, h2, z` at `ζω`.
+    pub at_zeta_omega: BatchOpening,
+}
+
+/// `s`: `f` and `t` together, sorted by where the values are in the table. A value that
+/// isn't in the table has nowhere to go, so it goes to the end, and the proof fails.
+fn sorted(values: &[Fr], table: &[Fr]) -> Vec<Fr> {
+    let mut index = HashMap::new();
+    for (i, value) in table.iter().enumerate() {
+        index.entry(value.to_repr().as_ref().to_vec()).or_insert(i);
+    }
+    let mut sorted: Vec<_> = values.iter().chain(table).copied().collect();
+    sorted.sort_by_key(|value| {
+        index
+            .get(value.to_repr().as_ref())
+            .copied()
+            .unwrap_or(usize::MAX)
+    });
+    sorted
+}
+
+/// `β, γ`, and the two sides of the product they define for a row: `z_{i+1} / z_i` is
+/// the numerator over the denominator.
+struct Challenges {
+    beta: Fr,
+    gamma: Fr,
+}
+
+impl Challenges {
+    fn numerator(&self, f: Fr, t: Fr, t_next: Fr) -> Fr {
+        let epsilon = self.gamma * (Fr::one() + self.beta);
+        (Fr::one() + self.beta) * (self.gamma + f) * (epsilon + t + self.beta * t_next)
+    }
+
+    fn denominator(&self, h1: Fr, h1_next: Fr, h2: Fr, h2_next: Fr) -> Fr {
+        let epsilon = self.gamma * (Fr::one() + self.beta);
+        (epsilon + h1 + self.beta * h1_next) * (epsilon + h2 + self.beta * h2_next)
+    }
+}
+
+/// Proves that every one of `values`, up to `2^k - 1` of them, is in the table.
+pub fn prove(srs: &Srs, pk: &ProvingKey, values: &[Fr]) -> Proof {
+    let n = pk.table_values.len();
+    assert!(
+        values.len() < n,
+        "{} values for a table of {}",
+        values.len(),
+        n
+    );
+
+    // Padding with a table value keeps an honest `f` in the table, and the last row has
+    // to be the last row of `t`.
+    let mut f_values = values.to_vec();
+    f_values.resize(n - 1, pk.table_values[0]);
+    f_values.push(pk.table_values[n - 1]);
+    prove_rows(srs, pk, f_values)
+}
+
+/// The proof for `f_values`, all `2^k` rows of `f`.
+fn prove_rows(srs: &Srs, pk: &ProvingKey, f_values: Vec<Fr>) -> Proof {
+    let vk = &pk.vk;
+    let domain = Domain::new(vk.k);
+    let n = domain.n;
+    let s = sorted(&f_values[..n - 1], &pk.table_values);
+    let (h1_values, h2_values) = (&s[..n], &s[n - 1..]);
+
+    let mut transcript = Transcript::default();
+    transcript.absorb_point(&vk.table);
+    let [f, h1, h2] = [&f_values[..], h1_values, h2_values].map(|values| domain.ifft(values));
+    let [f_commitment, h1_commitment, h2_commitment] =
+        [&f, &h1, &h2].map(|poly| srs.commit(&poly.coeffs));
+    for commitment in [&f_commitment, &h1_commitment, &h2_commitment] {
+        transcript.absorb_point(commitment);
+    }
+
+    let challenges = Challenges {
+        beta: transcript.squeeze(),
+        gamma: transcript.squeeze(),
+    };
+    let t_values = &pk.table_values;
+    let factors = (0..n - 1).map(|i| {
+        let numerator = challenges.numerator(f_values[i], t_values[i], t_values[i + 1]);
+        let denominator = challenges.denominator(
+            h1_values[i],
+            h1_values[i + 1],
+            h2_values[i],
+            h2_values[i + 1],
+        );
+        numerator * denominator.invert().unwrap()
+    });
+    let z_values: Vec<_> = std::iter::once(Fr::one())
+        .chain(factors.scan(Fr::one(), |z, factor| {
+            *z *= factor;
+            Some(*z)
+        }))
+        .collect();
+    let z = domain.ifft(&z_values);
+    let z_commitment = srs.commit(&z.coeffs);
+    transcript.absorb_point(&z_commitment);
+
+    // The quotient of
+    //
+    //     L_0(X) (z(X) - 1)
+    //     (X - ω^(N-1)) (z(X) numerator(X) - z(ωX) denominator(X))
+    //     L_(N-1)(X) (h1(X) - h2(ωX))
+    //     L_(N-1)(X) (z(X) - 1)
+    //     L_(N-1)(X) (f(X) - t(X))
+    //
+    // combined with powers of α. The product doesn't hold across the last row, where ωX
+    // wraps around to the first, so that row of `f` is checked on its own.
+    let alpha = transcript.squeeze();
+    let extended = Domain::new(vk.k + 2);
+    let shift = Fr::multiplicative_generator();
+    let on_coset = |poly: &Polynomial<Fr>| extended.coset_fft(poly, shift);
+    let [f_coset, t_coset, h1_coset, h2_coset, z_coset] =
+        [&f, &pk.table, &h1, &h2, &z].map(on_coset);
+    let first = on_coset(&domain.lagrange(0));
+    let last = on_coset(&domain.lagrange(n - 1));
+    let last_point = domain.omega.invert().unwrap();
+
+    let q_values: Vec<_> = extended
+        .elements()
+        .enumerate()
+        .map(|(i, point)| {
+            let x = shift * point;
+            // ω is ω_4N^4, so the next row is four points further along
+            let next = (i + 4) % extended.n;
+            let product = z_coset[i] * challenges.numerator(f_coset[i], t_coset[i], t_coset[next])
+                - z_coset[next]
+                    * challenges.denominator(
+                        h1_coset[i],
+                        h1_coset[next],
+                        h2_coset[i],
+                        h2_coset[next],
+                    );
+
+            let numerator = [
+                first[i] * (z_coset[i] - Fr::one()),
+                (x - last_point) * product,
+                last[i] * (h1_coset[i] - h2_coset[next]),
+                last[i] * (z_coset[i] - Fr::one()),
+                last[i] * (f_coset[i] - t_coset[i]),
+            ]
+            .iter()
+            .rev()
+            .fold(Fr::zero(), |acc, term| acc * alpha + term);
+            numerator * domain.evaluate_vanishing(x).invert().unwrap()
+        })
+        .collect();
+    let mut q = extended.coset_ifft(&q_values, shift);
+    q.coeffs.truncate(3 * n);
+    let q_commitment = srs.commit(&q.coeffs);
+    transcript.absorb_point(&q_commitment);
+
+    let zeta = transcript.squeeze();
+    let at_zeta = [&f, &pk.table, &h1, &h2, &z, &q];
+    let at_zeta_omega = [&pk.table, &h1, &h2, &z];
+    for poly in at_zeta {
+        transcript.absorb_scalar(&poly.evaluate(zeta));
+    }
+    for poly in at_zeta_omega {
+        transcript.absorb_scalar(&poly.evaluate(zeta * domain.omega));
+    }
+    let v = transcript.squeeze();
+
+    let open = |polys: &[&Polynomial<Fr>], point| {
+        srs.open_batch(
+            &polys
+                .iter()
+                .map(|poly| poly.coeffs.as_slice())
+                .collect::<Vec<_>>(),
+            point,
+            v,
+        )
+    };
+    Proof {
+        f: f_commitment,
+        h1: h1_commitment,
+        h2: h2_commitment,
+        z: z_commitment,
+        q: q_commitment,
+        at_zeta: open(&at_zeta, zeta),
+        at_zeta_omega: open(&at_zeta_omega, zeta * domain.omega),
+    }
+}
+
+/// Checks that the values committed to in `proof.f`, all `2^k` rows of it, are in the table.
+pub fn verify(srs: &Srs, vk: &VerifyingKey, proof: &Proof) -> bool {
+    let domain = Domain::new(vk.k);
+    let mut transcript = Transcript::default();
+    transcript.absorb_point(&vk.table);
+    for commitment in [&proof.f, &proof.h1, &proof.h2] {
+        transcript.absorb_point(commitment);
+    }
+    let challenges = Challenges {
+        beta: transcript.squeeze(),
+        gamma: transcript.squeeze(),
+    };
+    transcript.absorb_point(&proof.z);
+    let alpha = transcript.squeeze();
+    transcript.absorb_point(&proof.q);
+    let zeta = transcript.squeeze();
+    for value in proof
+        .at_zeta
+        .values
+        .iter()
+        .chain(&proof.at_zeta_omega.values)
+    {
+        transcript.absorb_scalar(value);
+    }
+    let v = transcript.squeeze();
+
+    if proof.at_zeta.point != zeta || proof.at_zeta_omega.point != zeta * domain.omega {
+        return false;
+    }
+    let (f, t, h1, h2, z, q, t_next, h1_next, h2_next, z_next) =
+        match (&proof.at_zeta.values[..], &proof.at_zeta_omega.values[..]) {
+            ([f, t, h1, h2, z, q], [t_next, h1_next, h2_next, z_next]) => (
+                *f, *t, *h1, *h2, *z, *q, *t_next, *h1_next, *h2_next, *z_next,
+            ),
+            _ => return false,
+        };
+
+    let first = domain.evaluate_lagrange(0, zeta);
+    let last = domain.evaluate_lagrange(domain.n - 1, zeta);
+    let last_point = domain.omega.invert().unwrap();
+    let product = z * challenges.numerator(f, t, t_next)
+        - z_next * challenges.denominator(h1, h1_next, h2, h2_next);
+    let numerator = [
+        first * (z - Fr::one()),
+        (zeta - last_point) * product,
+        last * (h1 - h2_next),
+        last * (z - Fr::one()),
+        last * (f - t),
+    ]
+    .iter()
+    .rev()
+    .fold(Fr::zero(), |acc, term| acc * alpha + term);
+    if numerator != q * domain.evaluate_vanishing(zeta) {
+        return false;
+    }
+
+    let at_zeta = [proof.f, vk.table, proof.h1, proof.h2, proof.z, proof.q];
+    let at_zeta_omega = [vk.table, proof.h1, proof.h2, proof.z];
+    srs.verify_batch(&at_zeta, &proof.at_zeta, v)
+        && srs.verify_batch(&at_zeta_omega, &proof.at_zeta_omega, v)
+}
+
+#[cfg(test)]
+mod tests {
+    use rand::rngs::OsRng;
+
+    use super::*;
+
+    const LOOKUP_RANGE: u64 = 16;
+
+    fn setup_range() -> (Srs, ProvingKey) {
+        let srs = Srs::setup(Fr::random(OsRng), 96);
+        let pk = setup(&srs, &range_table(LOOKUP_RANGE), 5);
+        (srs, pk)
+    }
+
+    #[test]
+    fn test_sorted() {
+        let table = range_table(4);
+        let s = sorted(&[Fr::from(2u64), Fr::from(0u64), Fr::from(2u64)], &table);
+        let expected = [0u64, 0, 1, 2, 2, 2, 3].map(Fr::from);
+        assert_eq!(s, expected);
+
+        // out of the table: nowhere to go
+        let s = sorted(&[Fr::from(7u64), Fr::from(1u64)], &table);
+        assert_eq!(s.last(), Some(&Fr::from(7u64)));
+    }
+
+    #[test]
+    fn test_range_check() {
+        let (srs, pk) = setup_range();
+        // like range_check::ex2, repeats and the ends of the range included
+        let values = [0u64, 5, 15, 15, 7, 1].map(Fr::from);
+        let proof = prove(&srs, &pk, &values);
+        assert!(verify(&srs, &pk.vk, &proof));
+
+        // as many values as fit, each table value at least once
+        let values: Vec<_> = (0..31).map(|i| Fr::from(i % LOOKUP_RANGE)).collect();
+        let proof = prove(&srs, &pk, &values);
+        assert!(verify(&srs, &pk.vk, &proof));
+    }
+
+    #[test]
+    fn test_outside_table() {
+        let (srs, pk) = setup_range();
+        for outside in [Fr::from(LOOKUP_RANGE), -Fr::one(), Fr::from(1000u64)] {
+            let values = [Fr::from(3u64), outside, Fr::from(4u64)];
+            let proof = prove(&srs, &pk, &values);
+            assert!(!verify(&srs, &pk.vk, &proof));
+        }
+    }
+
+    #[test]
+    fn test_last_row() {
+        let (srs, pk) = setup_range();
+        let n = pk.table_values.len();
+        // the last row of f is outside the grand product, so only its own constraint
+        // keeps a value out of the table from hiding there
+        for last in [Fr::from(LOOKUP_RANGE), Fr::from(1000u64)] {
+            let mut f_values = vec![Fr::from(3u64); n - 1];
+            f_values.push(last);
+            let proof = prove_rows(&srs, &pk, f_values);
+            assert!(!verify(&srs, &pk.vk, &proof));
+        }
+    }
+
+    #[test]
+    fn test_tampered_proof() {
+        let (srs, pk) = setup_range();
+        let proof = prove(&srs, &pk, &[1u64, 2, 3].map(Fr::from));
+
+        // the proof is about the committed f, not some other one
+        let mut other_f = proof.clone();
+        other_f.f = prove(&srs, &pk, &[1u64, 2, 4].map(Fr::from)).f;
+        assert!(!verify(&srs, &pk.vk, &other_f));
+
+        let mut wrong_evaluation = proof.clone();
+        wrong_evaluation.at_zeta_omega.values[3] += Fr::one();
+        assert!(!verify(&srs, &pk.vk, &wrong_evaluation));
+
+        // a different table
+        let other = setup(&srs, &range_table(8), 5);
+        assert!(!verify(&srs, &other.vk, &proof));
+    }
+}
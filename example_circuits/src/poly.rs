@@ -0,0 +1,466 @@
+//! Univariate polynomials and the FFT, the tools under PLONK and KZG.
+//!
+//! A polynomial of degree below `n` is the same thing as its values on `n` points. The
+//! points PLONK picks are the subgroup `H = {1, ω, ..., ω^(n-1)}` of the `n`-th roots of
+//! unity, `n = 2^k`, where the FFT moves between the two forms in `O(n log n)`:
+//! coefficients to values is evaluating, values to coefficients is interpolating, and the
+//! inverse FFT is the FFT with `ω^-1`, divided by `n`.
+//!
+//! On `H` itself `Z_H = X^n - 1` is zero, so anything divided by it, like PLONK's quotient,
+//! is computed on a coset `gH` instead.
+
+use std::ops::{Add, Mul, Neg, Sub};
+
+use halo2_proofs::arithmetic::FieldExt;
+
+/// A polynomial by its coefficients, lowest degree first. Trailing zeros are allowed and
+/// ignored.
+#[derive(Debug, Clone)]
+pub struct Polynomial<F> {
+    pub coeffs: Vec<F>,
+}
+
+impl<F: FieldExt> Polynomial<F> {
+    pub fn new(coeffs: Vec<F>) -> Self {
+        Self { coeffs }
+    }
+
+    pub fn zero() -> Self {
+        Self::new(vec![])
+    }
+
+    /// `X - point`
+    pub fn linear(point: F) -> Self {
+        Self::new(vec![-point, F::one()])
+    }
+
+    /// The coefficients without the trailing zeros.
+    pub fn trimmed(&self) -> &[F] {
+        let len = self
+            .coeffs
+            .iter()
+            .rposition(|coeff| !bool::from(coeff.is_zero()))
+            .map_or(0, |i| i + 1);
+        &self.coeffs[..len]
+    }
+
+    /// `None` for the zero polynomial.
+    pub fn degree(&self) -> Option<usize> {
+        self.trimmed().len().checked_sub(1)
+    }
+
+    /// Horner's rule.
+    pub fn evaluate(&self, point: F) -> F {
+        self.coeffs
+            .iter()
+            .rev()
+            .fold(F::zero(), |acc, coeff| acc * point + coeff)
+    }
+
+    pub fn scale(&self, by: F) -> Self {
+        Self::new(self.coeffs.iter().map(|coeff| *coeff * by).collect())
+    }
+
+    /// Long division: `self = quotient * divisor + remainder` with the remainder of lower
+    /// degree than the divisor.
+    pub fn div_rem(&self, divisor: &Self) -> (Self, Self) {
+        let divisor = divisor.trimmed();
+        let lead_inv = divisor.last().expect("division by zero").invert().unwrap();
+        let mut remainder = self.trimmed().to_vec();
+        if remainder.len() < divisor.len() {
+            return (Self::zero(), Self::new(remainder));
+        }
+
+        let mut quotient = vec![F::zero(); remainder.len() - divisor.len() + 1];
+        for i in (0..quotient.len()).rev() {
+            let coeff = remainder[i + divisor.len() - 1] * lead_inv;
+            quotient[i] = coeff;
+            for (remainder, d) in remainder[i..].iter_mut().zip(divisor) {
+                *remainder -= coeff * d;
+            }
+        }
+        remainder.truncate(divisor.len() - 1);
+        (Self::new(quotient), Self::new(remainder))
+    }
+
+    /// Division by `X^n - 1`, which only takes additions: `c_i X^i = c_i X^(i-n) (X^n - 1)
+    /// + c_i X^(i-n)`.
+    pub fn divide_by_vanishing(&self, n: usize) -> (Self, Self) {
+        let mut remainder = self.coeffs.clone();
+        let mut quotient = vec![F::zero(); remainder.len().saturating_sub(n)];
+        for i in (n..remainder.len()).rev() {
+            let coeff = remainder[i];
+            quotient[i - n] += coeff;
+            remainder[i - n] += coeff;
+        }
+        remainder.truncate(n);
+        (Self::new(quotient), Self::new(remainder))
+    }
+}
+
+impl<F: FieldExt> PartialEq for Polynomial<F> {
+    fn eq(&self, other: &Self) -> bool {
+        self.trimmed() == other.trimmed()
+    }
+}
+
+impl<F: FieldExt> Eq for Polynomial<F> {}
+
+impl<F: FieldExt> Add for &Polynomial<F> {
+    type Output = Polynomial<F>;
+
+    fn add(self, rhs: Self) -> Polynomial<F> {
+        let (long, short) = if self.coeffs.len() >= rhs.coeffs.len() {
+            (self, rhs)
+        } else {
+            (rhs, self)
+        };
+        let mut coeffs = long.coeffs.clone();
+        for (acc, coeff) in coeffs.iter_mut().zip(&short.coeffs) {
+            *acc += coeff;
+        }
+        Polynomial::new(coeffs)
+    }
+}
+
+impl<F: FieldExt> Neg for &Polynomial<F> {
+    type Output = Polynomial<F>;
+
+    fn neg(self) -> Polynomial<F> {
+        Polynomial::new(self.coeffs.iter().map(|coeff| -*coeff).collect())
+    }
+}
+
+impl<F: FieldExt> Sub for &Polynomial<F> {
+    type Output = Polynomial<F>;
+
+    fn sub(self, rhs: Self) -> Polynomial<F> {
+        self + &-rhs
+    }
+}
+
+/// The schoolbook product. [`Domain`] does it in `O(n log n)` for large ones.
+impl<F: FieldExt> Mul for &Polynomial<F> {
+    type Output = Polynomial<F>;
+
+    fn mul(self, rhs: Self) -> Polynomial<F> {
+        let (lhs, rhs) = (self.trimmed(), rhs.trimmed());
+        if lhs.is_empty() || rhs.is_empty() {
+            return Polynomial::zero();
+        }
+        let mut coeffs = vec![F::zero(); lhs.len() + rhs.len() - 1];
+        for (i, l) in lhs.iter().enumerate() {
+            for (acc, r) in coeffs[i..].iter_mut().zip(rhs) {
+                *acc += *l * r;
+            }
+        }
+        Polynomial::new(coeffs)
+    }
+}
+
+/// `H`, the subgroup of order `n = 2^k`.
+#[derive(Debug, Clone)]
+pub struct Domain<F> {
+    pub k: u32,
+    pub n: usize,
+    pub omega: F,
+    omega_inv: F,
+    n_inv: F,
+}
+
+impl<F: FieldExt> Domain<F> {
+    pub fn new(k: u32) -> Self {
+        assert!(
+            k <= F::S,
+            "the field only has roots of unity up to order 2^{}",
+            F::S
+        );
+        let omega = F::root_of_unity().pow_vartime([1u64 << (F::S - k)]);
+        Self {
+            k,
+            n: 1 << k,
+            omega,
+            omega_inv: omega.invert().unwrap(),
+            n_inv: F::from(1u64 << k).invert().unwrap(),
+        }
+    }
+
+    /// `1, ω, ..., ω^(n-1)`
+    pub fn elements(&self) -> impl Iterator<Item = F> {
+        let omega = self.omega;
+        std::iter::successors(Some(F::one()), move |power| Some(*power * omega)).take(self.n)
+    }
+
+    /// `[f(1), f(ω), ..., f(ω^(n-1))]`, for `f` of degree below `n`.
+    pub fn fft(&self, poly: &Polynomial<F>) -> Vec<F> {
+        let mut values = self.padded(poly.trimmed());
+        fft(&mut values, self.omega);
+        values
+    }
+
+    /// The polynomial of degree below `n` that takes `values` on `H`.
+    pub fn ifft(&self, values: &[F]) -> Polynomial<F> {
+        let mut coeffs = self.padded(values);
+        fft(&mut coeffs, self.omega_inv);
+        for coeff in coeffs.iter_mut() {
+            *coeff *= self.n_inv;
+        }
+        Polynomial::new(coeffs)
+    }
+
+    /// `f` on the coset `gH`: the FFT of `f(gX)`, whose coefficients are `f_i g^i`.
+    pub fn coset_fft(&self, poly: &Polynomial<F>, shift: F) -> Vec<F> {
+        let mut values = self.padded(poly.trimmed());
+        scale_by_powers(&mut values, shift);
+        fft(&mut values, self.omega);
+        values
+    }
+
+    pub fn coset_ifft(&self, values: &[F], shift: F) -> Polynomial<F> {
+        let mut poly = self.ifft(values);
+        scale_by_powers(&mut poly.coeffs, shift.invert().unwrap());
+        poly
+    }
+
+    /// The product in `O(n log n)`: multiply the values, then interpolate. The product
+    /// must still fit below degree `n`.
+    pub fn mul(&self, lhs: &Polynomial<F>, rhs: &Polynomial<F>) -> Polynomial<F> {
+        let values: Vec<_> = self
+            .fft(lhs)
+            .into_iter()
+            .zip(self.fft(rhs))
+            .map(|(l, r)| l * r)
+            .collect();
+        self.ifft(&values)
+    }
+
+    /// `Z_H = X^n - 1`, zero on all of `H`.
+    pub fn vanishing(&self) -> Polynomial<F> {
+        let mut coeffs = vec![F::zero(); self.n + 1];
+        coeffs[0] = -F::one();
+        coeffs[self.n] = F::one();
+        Polynomial::new(coeffs)
+    }
+
+    pub fn evaluate_vanishing(&self, point: F) -> F {
+        point.pow_vartime([self.n as u64]) - F::one()
+    }
+
+    /// `L_i`, which is 1 at `ω^i` and 0 on the rest of `H`.
+    pub fn lagrange(&self, i: usize) -> Polynomial<F> {
+        let mut values = vec![F::zero(); self.n];
+        values[i] = F::one();
+        self.ifft(&values)
+    }
+
+    /// `L_i(x) = ω^i / n * Z_H(x) / (x - ω^i)`, without interpolating `L_i`. The formula is
+    /// `0 / 0` at `ω^i` itself, where `L_i` is 1.
+    pub fn evaluate_lagrange(&self, i: usize, point: F) -> F {
+        let omega_i = self.omega.pow_vartime([i as u64]);
+        if point == omega_i {
+            return F::one();
+        }
+        omega_i * self.n_inv * self.evaluate_vanishing(point) * (point - omega_i).invert().unwrap()
+    }
+
+    /// `f(x)` for the `f` that takes `values` on `H`, as `sum_i values[i] L_i(x)`.
+    pub fn evaluate_values(&self, values: &[F], point: F) -> F {
+        values
+            .iter()
+            .enumerate()
+            .fold(F::zero(), |acc, (i, value)| {
+                acc + *value * self.evaluate_lagrange(i, point)
+            })
+    }
+
+    fn padded(&self, values: &[F]) -> Vec<F> {
+        assert!(
+            values.len() <= self.n,
+            "{} coefficients for a domain of {}",
+            values.len(),
+            self.n
+        );
+        let mut padded = values.to_vec();
+        padded.resize(self.n, F::zero());
+        padded
+    }
+}
+
+fn scale_by_powers<F: FieldExt>(coeffs: &mut [F], by: F) {
+    let mut power = F::one();
+    for coeff in coeffs.iter_mut() {
+        *coeff *= power;
+        power *= by;
+    }
+}
+
+/// The radix-2 FFT in place, for `omega` of order `values.len()`.
+///
+/// The recursion `f(X) = f_even(X^2) + X f_odd(X^2)` turned inside out: after the
+/// bit-reversal permutation, the evens and odds of every level sit next to each other,
+/// and the butterflies
+///
+///     f(ω^i)         = f_even(ω^2i) + ω^i f_odd(ω^2i)
+///     f(ω^(i + n/2)) = f_even(ω^2i) - ω^i f_odd(ω^2i)
+///
+/// merge them into twice as long a transform, `log n` times.
+fn fft<F: FieldExt>(values: &mut [F], omega: F) {
+    let n = values.len();
+    if n <= 1 {
+        return;
+    }
+    let log_n = n.trailing_zeros();
+    for i in 0..n {
+        let j = i.reverse_bits() >> (usize::BITS - log_n);
+        if i < j {
+            values.swap(i, j);
+        }
+    }
+
+    let mut half = 1;
+    while half < n {
+        let twiddle = omega.pow_vartime([(n / (2 * half)) as u64]);
+        for chunk in values.chunks_mut(2 * half) {
+            let (even, odd) = chunk.split_at_mut(half);
+            let mut power = F::one();
+            for (even, odd) in even.iter_mut().zip(odd) {
+                let t = *odd * power;
+                *odd = *even - t;
+                *even += t;
+                power *= twiddle;
+            }
+        }
+        half *= 2;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use ff::Field;
+    use halo2_proofs::pasta::Fp;
+    use rand::rngs::OsRng;
+
+    use super::*;
+    use crate::bn254::fr::Fr;
+
+    fn random_poly<F: FieldExt>(len: usize) -> Polynomial<F> {
+        Polynomial::new((0..len).map(|_| F::random(OsRng)).collect())
+    }
+
+    fn fft_matches_evaluation<F: FieldExt>() {
+        let domain = Domain::<F>::new(4);
+        let poly = random_poly(16);
+
+        let values = domain.fft(&poly);
+        for (value, point) in values.iter().zip(domain.elements()) {
+            assert_eq!(*value, poly.evaluate(point));
+        }
+        assert_eq!(domain.ifft(&values), poly);
+
+        let shift = F::multiplicative_generator();
+        let values = domain.coset_fft(&poly, shift);
+        assert_eq!(
+            values[5],
+            poly.evaluate(shift * domain.omega.pow_vartime([5u64]))
+        );
+        assert_eq!(domain.coset_ifft(&values, shift), poly);
+    }
+
+    #[test]
+    fn test_fft() {
+        fft_matches_evaluation::<Fp>();
+        fft_matches_evaluation::<Fr>();
+
+        // n = 1 is the identity, and the poly may be shorter than the domain
+        let domain = Domain::<Fp>::new(0);
+        assert_eq!(
+            domain.fft(&Polynomial::new(vec![Fp::from(5u64)])),
+            vec![Fp::from(5u64)]
+        );
+        let constant = Polynomial::new(vec![Fp::from(5u64)]);
+        assert_eq!(Domain::new(3).fft(&constant), vec![Fp::from(5u64); 8]);
+    }
+
+    #[test]
+    fn test_arithmetic() {
+        let (a, b) = (random_poly::<Fp>(5), random_poly::<Fp>(3));
+        let product = &a * &b;
+        assert_eq!(product.degree(), Some(6));
+        assert_eq!(Domain::new(3).mul(&a, &b), product);
+
+        let x = Fp::random(OsRng);
+        assert_eq!((&a + &b).evaluate(x), a.evaluate(x) + b.evaluate(x));
+        assert_eq!((&a - &a).degree(), None);
+
+        let (quotient, remainder) = product.div_rem(&b);
+        assert_eq!(quotient, a);
+        assert_eq!(remainder.degree(), None);
+
+        let (quotient, remainder) = a.div_rem(&b);
+        assert!(remainder.degree() < b.degree());
+        assert_eq!(&(&quotient * &b) + &remainder, a);
+
+        // dividing by X - z leaves f(z)
+        let (_, remainder) = a.div_rem(&Polynomial::linear(x));
+        assert_eq!(remainder, Polynomial::new(vec![a.evaluate(x)]));
+    }
+
+    #[test]
+    fn test_vanishing() {
+        let domain = Domain::<Fp>::new(3);
+        for point in domain.elements() {
+            assert_eq!(domain.evaluate_vanishing(point), Fp::zero());
+            assert_eq!(domain.vanishing().evaluate(point), Fp::zero());
+        }
+
+        // anything that is zero on H is a multiple of Z_H
+        let multiple = &random_poly(20) * &domain.vanishing();
+        let (quotient, remainder) = multiple.divide_by_vanishing(domain.n);
+        assert_eq!(remainder.degree(), None);
+        assert_eq!(&quotient * &domain.vanishing(), multiple);
+
+        let poly = random_poly(20);
+        let (quotient, remainder) = poly.divide_by_vanishing(domain.n);
+        assert_eq!(
+            poly.div_rem(&domain.vanishing()),
+            (quotient.clone(), remainder.clone())
+        );
+        // the remainder is what H sees of the poly
+        assert_eq!(
+            domain.fft(&remainder),
+            domain
+                .elements()
+                .map(|point| poly.evaluate(point))
+                .collect::<Vec<_>>()
+        );
+    }
+
+    #[test]
+    fn test_lagrange() {
+        let domain = Domain::<Fr>::new(3);
+        let x = Fr::random(OsRng);
+        for i in 0..domain.n {
+            let lagrange = domain.lagrange(i);
+            for (j, point) in domain.elements().enumerate() {
+                assert_eq!(lagrange.evaluate(point), Fr::from((i == j) as u64));
+                assert_eq!(
+                    domain.evaluate_lagrange(i, point),
+                    Fr::from((i == j) as u64)
+                );
+            }
+            assert_eq!(domain.evaluate_lagrange(i, x), lagrange.evaluate(x));
+        }
+
+        // the L_i add up to 1
+        let sum = (0..domain.n)
+            .map(|i| domain.evaluate_lagrange(i, x))
+            .fold(Fr::zero(), |acc, l| acc + l);
+        assert_eq!(sum, Fr::one());
+
+        let values: Vec<_> = (0..domain.n).map(|_| Fr::random(OsRng)).collect();
+        assert_eq!(
+            domain.evaluate_values(&values, x),
+            domain.ifft(&values).evaluate(x)
+        );
+    }
+}
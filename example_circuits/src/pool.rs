@@ -0,0 +1,204 @@
+//! Proving many instances of one circuit at once.
+//!
+//! halo2 already spreads the FFTs and MSMs of a single proof over rayon's threads, but a
+//! small circuit doesn't have enough of them to keep every core busy, and synthesis and
+//! the transcript are sequential anyway. Proving several witnesses side by side does.
+//!
+//! Every proof gets its own RNG, seeded from the pool's seed and the witness's index, so
+//! the proofs are the same whichever thread makes them and however many there are. The
+//! RNG seed is a hash of the pair, not a sum: with `seed + index`, proof `i + 1` of one
+//! pool would share its blinding factors with proof `i` of a pool seeded one higher, and
+//! the difference of their commitments would be an unblinded commitment to the
+//! difference of the witnesses.
+
+use std::{
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        mpsc,
+    },
+    thread,
+};
+
+use blake2b_simd::Params as Blake2bParams;
+use halo2_proofs::{
+    arithmetic::CurveAffine,
+    plonk::{Circuit, Error, ProvingKey},
+    poly::commitment::Params,
+};
+use rand::{rngs::StdRng, SeedableRng};
+
+use crate::dev_tools::prover;
+
+/// A circuit with its witness, and the instance columns it proves.
+pub type Witness<C, F> = (C, Vec<Vec<F>>);
+
+#[derive(Debug)]
+pub struct ProverPool<'a, C: CurveAffine> {
+    params: &'a Params<C>,
+    pk: &'a ProvingKey<C>,
+    threads: usize,
+    seed: [u8; 32],
+}
+
+impl<'a, C: CurveAffine> ProverPool<'a, C> {
+    /// A pool with a thread per core.
+    ///
+    /// `seed` is where every blinding factor of every proof comes from, so it has to be
+    /// secret and high-entropy, e.g. from `OsRng`: anyone who knows it can strip the
+    /// blinding from all the pool's proofs and read the witnesses off them. Only reuse a
+    /// seed to reproduce proofs you'd be happy to publish the witnesses of.
+    pub fn new(params: &'a Params<C>, pk: &'a ProvingKey<C>, seed: [u8; 32]) -> Self {
+        let threads = thread::available_parallelism().map_or(1, |n| n.get());
+        Self::with_threads(params, pk, seed, threads)
+    }
+
+    pub fn with_threads(
+        params: &'a Params<C>,
+        pk: &'a ProvingKey<C>,
+        seed: [u8; 32],
+        threads: usize,
+    ) -> Self {
+        assert!(threads > 0, "a pool needs at least one thread");
+        Self {
+            params,
+            pk,
+            threads,
+            seed,
+        }
+    }
+
+    /// Proves every witness, returning the proofs in the same order. A witness that fails
+    /// to synthesize only fails its own proof.
+    pub fn prove<ConcreteCircuit: Circuit<C::Scalar> + Sync>(
+        &self,
+        witnesses: &[Witness<ConcreteCircuit, C::Scalar>],
+    ) -> Vec<Result<Vec<u8>, Error>> {
+        let next = AtomicUsize::new(0);
+        let (sender, receiver) = mpsc::channel();
+        thread::scope(|scope| {
+            for _ in 0..self.threads.min(witnesses.len()) {
+                let sender = sender.clone();
+                let next = &next;
+                scope.spawn(move || loop {
+                    let index = next.fetch_add(1, Ordering::Relaxed);
+                    match witnesses.get(index) {
+                        Some((circuit, instance)) => sender
+                            .send((index, self.prove_one(index, circuit, instance)))
+                            .unwrap(),
+                        None => break,
+                    }
+                });
+            }
+        });
+        drop(sender);
+
+        let mut proofs: Vec<_> = receiver.into_iter().collect();
+        proofs.sort_by_key(|(index, _)| *index);
+        proofs.into_iter().map(|(_, proof)| proof).collect()
+    }
+
+    fn prove_one<ConcreteCircuit: Circuit<C::Scalar>>(
+        &self,
+        index: usize,
+        circuit: &ConcreteCircuit,
+        instance: &[Vec<C::Scalar>],
+    ) -> Result<Vec<u8>, Error> {
+        let instance: Vec<_> = instance.iter().map(|column| column.as_slice()).collect();
+        prover::prove(self.params, self.pk, circuit, &instance, self.rng(index))
+    }
+
+    /// The RNG of the proof at `index`: `BLAKE2b-256("ProverPool-seeds", seed, index)`.
+    fn rng(&self, index: usize) -> StdRng {
+        let hash = Blake2bParams::new()
+            .hash_length(32)
+            .personal(b"ProverPool-seeds")
+            .to_state()
+            .update(&self.seed)
+            .update(&(index as u64).to_le_bytes())
+            .finalize();
+        StdRng::from_seed(hash.as_bytes().try_into().unwrap())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use halo2_proofs::{
+        circuit::Value,
+        pasta::{EqAffine, Fp},
+    };
+
+    use super::*;
+    use crate::{
+        batch::{verify_batch, Statement},
+        dev_tools::prover::setup,
+        range_check::ex1::MyCircuit,
+    };
+
+    const RANGE: usize = 8;
+
+    fn witnesses(count: usize) -> Vec<Witness<MyCircuit<Fp, RANGE>, Fp>> {
+        (0..count)
+            .map(|i| {
+                let circuit = MyCircuit {
+                    value: Value::known(Fp::from((i % RANGE) as u64).into()),
+                };
+                (circuit, vec![])
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_proofs_verify() {
+        let (params, pk) = setup::<EqAffine, _>(&MyCircuit::<Fp, RANGE>::default());
+        let mut witnesses = witnesses(12);
+        // no value to prove
+        witnesses[3].0 = MyCircuit::default();
+
+        let proofs = ProverPool::with_threads(&params, &pk, [0; 32], 4).prove(&witnesses);
+        assert_eq!(proofs.len(), 12);
+        assert!(matches!(proofs[3], Err(Error::Synthesis)));
+
+        let statements: Vec<_> = proofs
+            .into_iter()
+            .enumerate()
+            .filter(|(i, _)| *i != 3)
+            .map(|(_, proof)| Statement {
+                proof: proof.unwrap(),
+                instance: vec![],
+            })
+            .collect();
+        assert_eq!(verify_batch(&params, pk.get_vk(), &statements), Ok(()));
+    }
+
+    #[test]
+    fn test_deterministic() {
+        let (params, pk) = setup::<EqAffine, _>(&MyCircuit::<Fp, RANGE>::default());
+        let witnesses = witnesses(RANGE + 2);
+        let proofs = |seed, threads| -> Vec<_> {
+            ProverPool::with_threads(&params, &pk, seed, threads)
+                .prove(&witnesses)
+                .into_iter()
+                .map(Result::unwrap)
+                .collect()
+        };
+
+        let sequential = proofs([7; 32], 1);
+        assert_eq!(proofs([7; 32], 3), sequential);
+        assert_ne!(proofs([8; 32], 3), sequential);
+        // the same value, but a different RNG
+        assert_ne!(sequential[0], sequential[RANGE]);
+    }
+
+    #[test]
+    fn test_seeds_dont_overlap() {
+        let (params, pk) = setup::<EqAffine, _>(&MyCircuit::<Fp, RANGE>::default());
+        // the same value at index 1 of one pool and index 0 of a pool seeded one higher
+        let seed = [7; 32];
+        let mut next_seed = seed;
+        next_seed[0] += 1;
+        let proofs = ProverPool::with_threads(&params, &pk, seed, 1).prove(&witnesses(2));
+        let shifted: Vec<_> = witnesses(2).into_iter().skip(1).collect();
+        let next = ProverPool::with_threads(&params, &pk, next_seed, 1).prove(&shifted);
+        assert_ne!(proofs[1].as_ref().unwrap(), next[0].as_ref().unwrap());
+    }
+}
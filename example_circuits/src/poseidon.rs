@@ -0,0 +1,529 @@
+use halo2_proofs::{
+    arithmetic::FieldExt,
+    circuit::{floor_planner::V1, AssignedCell, Layouter, Region, Value},
+    plonk::{
+        Advice, Circuit, Column, ConstraintSystem, Constraints, Error, Expression, Fixed, Instance,
+        Selector,
+    },
+    poly::Rotation,
+};
+
+mod grain;
+pub mod native;
+
+use native::{initial_capacity, Spec, RATE, WIDTH};
+
+/// The Poseidon permutation, one round per row, and a sponge on top of it.
+///
+/// A round adds the round constants, applies `x^5` to every word (full round) or to the
+/// first word only (partial round), and mixes the state with the MDS matrix:
+///
+///     |  s_0  |  s_1  |  s_2  |  rc_0  |  rc_1  |  rc_2  | q_full | q_partial |
+///     -------------------------------------------------------------------------
+///     |  x_0  |  x_1  |  x_2  | rc_0_0 | rc_0_1 | rc_0_2 |   1    |     0     |
+///     |  ...  |  ...  |  ...  |  ...   |  ...   |  ...   |  ...   |    ...    |
+///     |  ...  |  ...  |  ...  | rc_4_0 | rc_4_1 | rc_4_2 |   0    |     1     |
+///     |  ...  |  ...  |  ...  |  ...   |  ...   |  ...   |  ...   |    ...    |
+///     |  y_0  |  y_1  |  y_2  |        |        |        |   0    |     0     |
+///
+/// full round:     s_i(next) = sum_j mds[i][j] * (s_j + rc_j)^5
+/// partial round:  s_i(next) = mds[i][0] * (s_0 + rc_0)^5 + sum_{j>0} mds[i][j] * (s_j + rc_j)
+///
+/// Absorbing adds two inputs to the rate part of the state, two rows above the permutation:
+///
+///     |  s_0  |  s_1  |  s_2  | q_absorb |
+///     ------------------------------------
+///     |  x_0  |  x_1  |  x_2  |    1     |
+///     |  m_0  |  m_1  |       |    0     |
+///     | x_0+m_0 | x_1+m_1 | x_2 |  0     |  <- first row of the permutation
+///
+#[derive(Debug, Clone)]
+pub struct PoseidonConfig<F: FieldExt> {
+    state: [Column<Advice>; WIDTH],
+    round_constants: [Column<Fixed>; WIDTH],
+    q_full: Selector,
+    q_partial: Selector,
+    q_absorb: Selector,
+    spec: Spec<F>,
+}
+
+/// The words of the sponge state, as cells of the circuit.
+pub type State<F> = [AssignedCell<F, F>; WIDTH];
+
+#[derive(Debug, Clone)]
+pub struct PoseidonChip<F: FieldExt> {
+    config: PoseidonConfig<F>,
+}
+
+impl<F: FieldExt> PoseidonChip<F> {
+    pub fn construct(config: PoseidonConfig<F>) -> Self {
+        Self { config }
+    }
+
+    /// Configures the permutation with the default (`P128Pow5T3`) parameters.
+    pub fn configure(
+        meta: &mut ConstraintSystem<F>,
+        state: [Column<Advice>; WIDTH],
+    ) -> PoseidonConfig<F> {
+        Self::configure_with_spec(meta, state, Spec::default())
+    }
+
+    pub fn configure_with_spec(
+        meta: &mut ConstraintSystem<F>,
+        state: [Column<Advice>; WIDTH],
+        spec: Spec<F>,
+    ) -> PoseidonConfig<F> {
+        let round_constants = [(); WIDTH].map(|_| meta.fixed_column());
+        for column in state {
+            meta.enable_equality(column);
+        }
+        // the initial state and the zero padding are constants
+        meta.enable_constant(round_constants[0]);
+
+        let q_full = meta.selector();
+        let q_partial = meta.selector();
+        let q_absorb = meta.selector();
+
+        let pow_5 = |x: Expression<F>| {
+            let x2 = x.clone() * x.clone();
+            x2.clone() * x2 * x
+        };
+
+        // a round constrains the next row to MDS * sbox(state + rc)
+        let round_gate =
+            |meta: &mut ConstraintSystem<F>, name: &'static str, selector: Selector, full: bool| {
+                let mds = spec.mds;
+                meta.create_gate(name, |meta| {
+                    let selector = meta.query_selector(selector);
+                    let words: Vec<_> = (0..WIDTH)
+                        .map(|j| {
+                            let word = meta.query_advice(state[j], Rotation::cur())
+                                + meta.query_fixed(round_constants[j], Rotation::cur());
+                            if full || j == 0 {
+                                pow_5(word)
+                            } else {
+                                word
+                            }
+                        })
+                        .collect();
+
+                    let constraints: Vec<_> = (0..WIDTH)
+                        .map(|i| {
+                            let next = meta.query_advice(state[i], Rotation::next());
+                            let mixed = (0..WIDTH)
+                                .fold(Expression::Constant(F::zero()), |acc, j| {
+                                    acc + words[j].clone() * mds[i][j]
+                                });
+                            next - mixed
+                        })
+                        .collect();
+
+                    Constraints::with_selector(selector, constraints)
+                });
+            };
+        round_gate(meta, "full round", q_full, true);
+        round_gate(meta, "partial round", q_partial, false);
+
+        meta.create_gate("absorb", |meta| {
+            let q_absorb = meta.query_selector(q_absorb);
+
+            let constraints: Vec<_> = (0..WIDTH)
+                .map(|i| {
+                    let old = meta.query_advice(state[i], Rotation::cur());
+                    let new = meta.query_advice(state[i], Rotation(2));
+                    if i < RATE {
+                        let input = meta.query_advice(state[i], Rotation::next());
+                        new - (old + input)
+                    } else {
+                        // the capacity is carried over untouched
+                        new - old
+                    }
+                })
+                .collect();
+
+            Constraints::with_selector(q_absorb, constraints)
+        });
+
+        PoseidonConfig {
+            state,
+            round_constants,
+            q_full,
+            q_partial,
+            q_absorb,
+            spec,
+        }
+    }
+
+    pub fn spec(&self) -> &Spec<F> {
+        &self.config.spec
+    }
+
+    /// Assigns `[0, 0, capacity]`, the state a sponge hashing `length` elements starts from.
+    pub fn initial_state(
+        &self,
+        mut layouter: impl Layouter<F>,
+        length: usize,
+    ) -> Result<State<F>, Error> {
+        let config = &self.config;
+        layouter.assign_region(
+            || "initial state",
+            |mut region| {
+                let words = [F::zero(), F::zero(), initial_capacity(length)];
+                let mut cells = vec![];
+                for (i, word) in words.into_iter().enumerate() {
+                    cells.push(region.assign_advice_from_constant(
+                        || format!("s_{}", i),
+                        config.state[i],
+                        0,
+                        word,
+                    )?);
+                }
+                Ok(cells.try_into().unwrap())
+            },
+        )
+    }
+
+    /// Applies the permutation to `state`.
+    pub fn permute(
+        &self,
+        mut layouter: impl Layouter<F>,
+        state: &State<F>,
+    ) -> Result<State<F>, Error> {
+        layouter.assign_region(
+            || "permute",
+            |mut region| {
+                let words = state
+                    .iter()
+                    .enumerate()
+                    .map(|(i, cell)| {
+                        cell.copy_advice(
+                            || format!("s_{}", i),
+                            &mut region,
+                            self.config.state[i],
+                            0,
+                        )
+                    })
+                    .collect::<Result<Vec<_>, _>>()?;
+                self.assign_rounds(&mut region, 0, words.try_into().unwrap())
+            },
+        )
+    }
+
+    /// Adds `inputs` (at most `RATE` of them, padded with zeroes) to the rate part of
+    /// `state`, then applies the permutation.
+    pub fn absorb_and_permute(
+        &self,
+        mut layouter: impl Layouter<F>,
+        state: &State<F>,
+        inputs: &[AssignedCell<F, F>],
+    ) -> Result<State<F>, Error> {
+        assert!(inputs.len() <= RATE);
+        let config = &self.config;
+
+        layouter.assign_region(
+            || "absorb and permute",
+            |mut region| {
+                config.q_absorb.enable(&mut region, 0)?;
+
+                let mut absorbed = vec![];
+                for (i, (word, column)) in state.iter().zip(config.state).enumerate() {
+                    let old = word.copy_advice(|| format!("s_{}", i), &mut region, column, 0)?;
+                    let input = if i >= RATE {
+                        Value::known(F::zero())
+                    } else if let Some(input) = inputs.get(i) {
+                        input
+                            .copy_advice(|| format!("m_{}", i), &mut region, column, 1)?
+                            .value()
+                            .copied()
+                    } else {
+                        region
+                            .assign_advice_from_constant(
+                                || format!("padding m_{}", i),
+                                column,
+                                1,
+                                F::zero(),
+                            )?
+                            .value()
+                            .copied()
+                    };
+                    absorbed.push(region.assign_advice(
+                        || format!("s_{} + m_{}", i, i),
+                        column,
+                        2,
+                        || old.value().copied() + input,
+                    )?);
+                }
+
+                self.assign_rounds(&mut region, 2, absorbed.try_into().unwrap())
+            },
+        )
+    }
+
+    /// Assigns the rounds of the permutation, starting from `state` at `offset`.
+    fn assign_rounds(
+        &self,
+        region: &mut Region<'_, F>,
+        offset: usize,
+        state: State<F>,
+    ) -> Result<State<F>, Error> {
+        let config = &self.config;
+        let spec = &config.spec;
+        let half_full = spec.full_rounds / 2;
+
+        let mut values = Value::known([F::zero(); WIDTH]);
+        for (i, cell) in state.iter().enumerate() {
+            values = values.zip(cell.value()).map(|(mut words, word)| {
+                words[i] = *word;
+                words
+            });
+        }
+
+        let mut state = state;
+        for round in 0..spec.full_rounds + spec.partial_rounds {
+            let row = offset + round;
+            if round < half_full || round >= half_full + spec.partial_rounds {
+                config.q_full.enable(region, row)?;
+            } else {
+                config.q_partial.enable(region, row)?;
+            }
+            for (i, rc) in spec.round_constants[round].iter().enumerate() {
+                region.assign_fixed(
+                    || format!("round {} rc_{}", round, i),
+                    config.round_constants[i],
+                    row,
+                    || Value::known(*rc),
+                )?;
+            }
+
+            values = values.map(|mut words| {
+                native::round(&mut words, spec, round);
+                words
+            });
+            let next = (0..WIDTH)
+                .map(|i| {
+                    region.assign_advice(
+                        || format!("round {} s_{}", round, i),
+                        config.state[i],
+                        row + 1,
+                        || values.map(|words| words[i]),
+                    )
+                })
+                .collect::<Result<Vec<_>, _>>()?;
+            state = next.try_into().unwrap();
+        }
+
+        Ok(state)
+    }
+
+    /// Hashes a fixed-length message, like [`native::hash`].
+    pub fn hash(
+        &self,
+        mut layouter: impl Layouter<F>,
+        message: &[AssignedCell<F, F>],
+    ) -> Result<AssignedCell<F, F>, Error> {
+        let mut sponge = Sponge::new(self.clone(), layouter.namespace(|| "init"), message.len())?;
+        for (i, value) in message.iter().enumerate() {
+            sponge.absorb(
+                layouter.namespace(|| format!("absorb {}", i)),
+                value.clone(),
+            )?;
+        }
+        sponge.squeeze(layouter.namespace(|| "squeeze"))
+    }
+}
+
+/// A sponge over the permutation chip: absorb cells one at a time, squeeze one out.
+///
+/// The state only goes through the permutation once `RATE` inputs are buffered (or on
+/// [`Sponge::squeeze`]), so absorbing costs one permutation per two cells.
+#[derive(Debug)]
+pub struct Sponge<F: FieldExt> {
+    chip: PoseidonChip<F>,
+    state: State<F>,
+    buffer: Vec<AssignedCell<F, F>>,
+}
+
+impl<F: FieldExt> Sponge<F> {
+    /// Starts a sponge that will absorb exactly `length` cells.
+    pub fn new(
+        chip: PoseidonChip<F>,
+        layouter: impl Layouter<F>,
+        length: usize,
+    ) -> Result<Self, Error> {
+        let state = chip.initial_state(layouter, length)?;
+        Ok(Self {
+            chip,
+            state,
+            buffer: vec![],
+        })
+    }
+
+    pub fn absorb(
+        &mut self,
+        layouter: impl Layouter<F>,
+        value: AssignedCell<F, F>,
+    ) -> Result<(), Error> {
+        self.buffer.push(value);
+        if self.buffer.len() == RATE {
+            self.state = self
+                .chip
+                .absorb_and_permute(layouter, &self.state, &self.buffer)?;
+            self.buffer.clear();
+        }
+        Ok(())
+    }
+
+    /// Permutes whatever is left in the buffer (padded with zeroes) and returns the
+    /// first word of the state.
+    pub fn squeeze(mut self, layouter: impl Layouter<F>) -> Result<AssignedCell<F, F>, Error> {
+        if !self.buffer.is_empty() {
+            self.state = self
+                .chip
+                .absorb_and_permute(layouter, &self.state, &self.buffer)?;
+        }
+        let [output, _, _] = self.state;
+        Ok(output)
+    }
+}
+
+/// Proves knowledge of a message of `L` elements that hashes to the public digest.
+#[derive(Default)]
+pub struct MyCircuit<F: FieldExt, const L: usize> {
+    pub message: Vec<Value<F>>,
+}
+
+#[derive(Debug, Clone)]
+pub struct MyConfig<F: FieldExt> {
+    poseidon: PoseidonConfig<F>,
+    digest: Column<Instance>,
+}
+
+impl<F: FieldExt, const L: usize> Circuit<F> for MyCircuit<F, L> {
+    type Config = MyConfig<F>;
+    type FloorPlanner = V1;
+
+    fn without_witnesses(&self) -> Self {
+        Self {
+            message: vec![Value::unknown(); L],
+        }
+    }
+
+    fn configure(meta: &mut ConstraintSystem<F>) -> Self::Config {
+        let state = [(); WIDTH].map(|_| meta.advice_column());
+        let digest = meta.instance_column();
+        meta.enable_equality(digest);
+
+        MyConfig {
+            poseidon: PoseidonChip::configure(meta, state),
+            digest,
+        }
+    }
+
+    fn synthesize(
+        &self,
+        config: Self::Config,
+        mut layouter: impl Layouter<F>,
+    ) -> Result<(), Error> {
+        let chip = PoseidonChip::construct(config.poseidon.clone());
+
+        let message = layouter.assign_region(
+            || "message",
+            |mut region| {
+                (0..L)
+                    .map(|i| {
+                        let value = self.message.get(i).copied().unwrap_or_else(Value::unknown);
+                        region.assign_advice(
+                            || format!("m_{}", i),
+                            config.poseidon.state[i % WIDTH],
+                            i / WIDTH,
+                            || value,
+                        )
+                    })
+                    .collect::<Result<Vec<_>, _>>()
+            },
+        )?;
+
+        let digest = chip.hash(layouter.namespace(|| "hash"), &message)?;
+        layouter.constrain_instance(digest.cell(), config.digest, 0)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use halo2_proofs::pasta::Fp;
+
+    use super::*;
+    use crate::dev_tools::{
+        failure::{assert_satisfied, verify},
+        min_k::min_k,
+        stats::CircuitStats,
+    };
+
+    fn circuit<const L: usize>(message: &[Fp]) -> MyCircuit<Fp, L> {
+        MyCircuit {
+            message: message.iter().map(|m| Value::known(*m)).collect(),
+        }
+    }
+
+    #[test]
+    fn test_hash_matches_native() {
+        let spec = Spec::<Fp>::default();
+
+        let message = [Fp::one(), Fp::from(2)];
+        let circuit = circuit::<2>(&message);
+        let digest = native::hash(&spec, &message);
+        assert_eq!(
+            format!("{:?}", digest),
+            "0x3555a5ecb43c9998030ad4b06e7982eb3b4600ce9023c6838975dc0794bde34c"
+        );
+
+        let k = min_k(&circuit);
+        assert_satisfied(k, &circuit, vec![vec![digest]]);
+    }
+
+    #[test]
+    fn test_hash_with_padding() {
+        let spec = Spec::<Fp>::default();
+
+        // 5 elements: two full absorptions and one padded with a zero
+        let message: Vec<_> = (0..5).map(|i| Fp::from(i * 1000 + 7)).collect();
+        let circuit = circuit::<5>(&message);
+        let digest = native::hash(&spec, &message);
+
+        let k = min_k(&circuit);
+        assert_satisfied(k, &circuit, vec![vec![digest]]);
+    }
+
+    #[test]
+    fn test_wrong_digest() {
+        let spec = Spec::<Fp>::default();
+
+        let message = [Fp::from(6), Fp::from(42)];
+        let circuit = circuit::<2>(&message);
+        let digest = native::hash(&spec, &message);
+
+        let k = min_k(&circuit);
+        let report = verify(k, &circuit, vec![vec![digest + Fp::one()]]).unwrap_err();
+        assert_eq!(report.groups.len(), 1);
+        assert_eq!(report.groups[0].title, "Permutation");
+    }
+
+    #[test]
+    fn test_poseidon_stats() {
+        let stats = CircuitStats::measure(&MyCircuit::<Fp, 2>::default()).unwrap();
+        assert_eq!(stats.gates.len(), 3);
+        // (s + rc)^5 times the selector
+        assert_eq!(stats.max_degree, 6);
+        // absorbing takes 2 rows, the 64 rounds 65 more
+        assert!(stats.size.rows >= 67);
+    }
+
+    #[cfg(feature = "dev-graph")]
+    #[test]
+    fn print_poseidon() {
+        use crate::dev_tools::graph::render_all;
+
+        let circuit = MyCircuit::<Fp, 2>::default();
+        render_all("poseidon", min_k(&circuit), &circuit);
+    }
+}
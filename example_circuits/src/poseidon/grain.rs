@@ -0,0 +1,107 @@
+use std::marker::PhantomData;
+
+use halo2_proofs::arithmetic::FieldExt;
+
+const STATE: usize = 80;
+
+/// The Grain LFSR the Poseidon paper uses to derive round constants and the MDS matrix,
+/// in self-shrinking mode.
+///
+/// Bits come out in the order of the reference implementation, so the constants match
+/// the ones in `halo2_gadgets` and the Poseidon reference scripts.
+pub(super) struct Grain<F: FieldExt> {
+    state: [bool; STATE],
+    next_bit: usize,
+    _marker: PhantomData<F>,
+}
+
+impl<F: FieldExt> Grain<F> {
+    /// Grain seeded for an `x^5` S-box over a prime field with width `t`.
+    pub(super) fn new(t: u16, r_f: u16, r_p: u16) -> Self {
+        let mut state = [true; STATE];
+        // the parameters are written MSB first; bits 50..80 stay set
+        let mut set_bits = |offset: usize, len: usize, value: u16| {
+            for i in 0..len {
+                state[offset + len - 1 - i] = (value >> i) & 1 != 0;
+            }
+        };
+        set_bits(0, 2, 1); // prime field
+        set_bits(2, 4, 0); // x^alpha S-box
+        set_bits(6, 12, F::NUM_BITS as u16);
+        set_bits(18, 12, t);
+        set_bits(30, 10, r_f);
+        set_bits(40, 10, r_p);
+
+        let mut grain = Grain {
+            state,
+            next_bit: STATE,
+            _marker: PhantomData,
+        };
+        // discard the first 160 bits
+        for _ in 0..20 {
+            grain.load_next_8_bits();
+            grain.next_bit = STATE;
+        }
+        grain
+    }
+
+    fn load_next_8_bits(&mut self) {
+        let mut new_bits = [false; 8];
+        for (i, bit) in new_bits.iter_mut().enumerate() {
+            *bit = self.state[i + 62]
+                ^ self.state[i + 51]
+                ^ self.state[i + 38]
+                ^ self.state[i + 23]
+                ^ self.state[i + 13]
+                ^ self.state[i];
+        }
+        self.state.rotate_left(8);
+        self.next_bit -= 8;
+        self.state[self.next_bit..self.next_bit + 8].copy_from_slice(&new_bits);
+    }
+
+    fn raw_bit(&mut self) -> bool {
+        if self.next_bit == STATE {
+            self.load_next_8_bits();
+        }
+        let bit = self.state[self.next_bit];
+        self.next_bit += 1;
+        bit
+    }
+
+    /// Self-shrinking: draw pairs of bits and keep the second one when the first is set.
+    fn next_bit(&mut self) -> bool {
+        while !self.raw_bit() {
+            self.raw_bit();
+        }
+        self.raw_bit()
+    }
+
+    /// The next `F::NUM_BITS` bits, most significant first, as little-endian bytes.
+    fn next_bytes(&mut self, bytes: &mut [u8]) {
+        let num_bits = F::NUM_BITS as usize;
+        for i in (0..num_bits).rev() {
+            if self.next_bit() {
+                bytes[i / 8] |= 1 << (i % 8);
+            }
+        }
+    }
+
+    /// The next field element, sampling again when the bits are not canonical.
+    pub(super) fn next_field_element(&mut self) -> F {
+        loop {
+            let mut repr = F::Repr::default();
+            self.next_bytes(repr.as_mut());
+            if let Some(f) = Option::from(F::from_repr(repr)) {
+                break f;
+            }
+        }
+    }
+
+    /// The next field element, reducing the bits modulo `p`.
+    pub(super) fn next_field_element_without_rejection(&mut self) -> F {
+        let mut bytes = [0u8; 64];
+        self.next_bytes(&mut bytes);
+        F::from_bytes_wide(&bytes)
+    }
+}
@@ -0,0 +1,197 @@
+use halo2_proofs::arithmetic::FieldExt;
+
+use super::grain::Grain;
+
+/// Width of the permutation.
+pub const WIDTH: usize = 3;
+/// Number of field elements absorbed per permutation.
+pub const RATE: usize = 2;
+
+/// Poseidon parameters for a width-3 permutation with the `x^5` S-box.
+#[derive(Debug, Clone)]
+pub struct Spec<F: FieldExt> {
+    /// Full rounds, half of them before the partial rounds and half after.
+    pub full_rounds: usize,
+    pub partial_rounds: usize,
+    /// One row of constants per round.
+    pub round_constants: Vec<[F; WIDTH]>,
+    pub mds: [[F; WIDTH]; WIDTH],
+}
+
+impl<F: FieldExt> Spec<F> {
+    /// Derives the round constants and the MDS matrix from the Grain LFSR, the way the
+    /// Poseidon reference implementation does.
+    pub fn new(full_rounds: usize, partial_rounds: usize) -> Self {
+        let mut grain = Grain::new(WIDTH as u16, full_rounds as u16, partial_rounds as u16);
+
+        let round_constants = (0..full_rounds + partial_rounds)
+            .map(|_| [(); WIDTH].map(|_| grain.next_field_element()))
+            .collect();
+
+        // a Cauchy matrix 1 / (x_i + y_j) over 2 * WIDTH distinct elements
+        let (xs, ys) = loop {
+            let vals: Vec<F> = (0..2 * WIDTH)
+                .map(|_| grain.next_field_element_without_rejection())
+                .collect();
+            let mut unique = vals.clone();
+            unique.sort_unstable();
+            unique.dedup();
+            if unique.len() == vals.len() {
+                break (vals[..WIDTH].to_vec(), vals[WIDTH..].to_vec());
+            }
+        };
+        let mut mds = [[F::zero(); WIDTH]; WIDTH];
+        for (i, row) in mds.iter_mut().enumerate() {
+            for (j, entry) in row.iter_mut().enumerate() {
+                *entry = (xs[i] + ys[j]).invert().unwrap();
+            }
+        }
+
+        Self {
+            full_rounds,
+            partial_rounds,
+            round_constants,
+            mds,
+        }
+    }
+}
+
+impl<F: FieldExt> Default for Spec<F> {
+    /// `P128Pow5T3`: 8 full rounds and 56 partial rounds, 128-bit security over the
+    /// Pasta fields.
+    fn default() -> Self {
+        Self::new(8, 56)
+    }
+}
+
+pub fn sbox<F: FieldExt>(x: F) -> F {
+    x.square().square() * x
+}
+
+fn apply_mds<F: FieldExt>(state: &mut [F; WIDTH], mds: &[[F; WIDTH]; WIDTH]) {
+    let mut next = [F::zero(); WIDTH];
+    for (next, row) in next.iter_mut().zip(mds.iter()) {
+        for (m, word) in row.iter().zip(state.iter()) {
+            *next += *m * word;
+        }
+    }
+    *state = next;
+}
+
+/// One round: add the round constants, apply the S-box to the whole state in a full
+/// round and to the first word only in a partial round, then mix with the MDS matrix.
+pub fn round<F: FieldExt>(state: &mut [F; WIDTH], spec: &Spec<F>, round: usize) {
+    let half_full = spec.full_rounds / 2;
+    let full = round < half_full || round >= half_full + spec.partial_rounds;
+
+    for (word, rc) in state.iter_mut().zip(spec.round_constants[round].iter()) {
+        *word += rc;
+    }
+    if full {
+        for word in state.iter_mut() {
+            *word = sbox(*word);
+        }
+    } else {
+        state[0] = sbox(state[0]);
+    }
+    apply_mds(state, &spec.mds);
+}
+
+pub fn permute<F: FieldExt>(state: &mut [F; WIDTH], spec: &Spec<F>) {
+    for r in 0..spec.full_rounds + spec.partial_rounds {
+        round(state, spec, r);
+    }
+}
+
+/// The capacity element a sponge starts from when it hashes a message of `length`
+/// field elements (`ConstantLength` domain in `halo2_gadgets`).
+pub fn initial_capacity<F: FieldExt>(length: usize) -> F {
+    F::from_u128((length as u128) << 64)
+}
+
+/// Hashes a fixed-length message with the sponge: absorb `RATE` elements at a time
+/// (the last chunk padded with zeroes), permute after each chunk, and output the
+/// first word of the state.
+pub fn hash<F: FieldExt>(spec: &Spec<F>, message: &[F]) -> F {
+    let mut state = [F::zero(), F::zero(), initial_capacity(message.len())];
+    for chunk in message.chunks(RATE) {
+        for (word, value) in state.iter_mut().zip(chunk.iter()) {
+            *word += value;
+        }
+        permute(&mut state, spec);
+    }
+    state[0]
+}
+
+#[cfg(test)]
+mod tests {
+    use halo2_proofs::pasta::Fp;
+
+    use super::*;
+
+    #[test]
+    fn test_constants_match_halo2_gadgets() {
+        // the first round constants and MDS entry of `P128Pow5T3` in halo2_gadgets
+        let spec = Spec::<Fp>::default();
+        assert_eq!(spec.round_constants.len(), 64);
+        assert_eq!(
+            format!("{:?}", spec.round_constants[0][0]),
+            "0x360d7470611e473d353f628f76d110f34e71162f31003b7057538c2596426303"
+        );
+        assert_eq!(
+            format!("{:?}", spec.mds[0][0]),
+            "0x0ab5e5b874a68de7b3d59fbdc8c9ead497d7a0ab23850b56323f2486d7e11b63"
+        );
+    }
+
+    #[test]
+    fn test_permute_vector() {
+        let spec = Spec::<Fp>::default();
+        let mut state = [Fp::zero(), Fp::one(), Fp::from(2)];
+        permute(&mut state, &spec);
+
+        let state: Vec<_> = state.iter().map(|word| format!("{:?}", word)).collect();
+        assert_eq!(
+            state,
+            [
+                "0x2a526acd0b64b45394efb364f966240ff7e69a71d0b642a0aeb1bc024aeca456",
+                "0x13c5d1568b4aa43076ff7dae343d5512dcd42e7fbed9dafe012a3e9628e5b82a",
+                "0x0a49c868c6976544256fcd597984561af7cfdfe1bda42c7b359029a1d34e9ddd",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_hash_is_one_permutation() {
+        // a two-element message fills the rate, so hashing it is a single permutation
+        let spec = Spec::<Fp>::default();
+        let message = [Fp::from(6), Fp::from(42)];
+
+        let mut state = [message[0], message[1], Fp::from_u128(2 << 64)];
+        permute(&mut state, &spec);
+        assert_eq!(hash(&spec, &message), state[0]);
+
+        assert_eq!(
+            format!("{:?}", hash(&spec, &[Fp::one(), Fp::from(2)])),
+            "0x3555a5ecb43c9998030ad4b06e7982eb3b4600ce9023c6838975dc0794bde34c"
+        );
+    }
+
+    #[test]
+    fn test_hash_pads_with_zeroes() {
+        let spec = Spec::<Fp>::default();
+        let message = [Fp::from(1), Fp::from(2), Fp::from(3)];
+
+        let mut state = [Fp::from(1), Fp::from(2), initial_capacity(3)];
+        permute(&mut state, &spec);
+        state[0] += Fp::from(3);
+        permute(&mut state, &spec);
+        assert_eq!(hash(&spec, &message), state[0]);
+
+        // the length is part of the domain, so trailing zeroes change the hash
+        assert_ne!(
+            hash(&spec, &message[..2]),
+            hash(&spec, &[message[0], message[1], Fp::zero()])
+        );
+    }
+}
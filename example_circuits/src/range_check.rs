@@ -1,3 +1,3 @@
-mod ex1;
-mod ex2;
-mod ex3;
\ No newline at end of file
+pub mod ex1;
+pub mod ex2;
+pub mod ex3;
@@ -2,13 +2,13 @@ use std::marker::PhantomData;
 
 use halo2_proofs::{
     arithmetic::FieldExt,
-    circuit::{AssignedCell, Layouter, Value},
-    plonk::{Advice, Assigned, Column, ConstraintSystem, Constraints, Error, Expression, Selector},
+    circuit::{floor_planner::V1, AssignedCell, Layouter, Value},
+    plonk::{Advice, Assigned, Circuit, Column, ConstraintSystem, Constraints, Error, Expression, Selector},
     poly::Rotation,
 };
 
 #[derive(Debug, Clone)]
-struct RangeCheckConfig<F: FieldExt, const RANGE:usize> {
+pub struct RangeCheckConfig<F: FieldExt, const RANGE:usize> {
     value: Column<Advice>,
     q_range_check: Selector,
     _marker: PhantomData<F>,
@@ -76,59 +76,66 @@ impl<F: FieldExt, const RANGE: usize> RangeCheckConfig<F, RANGE> {
 }
 
 
-#[cfg(test)]
-mod tests {
-    use halo2_proofs::{
-        circuit::floor_planner::V1,
-        dev::{FailureLocation, MockProver, VerifyFailure},
-        pasta::Fp,
-        plonk::{Any, Circuit},
-    };
-
-    use super::*;
-
 #[derive(Default)]
-struct MyCircuit <F: FieldExt, const RANGE: usize> {
-    value: Value<Assigned<F>>,
+pub struct MyCircuit <F: FieldExt, const RANGE: usize> {
+    pub value: Value<Assigned<F>>,
 }
 
-    impl<F: FieldExt, const RANGE: usize> Circuit<F> for MyCircuit<F, RANGE> {
-        type Config = RangeCheckConfig<F, RANGE>;
-        type FloorPlanner = V1;
+impl<F: FieldExt, const RANGE: usize> Circuit<F> for MyCircuit<F, RANGE> {
+    type Config = RangeCheckConfig<F, RANGE>;
+    type FloorPlanner = V1;
 
-        fn without_witnesses(&self) -> Self {
-            Self::default()
-        }
+    fn without_witnesses(&self) -> Self {
+        Self::default()
+    }
 
-        fn configure(meta: &mut ConstraintSystem<F>) -> Self::Config {
-            let value = meta.advice_column();
-            RangeCheckConfig::configure(meta, value)
-        }
+    fn configure(meta: &mut ConstraintSystem<F>) -> Self::Config {
+        let value = meta.advice_column();
+        RangeCheckConfig::configure(meta, value)
+    }
 
-        fn synthesize(
-            &self,
-            config: Self::Config,
-            mut layouter: impl Layouter<F>,
-        ) -> Result<(), Error> {
-            // let chip =RangeCheckChip::construct(config);
-            config.assign(layouter.namespace(|| "Assign value"), self.value)?;
+    fn synthesize(
+        &self,
+        config: Self::Config,
+        mut layouter: impl Layouter<F>,
+    ) -> Result<(), Error> {
+        // let chip =RangeCheckChip::construct(config);
+        config.assign(layouter.namespace(|| "Assign value"), self.value)?;
 
-            Ok(())
-        }
+        Ok(())
     }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use halo2_proofs::{
+        dev::{FailureLocation, VerifyFailure},
+        pasta::Fp,
+        plonk::Any,
+    };
+
+    use proptest::prelude::*;
+
+    use super::*;
+    use crate::dev_tools::{
+        failure::{assert_satisfied, verify},
+        min_k::min_k,
+        stats::CircuitStats,
+        strategies::{field_element, to_u128},
+    };
 
     #[test]
     fn test_range_check() {
-        let k = 4;
         const RANGE: usize = 8;
+        let k = min_k(&MyCircuit::<Fp, RANGE>::default());
 
         for i in 0..RANGE {
             let circuit = MyCircuit::<Fp, RANGE> {
                 value: Value::known(Fp::from(i as u64).into()),
             };
 
-            let prover = MockProver::run(k, &circuit, vec![]).unwrap();
-            prover.assert_satisfied();
+            assert_satisfied(k, &circuit, vec![]);
         }
 
         // {
@@ -149,4 +156,73 @@ struct MyCircuit <F: FieldExt, const RANGE: usize> {
         //     );
         // }
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn test_range_check_failure_report() {
+        const RANGE: usize = 8;
+        let k = min_k(&MyCircuit::<Fp, RANGE>::default());
+
+        let circuit = MyCircuit::<Fp, RANGE> {
+            value: Value::known(Fp::from(RANGE as u64).into()),
+        };
+        let report = verify(k, &circuit, vec![]).unwrap_err();
+
+        assert_eq!(report.groups.len(), 1);
+        assert!(report.groups[0].title.contains("Range check"));
+        // 8 * (0 - 8) * (1 - 8) * ... * (7 - 8) = 8 * 8!
+        assert!(report.groups[0]
+            .failures
+            .iter()
+            .any(|line| line.contains("evaluates to 0x4ec00")));
+
+        let text = report.to_string();
+        assert!(text.starts_with("the circuit is not satisfied:"));
+        assert!(text.contains("('Range check'): 1 failure\n"));
+        assert!(text.contains("  - in Region 0 ('Assign value') at offset 0 (row 0)\n"));
+        assert!(text.contains("      evaluates to 0x4ec00\n"));
+        assert!(text.contains("  likely causes:\n    the values assigned here do not satisfy the gate"));
+    }
+
+    #[test]
+    fn test_range_check_stats() {
+        const RANGE: usize = 8;
+        let stats = CircuitStats::measure(&MyCircuit::<Fp, RANGE>::default()).unwrap();
+
+        assert_eq!(stats.advice_columns, 1);
+        assert_eq!(stats.instance_columns, 0);
+        assert_eq!(stats.table_columns, 0);
+        assert_eq!(stats.selectors, 1);
+        assert!(stats.lookups.is_empty());
+        assert!(stats.equality_columns.is_empty());
+        assert_eq!(stats.gates.len(), 1);
+        assert_eq!(stats.gates[0].name, "Range check");
+        assert_eq!(stats.gates[0].constraints, vec!["range_check"]);
+        // v * (0 - v) * ... * (RANGE-1 - v) has degree RANGE + 1, and the selector adds one more
+        assert_eq!(stats.gates[0].degree, RANGE + 2);
+        assert_eq!(stats.max_degree, RANGE + 2);
+    }
+
+    proptest! {
+        // the gate accepts exactly 0..RANGE: anything else, including -1 = p - 1, fails
+        #[test]
+        fn prop_range_check(value in field_element::<Fp>()) {
+            const RANGE: usize = 8;
+            let k = min_k(&MyCircuit::<Fp, RANGE>::default());
+
+            let circuit = MyCircuit::<Fp, RANGE> {
+                value: Value::known(value.into()),
+            };
+            let in_range = to_u128(value).map_or(false, |v| v < RANGE as u128);
+            prop_assert_eq!(verify(k, &circuit, vec![]).is_ok(), in_range);
+        }
+    }
+
+    #[cfg(feature = "dev-graph")]
+    #[test]
+    fn print_range_check_1() {
+        use crate::dev_tools::graph::render_all;
+
+        let circuit = MyCircuit::<Fp, 8>::default();
+        render_all("range-check-1", min_k(&circuit), &circuit);
+    }
+}
@@ -13,8 +13,8 @@ use std::marker::PhantomData;
 
 use halo2_proofs::{
     arithmetic::FieldExt,
-    circuit::{AssignedCell, Layouter, Value},
-    plonk::{Advice, Assigned, Column, ConstraintSystem, Constraints, Error, Expression, Selector},
+    circuit::{floor_planner::V1, AssignedCell, Layouter, Value},
+    plonk::{Advice, Assigned, Circuit, Column, ConstraintSystem, Constraints, Error, Expression, Selector},
     poly::Rotation,
 };
 
@@ -23,7 +23,7 @@ mod table;
 use table::RangeCheckTable;
 
 #[derive(Debug, Clone)]
-struct RangeCheckConfig<F: FieldExt, const RANGE:usize, const LOOKUP_RANGE: usize> {
+pub struct RangeCheckConfig<F: FieldExt, const RANGE:usize, const LOOKUP_RANGE: usize> {
     value: Column<Advice>,
     q_range_check: Selector,
     q_lookup: Selector,
@@ -82,7 +82,7 @@ impl<F: FieldExt, const RANGE: usize, const LOOKUP_RANGE: usize> RangeCheckConfi
             
             //lookup API returns a vedtor
             vec![
-                (q_lookup * value.square(), table.value)
+                (q_lookup * value, table.value)
             ]
         });
 
@@ -98,7 +98,7 @@ impl<F: FieldExt, const RANGE: usize, const LOOKUP_RANGE: usize> RangeCheckConfi
     ) -> Result<(), Error> {
         assert!(range <= LOOKUP_RANGE);
 
-        if (range < RANGE ) {
+        if range <= RANGE {
             layouter.assign_region(|| "Assign value", |mut region| {
                 let offset = 0;
     
@@ -128,67 +128,74 @@ impl<F: FieldExt, const RANGE: usize, const LOOKUP_RANGE: usize> RangeCheckConfi
 }
 
 
-#[cfg(test)]
-mod tests {
-    use halo2_proofs::{
-        circuit::floor_planner::V1,
-        dev::{FailureLocation, MockProver, VerifyFailure},
-        pasta::Fp,
-        plonk::{Any, Circuit},
-    };
-
-    use super::*;
-
 #[derive(Default)]
-struct MyCircuit <F: FieldExt, const RANGE: usize, const LOOKUP_RANGE: usize> {
-    value: Value<Assigned<F>>,
-    large_value: Value<Assigned<F>>,
+pub struct MyCircuit <F: FieldExt, const RANGE: usize, const LOOKUP_RANGE: usize> {
+    pub value: Value<Assigned<F>>,
+    pub large_value: Value<Assigned<F>>,
 }
 
-    impl<F: FieldExt, const RANGE: usize, const LOOKUP_RANGE: usize> Circuit<F> for MyCircuit<F, RANGE, LOOKUP_RANGE> {
-        type Config = RangeCheckConfig<F, RANGE, LOOKUP_RANGE>;
-        type FloorPlanner = V1;
+impl<F: FieldExt, const RANGE: usize, const LOOKUP_RANGE: usize> Circuit<F> for MyCircuit<F, RANGE, LOOKUP_RANGE> {
+    type Config = RangeCheckConfig<F, RANGE, LOOKUP_RANGE>;
+    type FloorPlanner = V1;
 
-        fn without_witnesses(&self) -> Self {
-            Self::default()
-        }
+    fn without_witnesses(&self) -> Self {
+        Self::default()
+    }
 
-        fn configure(meta: &mut ConstraintSystem<F>) -> Self::Config {
-            let value = meta.advice_column();
-            RangeCheckConfig::configure(meta, value)
-        }
+    fn configure(meta: &mut ConstraintSystem<F>) -> Self::Config {
+        let value = meta.advice_column();
+        RangeCheckConfig::configure(meta, value)
+    }
 
-        fn synthesize(
-            &self,
-            config: Self::Config,
-            mut layouter: impl Layouter<F>,
-        ) -> Result<(), Error> {
+    fn synthesize(
+        &self,
+        config: Self::Config,
+        mut layouter: impl Layouter<F>,
+    ) -> Result<(), Error> {
 
-            config.table.load(&mut layouter)?;
-            // let chip =RangeCheckChip::construct(config);
-            config.assign(layouter.namespace(|| "Assign value"), self.value, RANGE)?;
-            config.assign(layouter.namespace(|| "Assign large value"), self.large_value, LOOKUP_RANGE)?;
+        config.table.load(&mut layouter)?;
+        // let chip =RangeCheckChip::construct(config);
+        config.assign(layouter.namespace(|| "Assign value"), self.value, RANGE)?;
+        config.assign(layouter.namespace(|| "Assign large value"), self.large_value, LOOKUP_RANGE)?;
 
-            Ok(())
-        }
+        Ok(())
     }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use halo2_proofs::{
+        dev::{FailureLocation, VerifyFailure},
+        pasta::Fp,
+        plonk::Any,
+    };
+
+    use proptest::prelude::*;
+
+    use super::*;
+    use crate::dev_tools::{
+        failure::{assert_satisfied, verify},
+        min_k::min_k,
+        strategies::{field_element, near_power_of_two, to_u128},
+    };
 
     #[test]
     fn test_range_check() {
-        //k 
-        let k = 9;
         const RANGE: usize = 8;
         const LOOKUP_RANGE: usize = 256;
+        //k is large enough to hold the LOOKUP_RANGE rows of the table
+        let k = min_k(&MyCircuit::<Fp, RANGE, LOOKUP_RANGE>::default());
 
         for i in 0..RANGE {
             let circuit = MyCircuit::<Fp, RANGE, LOOKUP_RANGE> {
                 //value = v in  [v * (v-1) * (v-2) * ... * (v-(RANGE-1)) = 0]
                 value: Value::known(Fp::from(i as u64).into()),
-                large_value: Value::known(Fp::from((i*i) as u64).into()),
+                // spread over the table, from 0 up to LOOKUP_RANGE - 1
+                large_value: Value::known(Fp::from((i * (LOOKUP_RANGE - 1) / (RANGE - 1)) as u64).into()),
             };
 
-            let prover = MockProver::run(k, &circuit, vec![]).unwrap();
-            prover.assert_satisfied();
+            assert_satisfied(k, &circuit, vec![]);
         }
 
         // {
@@ -209,4 +216,67 @@ struct MyCircuit <F: FieldExt, const RANGE: usize, const LOOKUP_RANGE: usize> {
         //     );
         // }
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn test_range_check_lookup_failure_report() {
+        const RANGE: usize = 8;
+        const LOOKUP_RANGE: usize = 256;
+        let k = min_k(&MyCircuit::<Fp, RANGE, LOOKUP_RANGE>::default());
+
+        // 256 is one past the last row of the table
+        let circuit = MyCircuit::<Fp, RANGE, LOOKUP_RANGE> {
+            value: Value::known(Fp::zero().into()),
+            large_value: Value::known(Fp::from(LOOKUP_RANGE as u64).into()),
+        };
+        let report = verify(k, &circuit, vec![]).unwrap_err();
+
+        assert_eq!(report.groups.len(), 1);
+        assert_eq!(report.groups[0].title, "Lookup 0");
+        assert!(report.groups[0]
+            .failures
+            .iter()
+            .any(|line| line.contains("input = (0x100)")));
+
+        let text = report.to_string();
+        assert!(text.contains("\nLookup 0: 1 failure\n"));
+        assert!(text.contains("('Assign value in lookup') at offset 0"));
+        assert!(text.contains("      input = (0x100) is not in the table\n"));
+        assert!(text.contains("  likely causes:\n    the input is not a row of the table"));
+    }
+
+    fn below(value: Fp, range: usize) -> bool {
+        to_u128(value).map_or(false, |v| v < range as u128)
+    }
+
+    proptest! {
+        #![proptest_config(ProptestConfig::with_cases(64))]
+
+        // `value` goes through the gate and `large_value` through the lookup; the
+        // circuit is satisfied exactly when both are in range
+        #[test]
+        fn prop_range_check(
+            value in prop_oneof![field_element::<Fp>(), near_power_of_two::<Fp>(3, 2)],
+            large_value in prop_oneof![field_element::<Fp>(), near_power_of_two::<Fp>(8, 2)],
+        ) {
+            const RANGE: usize = 8;
+            const LOOKUP_RANGE: usize = 256;
+            let k = min_k(&MyCircuit::<Fp, RANGE, LOOKUP_RANGE>::default());
+
+            let circuit = MyCircuit::<Fp, RANGE, LOOKUP_RANGE> {
+                value: Value::known(value.into()),
+                large_value: Value::known(large_value.into()),
+            };
+            let in_range = below(value, RANGE) && below(large_value, LOOKUP_RANGE);
+            prop_assert_eq!(verify(k, &circuit, vec![]).is_ok(), in_range);
+        }
+    }
+
+    #[cfg(feature = "dev-graph")]
+    #[test]
+    fn print_range_check_2() {
+        use crate::dev_tools::graph::render_all;
+
+        let circuit = MyCircuit::<Fp, 8, 256>::default();
+        render_all("range-check-2", min_k(&circuit), &circuit);
+    }
+}
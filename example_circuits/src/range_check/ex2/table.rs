@@ -33,7 +33,7 @@ pub(super) struct RangeCheckTable<F: FieldExt, const RANGE: usize> {
             let mut offset = 0;
             //for some NUM BITS we want to load all the values into the row
             for i in 0..(RANGE) {
-                table.assign_cell(|| "assign cell", self.value, offset, || Value::known(F::from((i*i) as u64)))?;
+                table.assign_cell(|| "assign cell", self.value, offset, || Value::known(F::from(i as u64)))?;
                 offset += 1;
             }
             Ok(())
@@ -1,9 +1,9 @@
 use halo2_proofs::{
     arithmetic::FieldExt,
-    circuit::{AssignedCell, Layouter, Value},
+    circuit::{floor_planner::V1, AssignedCell, Layouter, Value},
     plonk::{
-        Advice, Assigned, Column, ConstraintSystem, Constraints, Error, Expression, Selector,
-        TableColumn,
+        Advice, Assigned, Circuit, Column, ConstraintSystem, Constraints, Error, Expression,
+        Selector, TableColumn,
     },
     poly::Rotation,
 };
@@ -29,13 +29,13 @@ use table3::*;
 
 #[derive(Debug, Clone)]
 /// A range-constrained value in the circuit produced by the RangeCheckConfig.
-struct RangeConstrained<F: FieldExt> {
+pub struct RangeConstrained<F: FieldExt> {
     num_bits: AssignedCell<Assigned<F>, F>,
     assigned_cell: AssignedCell<Assigned<F>, F>,
 }
 
 #[derive(Debug, Clone)]
-struct RangeCheckConfig<F: FieldExt, const NUM_BITS: usize, const RANGE: usize> {
+pub struct RangeCheckConfig<F: FieldExt, const NUM_BITS: usize, const RANGE: usize> {
     q_lookup: Selector,
     num_bits: Column<Advice>,
     value: Column<Advice>,
@@ -115,61 +115,68 @@ impl<F: FieldExt, const NUM_BITS: usize, const RANGE: usize> RangeCheckConfig<F,
     }
 }
 
-#[cfg(test)]
-mod tests {
-    use halo2_proofs::{
-        circuit::floor_planner::V1,
-        dev::{FailureLocation, MockProver, VerifyFailure},
-        pasta::Fp,
-        plonk::{Any, Circuit},
-    };
+#[derive(Default)]
+pub struct MyCircuit<F: FieldExt, const NUM_BITS: usize, const RANGE: usize> {
+    pub num_bits: Value<u8>,
+    pub value: Value<Assigned<F>>,
+}
 
-    use super::*;
+impl<F: FieldExt, const NUM_BITS: usize, const RANGE: usize> Circuit<F>
+    for MyCircuit<F, NUM_BITS, RANGE>
+{
+    type Config = RangeCheckConfig<F, NUM_BITS, RANGE>;
+    type FloorPlanner = V1;
 
-    #[derive(Default)]
-    struct MyCircuit<F: FieldExt, const NUM_BITS: usize, const RANGE: usize> {
-        num_bits: Value<u8>,
-        value: Value<Assigned<F>>,
+    fn without_witnesses(&self) -> Self {
+        Self::default()
     }
 
-    impl<F: FieldExt, const NUM_BITS: usize, const RANGE: usize> Circuit<F>
-        for MyCircuit<F, NUM_BITS, RANGE>
-    {
-        type Config = RangeCheckConfig<F, NUM_BITS, RANGE>;
-        type FloorPlanner = V1;
+    fn configure(meta: &mut ConstraintSystem<F>) -> Self::Config {
+        let num_bits = meta.advice_column();
+        let value = meta.advice_column();
+        RangeCheckConfig::configure(meta, num_bits, value)
+    }
 
-        fn without_witnesses(&self) -> Self {
-            Self::default()
-        }
+    fn synthesize(
+        &self,
+        config: Self::Config,
+        mut layouter: impl Layouter<F>,
+    ) -> Result<(), Error> {
+        config.table.load(&mut layouter)?;
 
-        fn configure(meta: &mut ConstraintSystem<F>) -> Self::Config {
-            let num_bits = meta.advice_column();
-            let value = meta.advice_column();
-            RangeCheckConfig::configure(meta, num_bits, value)
-        }
+        config.assign(
+            layouter.namespace(|| "Assign value"),
+            self.num_bits,
+            self.value,
+        )?;
 
-        fn synthesize(
-            &self,
-            config: Self::Config,
-            mut layouter: impl Layouter<F>,
-        ) -> Result<(), Error> {
-            config.table.load(&mut layouter)?;
+        Ok(())
+    }
+}
 
-            config.assign(
-                layouter.namespace(|| "Assign value"),
-                self.num_bits,
-                self.value,
-            )?;
 
-            Ok(())
-        }
-    }
+#[cfg(test)]
+mod tests {
+    use halo2_proofs::{
+        dev::{FailureLocation, VerifyFailure},
+        pasta::Fp,
+        plonk::Any,
+    };
+
+    use proptest::prelude::*;
+
+    use super::*;
+    use crate::dev_tools::{
+        failure::{assert_satisfied, verify},
+        min_k::min_k,
+        strategies::{field_element, near_power_of_two, to_u128},
+    };
 
     #[test]
     fn test_range_check_3() {
-        let k = 9;
         const NUM_BITS: usize = 8;
         const RANGE: usize = 256; // 8-bit value
+        let k = min_k(&MyCircuit::<Fp, NUM_BITS, RANGE>::default());
 
         // Successful cases
         for num_bits in 1u8..=NUM_BITS.try_into().unwrap() {
@@ -179,29 +186,51 @@ mod tests {
                     value: Value::known(Fp::from(value as u64).into()),
                 };
 
-                let prover = MockProver::run(k, &circuit, vec![]).unwrap();
-                prover.assert_satisfied();
+                assert_satisfied(k, &circuit, vec![]);
             }
         }
     }
 
+    /// The tag of `value` in the table: its bit length, with 0 tagged as a 1-bit value.
+    fn tag(value: u128) -> u8 {
+        (128 - value.leading_zeros()).max(1) as u8
+    }
+
+    proptest! {
+        #![proptest_config(ProptestConfig::with_cases(64))]
+
+        // the lookup accepts exactly the values below RANGE whose bit length is `num_bits`
+        #[test]
+        fn prop_range_check_3(
+            num_bits in 0u8..=10,
+            value in prop_oneof![
+                field_element::<Fp>(),
+                (1u64..=8).prop_flat_map(|bits| near_power_of_two::<Fp>(bits, 1)),
+            ],
+        ) {
+            const NUM_BITS: usize = 8;
+            const RANGE: usize = 256;
+            let k = min_k(&MyCircuit::<Fp, NUM_BITS, RANGE>::default());
+
+            let circuit = MyCircuit::<Fp, NUM_BITS, RANGE> {
+                num_bits: Value::known(num_bits),
+                value: Value::known(value.into()),
+            };
+            let in_range = to_u128(value)
+                .map_or(false, |v| v < RANGE as u128 && tag(v) == num_bits);
+            prop_assert_eq!(verify(k, &circuit, vec![]).is_ok(), in_range);
+        }
+    }
+
     #[cfg(feature = "dev-graph")]
     #[test]
     fn print_range_check_3() {
-        use plotters::prelude::*;
-
-        let root = BitMapBackend::new("range-check-3-layout.png", (1024, 3096)).into_drawing_area();
-        root.fill(&WHITE).unwrap();
-        let root = root
-            .titled("Range Check 3 Layout", ("sans-serif", 60))
-            .unwrap();
+        use crate::dev_tools::graph::render_all;
 
         let circuit = MyCircuit::<Fp, 8, 256> {
             num_bits: Value::unknown(),
             value: Value::unknown(),
         };
-        halo2_proofs::dev::CircuitLayout::default()
-            .render(9, &circuit, &root)
-            .unwrap();
+        render_all("range-check-3", min_k(&circuit), &circuit);
     }
-}
\ No newline at end of file
+}
@@ -0,0 +1,196 @@
+use group::prime::PrimeCurveAffine;
+use halo2_proofs::{
+    circuit::{floor_planner::V1, Layouter, Value},
+    pasta::{pallas, Fp},
+    plonk::{Advice, Circuit, Column, ConstraintSystem, Error, Instance},
+};
+
+use crate::{
+    ecc::{coordinates, EccChip, EccConfig},
+    poseidon::{PoseidonChip, PoseidonConfig},
+};
+
+pub mod native;
+
+use native::Signature;
+
+/// Proves "I hold a valid Schnorr signature from `PK` over `m`", with `PK` and `m`
+/// public and the signature `(R, s)` private:
+///
+///     e = Poseidon(R.x, PK.x, m)          the Poseidon chip
+///     s * G = R + e * PK                  fixed- and variable-base mul, complete add
+///
+/// `e` is computed in the circuit's field, which is the Pallas base field, and used as
+/// a scalar directly: every base field element is below the scalar field modulus, so
+/// the native [`native::to_scalar`] is the same integer. The ECC and Poseidon chips
+/// share their advice columns.
+#[derive(Default)]
+pub struct MyCircuit {
+    pub public_key: Value<pallas::Affine>,
+    pub message: Value<Fp>,
+    pub signature: Value<Signature>,
+}
+
+impl MyCircuit {
+    pub fn new(public_key: pallas::Affine, message: Fp, signature: Signature) -> Self {
+        Self {
+            public_key: Value::known(public_key),
+            message: Value::known(message),
+            signature: Value::known(signature),
+        }
+    }
+
+    /// The public input: `(PK.x, PK.y, m)`.
+    pub fn instance(public_key: pallas::Affine, message: Fp) -> Vec<Vec<Fp>> {
+        let (x, y) = coordinates(public_key);
+        vec![vec![x, y, message]]
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct MyConfig {
+    advice: [Column<Advice>; 4],
+    ecc: EccConfig<pallas::Affine>,
+    poseidon: PoseidonConfig<Fp>,
+    instance: Column<Instance>,
+}
+
+impl Circuit<Fp> for MyCircuit {
+    type Config = MyConfig;
+    type FloorPlanner = V1;
+
+    fn without_witnesses(&self) -> Self {
+        Self::default()
+    }
+
+    fn configure(meta: &mut ConstraintSystem<Fp>) -> Self::Config {
+        let advice = [(); 4].map(|_| meta.advice_column());
+        let instance = meta.instance_column();
+        meta.enable_equality(instance);
+
+        MyConfig {
+            advice,
+            ecc: EccChip::configure(meta, advice),
+            poseidon: PoseidonChip::configure(meta, [advice[0], advice[1], advice[2]]),
+            instance,
+        }
+    }
+
+    fn synthesize(
+        &self,
+        config: Self::Config,
+        mut layouter: impl Layouter<Fp>,
+    ) -> Result<(), Error> {
+        let ecc = EccChip::construct(config.ecc.clone());
+        let poseidon = PoseidonChip::construct(config.poseidon.clone());
+
+        let public_key = ecc.witness_point(layouter.namespace(|| "PK"), self.public_key)?;
+        let r = ecc.witness_point(layouter.namespace(|| "R"), self.signature.map(|sig| sig.r))?;
+        let message = layouter.assign_region(
+            || "message",
+            |mut region| region.assign_advice(|| "m", config.advice[0], 0, || self.message),
+        )?;
+
+        let e = poseidon.hash(
+            layouter.namespace(|| "e"),
+            &[r.x.clone(), public_key.x.clone(), message.clone()],
+        )?;
+
+        let s_g = ecc.mul_fixed(
+            layouter.namespace(|| "s * G"),
+            pallas::Affine::generator(),
+            self.signature.map(|sig| sig.s),
+        )?;
+        let e_pk = ecc.mul(layouter.namespace(|| "e * PK"), &e, &public_key)?;
+        let rhs = ecc.add(layouter.namespace(|| "R + e * PK"), &r, &e_pk)?;
+        ecc.constrain_equal(layouter.namespace(|| "s * G = R + e * PK"), &s_g, &rhs)?;
+
+        layouter.constrain_instance(public_key.x.cell(), config.instance, 0)?;
+        layouter.constrain_instance(public_key.y.cell(), config.instance, 1)?;
+        layouter.constrain_instance(message.cell(), config.instance, 2)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use ff::Field;
+    use group::{Curve, Group};
+    use rand::rngs::OsRng;
+
+    use super::*;
+    use crate::dev_tools::{
+        failure::{assert_satisfied, verify},
+        min_k::min_k,
+    };
+    use native::{keygen, sign};
+
+    fn signed(message: Fp) -> (pallas::Affine, Signature) {
+        let (secret_key, public_key) = keygen(OsRng);
+        (public_key, sign(secret_key, message, OsRng))
+    }
+
+    #[test]
+    fn test_valid_signature() {
+        let message = Fp::from(42);
+        let (public_key, signature) = signed(message);
+
+        let circuit = MyCircuit::new(public_key, message, signature);
+        assert_satisfied(
+            min_k(&circuit),
+            &circuit,
+            MyCircuit::instance(public_key, message),
+        );
+    }
+
+    #[test]
+    fn test_tampered_signature() {
+        let message = Fp::from(42);
+        let (public_key, signature) = signed(message);
+        let k = min_k(&MyCircuit::default());
+        let instance = MyCircuit::instance(public_key, message);
+
+        let tampered_s = Signature {
+            s: signature.s + pallas::Scalar::one(),
+            ..signature
+        };
+        let tampered_r = Signature {
+            r: (signature.r + pallas::Point::generator()).to_affine(),
+            ..signature
+        };
+        for tampered in [tampered_s, tampered_r] {
+            let circuit = MyCircuit::new(public_key, message, tampered);
+            let report = verify(k, &circuit, instance.clone()).unwrap_err();
+            assert_eq!(report.groups[0].title, "Permutation");
+        }
+    }
+
+    #[test]
+    fn test_wrong_message_or_key() {
+        let message = Fp::from(42);
+        let (public_key, signature) = signed(message);
+        let k = min_k(&MyCircuit::default());
+
+        // a valid signature, claimed for another message
+        let circuit = MyCircuit::new(public_key, message, signature);
+        assert!(verify(
+            k,
+            &circuit,
+            MyCircuit::instance(public_key, message + Fp::one())
+        )
+        .is_err());
+
+        // ... or for another key
+        let (other_key, _) = keygen(OsRng);
+        let circuit = MyCircuit::new(other_key, message, signature);
+        assert!(verify(k, &circuit, MyCircuit::instance(other_key, message)).is_err());
+    }
+
+    #[cfg(feature = "dev-graph")]
+    #[test]
+    fn print_schnorr() {
+        use crate::dev_tools::graph::render_all;
+
+        let circuit = MyCircuit::default();
+        render_all("schnorr", min_k(&circuit), &circuit);
+    }
+}
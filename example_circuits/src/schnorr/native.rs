@@ -0,0 +1,81 @@
+use ff::{Field, PrimeField};
+use group::{Curve, Group};
+use halo2_proofs::pasta::{pallas, Fp};
+use rand::RngCore;
+
+use crate::{
+    ecc::coordinates,
+    poseidon::native::{hash, Spec},
+};
+
+/// A Schnorr signature `(R, s)` over Pallas.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Signature {
+    pub r: pallas::Affine,
+    pub s: pallas::Scalar,
+}
+
+/// A secret key and its public key `sk * G`.
+pub fn keygen(rng: impl RngCore) -> (pallas::Scalar, pallas::Affine) {
+    let secret_key = pallas::Scalar::random(rng);
+    (
+        secret_key,
+        (pallas::Point::generator() * secret_key).to_affine(),
+    )
+}
+
+/// `e = Poseidon(R.x, PK.x, m)`. It lives in the base field, where the circuit can
+/// compute it.
+pub fn challenge(r: pallas::Affine, public_key: pallas::Affine, message: Fp) -> Fp {
+    let (r_x, _) = coordinates(r);
+    let (pk_x, _) = coordinates(public_key);
+    hash(&Spec::default(), &[r_x, pk_x, message])
+}
+
+/// `e` as a scalar. Every base field element is below `q`, so this keeps the integer.
+pub fn to_scalar(e: Fp) -> pallas::Scalar {
+    pallas::Scalar::from_repr(e.to_repr()).unwrap()
+}
+
+/// `R = k * G` for a random nonce `k`, and `s = k + e * sk`.
+pub fn sign(secret_key: pallas::Scalar, message: Fp, mut rng: impl RngCore) -> Signature {
+    let public_key = (pallas::Point::generator() * secret_key).to_affine();
+    let nonce = pallas::Scalar::random(&mut rng);
+    let r = (pallas::Point::generator() * nonce).to_affine();
+
+    let e = to_scalar(challenge(r, public_key, message));
+    Signature {
+        r,
+        s: nonce + e * secret_key,
+    }
+}
+
+/// `s * G = R + e * PK`.
+pub fn verify(public_key: pallas::Affine, message: Fp, signature: &Signature) -> bool {
+    let e = to_scalar(challenge(signature.r, public_key, message));
+    pallas::Point::generator() * signature.s == signature.r + public_key * e
+}
+
+#[cfg(test)]
+mod tests {
+    use rand::rngs::OsRng;
+
+    use super::*;
+
+    #[test]
+    fn test_sign_and_verify() {
+        let (secret_key, public_key) = keygen(OsRng);
+        let message = Fp::from(42);
+        let signature = sign(secret_key, message, OsRng);
+        assert!(verify(public_key, message, &signature));
+
+        // another message, another key, or a tampered signature
+        assert!(!verify(public_key, message + Fp::one(), &signature));
+        assert!(!verify(keygen(OsRng).1, message, &signature));
+        let tampered = Signature {
+            s: signature.s + pallas::Scalar::one(),
+            ..signature
+        };
+        assert!(!verify(public_key, message, &tampered));
+    }
+}
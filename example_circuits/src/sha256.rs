@@ -0,0 +1,867 @@
+use halo2_proofs::{
+    arithmetic::FieldExt,
+    circuit::{floor_planner::V1, AssignedCell, Layouter, Value},
+    plonk::{Advice, Circuit, Column, ConstraintSystem, Error, Fixed, Instance, Selector},
+    poly::Rotation,
+};
+
+pub mod native;
+mod table;
+
+use native::{even_bits, odd_bits, spread, IV, K};
+use table::{SpreadTable, MAX_CHUNK_BITS};
+
+/// Where the rotations of Σ0 cut a word.
+const BIG_SIGMA_0: &[usize] = &[2, 13, 22];
+/// Where the rotations of Σ1 cut a word.
+const BIG_SIGMA_1: &[usize] = &[6, 11, 25];
+/// Where the rotations and shifts of σ0 and σ1 cut a message schedule word.
+const SMALL_SIGMAS: &[usize] = &[3, 7, 10, 17, 18, 19];
+/// Plain byte boundaries, for the halves of a spread sum.
+const BYTES: &[usize] = &[8, 16, 24];
+
+/// `0x5555...`: the spread form of `0xffffffff`.
+const SPREAD_ONES: u64 = 0x5555_5555_5555_5555;
+
+/// The SHA-256 compression function, built from two primitives:
+///
+/// 1. chunk lookups: a 32-bit word is split into chunks of at most 8 bits, and each
+///    `(num_bits, dense, spread)` is looked up in a [`SpreadTable`]. This is the tagged
+///    range check of `range_check::ex3` with the spread form of the chunk added.
+///
+///        | dense | spread | tag (fixed) | q_lookup |
+///        -------------------------------------------
+///        |  c_0  | s(c_0) |    len_0    |    1     |
+///        |  c_1  | s(c_1) |    len_1    |    1     |
+///
+/// 2. linear combinations `sum_i coeff_i * term_i`, with a running sum like the one in
+///    `decompose_range_check`:
+///
+///        | term | acc | coeff (fixed) | q_first | q_sum |
+///        -------------------------------------------------
+///        | t_0  | a_0 |      k_0      |    1    |   0   |   a_0 = k_0 * t_0
+///        | t_1  | a_1 |      k_1      |    0    |   1   |   a_1 = a_0 + k_1 * t_1
+///
+/// Recomposing the chunks with `2^offset` checks the word is 32 bits; recomposing their
+/// spreads with `4^offset` gives the spread word. Because the chunks are cut where the
+/// rotations are, a rotated spread word is the same chunks with shifted coefficients,
+/// and the sum of three of them holds `x ^ y ^ z` in its even bits and `maj(x, y, z)` in
+/// its odd bits. Splitting a sum back into its even and odd halves takes two more chunk
+/// decompositions. Additions mod `2^32` witness the carry and look it up as a 3-bit chunk.
+#[derive(Debug, Clone)]
+pub struct Sha256Config<F: FieldExt> {
+    dense: Column<Advice>,
+    spread: Column<Advice>,
+    tag: Column<Fixed>,
+    q_lookup: Selector,
+    term: Column<Advice>,
+    acc: Column<Advice>,
+    coeff: Column<Fixed>,
+    q_first: Selector,
+    q_sum: Selector,
+    table: SpreadTable<F>,
+}
+
+/// A 32-bit word in the circuit.
+#[derive(Debug, Clone)]
+pub struct Word<F: FieldExt> {
+    pub cell: AssignedCell<F, F>,
+    pub value: Value<u32>,
+}
+
+/// A chunk of a word: `len` bits starting at `offset`, and their spread form.
+#[derive(Debug, Clone)]
+struct Chunk<F: FieldExt> {
+    offset: usize,
+    len: usize,
+    value: Value<u32>,
+    dense: AssignedCell<F, F>,
+    spread: AssignedCell<F, F>,
+}
+
+/// A word that has been split into chunks, along with its spread form.
+#[derive(Debug, Clone)]
+pub struct SpreadWord<F: FieldExt> {
+    pub word: Word<F>,
+    chunks: Vec<Chunk<F>>,
+    spread: AssignedCell<F, F>,
+    spread_value: Value<u64>,
+}
+
+/// A term of a linear combination.
+enum Term<'a, F: FieldExt> {
+    Cell(&'a AssignedCell<F, F>),
+    Constant(F),
+}
+
+enum Shift {
+    Rotr(usize),
+    Shr(usize),
+}
+
+/// `(offset, len)` of the chunks of a word cut at `boundaries`.
+fn chunk_plan(boundaries: &[usize]) -> Vec<(usize, usize)> {
+    let mut cuts = vec![0];
+    cuts.extend_from_slice(boundaries);
+    cuts.push(32);
+
+    let mut plan = vec![];
+    for pair in cuts.windows(2) {
+        let mut offset = pair[0];
+        while offset < pair[1] {
+            let len = (pair[1] - offset).min(MAX_CHUNK_BITS);
+            plan.push((offset, len));
+            offset += len;
+        }
+    }
+    plan
+}
+
+fn pow2<F: FieldExt>(bits: usize) -> F {
+    F::from(2u64).pow_vartime([bits as u64])
+}
+
+impl<F: FieldExt> Sha256Config<F> {
+    pub fn configure(meta: &mut ConstraintSystem<F>) -> Self {
+        let dense = meta.advice_column();
+        let spread = meta.advice_column();
+        let term = meta.advice_column();
+        let acc = meta.advice_column();
+        for column in [dense, spread, term, acc] {
+            meta.enable_equality(column);
+        }
+        let tag = meta.fixed_column();
+        let coeff = meta.fixed_column();
+        // the round constants and IV go into the linear combinations as constants
+        let constant = meta.fixed_column();
+        meta.enable_constant(constant);
+
+        let q_lookup = meta.complex_selector();
+        let q_first = meta.selector();
+        let q_sum = meta.selector();
+        let table = SpreadTable::configure(meta);
+
+        meta.lookup(|meta| {
+            let q_lookup = meta.query_selector(q_lookup);
+            let tag = meta.query_fixed(tag, Rotation::cur());
+            let dense = meta.query_advice(dense, Rotation::cur());
+            let spread = meta.query_advice(spread, Rotation::cur());
+
+            // (0, 0, 0) when the selector is off, which is the first row of the table
+            vec![
+                (q_lookup.clone() * tag, table.tag),
+                (q_lookup.clone() * dense, table.dense),
+                (q_lookup * spread, table.spread),
+            ]
+        });
+
+        // two gates rather than one, so that the first row never queries the row above it
+        meta.create_gate("first term", |meta| {
+            let q_first = meta.query_selector(q_first);
+            let term = meta.query_advice(term, Rotation::cur());
+            let coeff = meta.query_fixed(coeff, Rotation::cur());
+            let acc = meta.query_advice(acc, Rotation::cur());
+
+            vec![q_first * (acc - coeff * term)]
+        });
+
+        meta.create_gate("running sum", |meta| {
+            let q_sum = meta.query_selector(q_sum);
+            let term = meta.query_advice(term, Rotation::cur());
+            let coeff = meta.query_fixed(coeff, Rotation::cur());
+            let acc = meta.query_advice(acc, Rotation::cur());
+            let acc_prev = meta.query_advice(acc, Rotation::prev());
+
+            vec![q_sum * (acc - (acc_prev + coeff * term))]
+        });
+
+        Self {
+            dense,
+            spread,
+            tag,
+            q_lookup,
+            term,
+            acc,
+            coeff,
+            q_first,
+            q_sum,
+            table,
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Sha256Chip<F: FieldExt> {
+    config: Sha256Config<F>,
+}
+
+impl<F: FieldExt> Sha256Chip<F> {
+    pub fn construct(config: Sha256Config<F>) -> Self {
+        Self { config }
+    }
+
+    pub fn load_table(&self, layouter: &mut impl Layouter<F>) -> Result<(), Error> {
+        self.config.table.load(layouter)
+    }
+
+    /// `sum_i coeff_i * term_i`.
+    fn sum(
+        &self,
+        layouter: &mut impl Layouter<F>,
+        terms: &[(Term<'_, F>, F)],
+    ) -> Result<AssignedCell<F, F>, Error> {
+        let config = &self.config;
+
+        layouter.assign_region(
+            || "sum",
+            |mut region| {
+                let mut acc: Option<AssignedCell<F, F>> = None;
+                for (row, (term, coeff)) in terms.iter().enumerate() {
+                    let term = match term {
+                        Term::Cell(cell) => {
+                            cell.copy_advice(|| "term", &mut region, config.term, row)?
+                        }
+                        Term::Constant(c) => region.assign_advice_from_constant(
+                            || "constant",
+                            config.term,
+                            row,
+                            *c,
+                        )?,
+                    };
+                    region.assign_fixed(|| "coeff", config.coeff, row, || Value::known(*coeff))?;
+
+                    let weighted = term.value().map(|t| *t * coeff);
+                    let value = match &acc {
+                        None => {
+                            config.q_first.enable(&mut region, row)?;
+                            weighted
+                        }
+                        Some(acc) => {
+                            config.q_sum.enable(&mut region, row)?;
+                            acc.value().copied() + weighted
+                        }
+                    };
+                    acc = Some(region.assign_advice(|| "acc", config.acc, row, || value)?);
+                }
+                Ok(acc.expect("a linear combination needs at least one term"))
+            },
+        )
+    }
+
+    fn constrain_equal(
+        &self,
+        layouter: &mut impl Layouter<F>,
+        a: &AssignedCell<F, F>,
+        b: &AssignedCell<F, F>,
+    ) -> Result<(), Error> {
+        layouter.assign_region(
+            || "constrain equal",
+            |mut region| region.constrain_equal(a.cell(), b.cell()),
+        )
+    }
+
+    /// Witnesses the chunks `(offset, len)` of `value` and looks each one up.
+    fn lookup_chunks(
+        &self,
+        layouter: &mut impl Layouter<F>,
+        value: Value<u32>,
+        plan: &[(usize, usize)],
+    ) -> Result<Vec<Chunk<F>>, Error> {
+        let config = &self.config;
+
+        layouter.assign_region(
+            || "chunks",
+            |mut region| {
+                let mut chunks = vec![];
+                for (row, &(offset, len)) in plan.iter().enumerate() {
+                    config.q_lookup.enable(&mut region, row)?;
+                    region.assign_fixed(
+                        || "tag",
+                        config.tag,
+                        row,
+                        || Value::known(F::from(len as u64)),
+                    )?;
+
+                    let chunk = value.map(|v| ((v as u64 >> offset) & ((1 << len) - 1)) as u32);
+                    let dense = region.assign_advice(
+                        || "dense",
+                        config.dense,
+                        row,
+                        || chunk.map(|c| F::from(c as u64)),
+                    )?;
+                    let spread = region.assign_advice(
+                        || "spread",
+                        config.spread,
+                        row,
+                        || chunk.map(|c| F::from(spread(c))),
+                    )?;
+                    chunks.push(Chunk {
+                        offset,
+                        len,
+                        value: chunk,
+                        dense,
+                        spread,
+                    });
+                }
+                Ok(chunks)
+            },
+        )
+    }
+
+    /// `sum_i chunk_i * 2^offset_i`.
+    fn compose(
+        &self,
+        layouter: &mut impl Layouter<F>,
+        chunks: &[&Chunk<F>],
+    ) -> Result<Word<F>, Error> {
+        let terms: Vec<_> = chunks
+            .iter()
+            .map(|chunk| (Term::Cell(&chunk.dense), pow2(chunk.offset)))
+            .collect();
+        let cell = self.sum(layouter, &terms)?;
+
+        let value = chunks.iter().fold(Value::known(0u64), |acc, chunk| {
+            acc.zip(chunk.value)
+                .map(|(acc, c)| acc + ((c as u64) << chunk.offset))
+        });
+        Ok(Word {
+            cell,
+            value: value.map(|v| v as u32),
+        })
+    }
+
+    /// Splits `value` into chunks cut at `boundaries` and recomposes the word and its
+    /// spread form. The chunk lookups range-check the word to 32 bits.
+    pub fn decompose(
+        &self,
+        layouter: &mut impl Layouter<F>,
+        value: Value<u32>,
+        boundaries: &[usize],
+    ) -> Result<SpreadWord<F>, Error> {
+        let chunks = self.lookup_chunks(layouter, value, &chunk_plan(boundaries))?;
+
+        let word = self.compose(layouter, &chunks.iter().collect::<Vec<_>>())?;
+        let terms: Vec<_> = chunks
+            .iter()
+            .map(|chunk| (Term::Cell(&chunk.spread), pow2(2 * chunk.offset)))
+            .collect();
+        let spread_cell = self.sum(layouter, &terms)?;
+
+        Ok(SpreadWord {
+            word,
+            chunks,
+            spread: spread_cell,
+            spread_value: value.map(spread),
+        })
+    }
+
+    /// Splits a sum of spread words into its even and odd bits, as byte chunks.
+    fn split(
+        &self,
+        layouter: &mut impl Layouter<F>,
+        sum: &AssignedCell<F, F>,
+        sum_value: Value<u64>,
+    ) -> Result<(Vec<Chunk<F>>, Vec<Chunk<F>>), Error> {
+        let plan = chunk_plan(BYTES);
+        let even = self.lookup_chunks(layouter, sum_value.map(even_bits), &plan)?;
+        let odd = self.lookup_chunks(layouter, sum_value.map(odd_bits), &plan)?;
+
+        // sum = spread(even) + 2 * spread(odd)
+        let terms: Vec<_> = even
+            .iter()
+            .map(|chunk| (Term::Cell(&chunk.spread), pow2(2 * chunk.offset)))
+            .chain(
+                odd.iter()
+                    .map(|chunk| (Term::Cell(&chunk.spread), pow2(2 * chunk.offset + 1))),
+            )
+            .collect();
+        let recomposed = self.sum(layouter, &terms)?;
+        self.constrain_equal(layouter, &recomposed, sum)?;
+
+        Ok((even, odd))
+    }
+
+    /// XOR of the rotations/shifts of `x`: the even bits of the sum of their spread forms.
+    fn sigma(
+        &self,
+        layouter: &mut impl Layouter<F>,
+        x: &SpreadWord<F>,
+        shifts: &[Shift],
+    ) -> Result<Word<F>, Error> {
+        let mut terms = vec![];
+        for shift in shifts {
+            for chunk in x.chunks.iter() {
+                let end = chunk.offset + chunk.len;
+                let position = match *shift {
+                    Shift::Rotr(r) => {
+                        assert!(chunk.offset >= r || end <= r, "chunk straddles a rotation");
+                        (chunk.offset + 32 - r) % 32
+                    }
+                    Shift::Shr(r) => {
+                        assert!(chunk.offset >= r || end <= r, "chunk straddles a shift");
+                        if chunk.offset < r {
+                            // shifted out
+                            continue;
+                        }
+                        chunk.offset - r
+                    }
+                };
+                terms.push((Term::Cell(&chunk.spread), pow2(2 * position)));
+            }
+        }
+        let sum = self.sum(layouter, &terms)?;
+
+        let sum_value = x.word.value.map(|x| {
+            shifts
+                .iter()
+                .map(|shift| match *shift {
+                    Shift::Rotr(r) => spread(x.rotate_right(r as u32)),
+                    Shift::Shr(r) => spread(x >> r),
+                })
+                .sum::<u64>()
+        });
+        let (even, _) = self.split(layouter, &sum, sum_value)?;
+        self.compose(layouter, &even.iter().collect::<Vec<_>>())
+    }
+
+    fn big_sigma_0(
+        &self,
+        layouter: &mut impl Layouter<F>,
+        a: &SpreadWord<F>,
+    ) -> Result<Word<F>, Error> {
+        self.sigma(
+            layouter,
+            a,
+            &[Shift::Rotr(2), Shift::Rotr(13), Shift::Rotr(22)],
+        )
+    }
+
+    fn big_sigma_1(
+        &self,
+        layouter: &mut impl Layouter<F>,
+        e: &SpreadWord<F>,
+    ) -> Result<Word<F>, Error> {
+        self.sigma(
+            layouter,
+            e,
+            &[Shift::Rotr(6), Shift::Rotr(11), Shift::Rotr(25)],
+        )
+    }
+
+    fn small_sigma_0(
+        &self,
+        layouter: &mut impl Layouter<F>,
+        w: &SpreadWord<F>,
+    ) -> Result<Word<F>, Error> {
+        self.sigma(
+            layouter,
+            w,
+            &[Shift::Rotr(7), Shift::Rotr(18), Shift::Shr(3)],
+        )
+    }
+
+    fn small_sigma_1(
+        &self,
+        layouter: &mut impl Layouter<F>,
+        w: &SpreadWord<F>,
+    ) -> Result<Word<F>, Error> {
+        self.sigma(
+            layouter,
+            w,
+            &[Shift::Rotr(17), Shift::Rotr(19), Shift::Shr(10)],
+        )
+    }
+
+    /// `(e & f) ^ (!e & g)`: the odd bits of `spread(e) + spread(f)` and of
+    /// `spread(!e) + spread(g)`, which never overlap.
+    fn ch(
+        &self,
+        layouter: &mut impl Layouter<F>,
+        e: &SpreadWord<F>,
+        f: &SpreadWord<F>,
+        g: &SpreadWord<F>,
+    ) -> Result<Word<F>, Error> {
+        let one = F::one();
+        let e_and_f = self.sum(
+            layouter,
+            &[(Term::Cell(&e.spread), one), (Term::Cell(&f.spread), one)],
+        )?;
+        let e_and_f_value = e.spread_value.zip(f.spread_value).map(|(e, f)| e + f);
+
+        // spread(!e) = spread(0xffffffff) - spread(e)
+        let not_e_and_g = self.sum(
+            layouter,
+            &[
+                (Term::Constant(F::from(SPREAD_ONES)), one),
+                (Term::Cell(&e.spread), -one),
+                (Term::Cell(&g.spread), one),
+            ],
+        )?;
+        let not_e_and_g_value = e
+            .spread_value
+            .zip(g.spread_value)
+            .map(|(e, g)| SPREAD_ONES - e + g);
+
+        let (_, odd_1) = self.split(layouter, &e_and_f, e_and_f_value)?;
+        let (_, odd_2) = self.split(layouter, &not_e_and_g, not_e_and_g_value)?;
+        self.compose(
+            layouter,
+            &odd_1.iter().chain(odd_2.iter()).collect::<Vec<_>>(),
+        )
+    }
+
+    /// `maj(a, b, c)`: the odd bits of `spread(a) + spread(b) + spread(c)`.
+    fn maj(
+        &self,
+        layouter: &mut impl Layouter<F>,
+        a: &SpreadWord<F>,
+        b: &SpreadWord<F>,
+        c: &SpreadWord<F>,
+    ) -> Result<Word<F>, Error> {
+        let one = F::one();
+        let sum = self.sum(
+            layouter,
+            &[
+                (Term::Cell(&a.spread), one),
+                (Term::Cell(&b.spread), one),
+                (Term::Cell(&c.spread), one),
+            ],
+        )?;
+        let sum_value = a
+            .spread_value
+            .zip(b.spread_value)
+            .zip(c.spread_value)
+            .map(|((a, b), c)| a + b + c);
+
+        let (_, odd) = self.split(layouter, &sum, sum_value)?;
+        self.compose(layouter, &odd.iter().collect::<Vec<_>>())
+    }
+
+    /// `(sum_i terms_i + constant) mod 2^32`, split at `boundaries`.
+    fn add(
+        &self,
+        layouter: &mut impl Layouter<F>,
+        terms: &[&Word<F>],
+        constant: u32,
+        boundaries: &[usize],
+    ) -> Result<SpreadWord<F>, Error> {
+        // the carry is looked up as a 3-bit chunk
+        assert!(terms.len() < 8);
+
+        let total = terms
+            .iter()
+            .fold(Value::known(constant as u64), |acc, term| {
+                acc.zip(term.value).map(|(acc, v)| acc + v as u64)
+            });
+        let out = self.decompose(layouter, total.map(|t| t as u32), boundaries)?;
+        let carry = self.lookup_chunks(layouter, total.map(|t| (t >> 32) as u32), &[(0, 3)])?;
+
+        let mut sum: Vec<_> = terms
+            .iter()
+            .map(|term| (Term::Cell(&term.cell), F::one()))
+            .collect();
+        if constant != 0 {
+            sum.push((Term::Constant(F::from(constant as u64)), F::one()));
+        }
+        sum.push((Term::Cell(&carry[0].dense), -pow2::<F>(32)));
+        let check = self.sum(layouter, &sum)?;
+        self.constrain_equal(layouter, &check, &out.word.cell)?;
+
+        Ok(out)
+    }
+
+    /// The initial hash value, with `a..d` split for Σ0 and `e..h` for Σ1.
+    pub fn initial_state(
+        &self,
+        mut layouter: impl Layouter<F>,
+    ) -> Result<Vec<SpreadWord<F>>, Error> {
+        IV.iter()
+            .enumerate()
+            .map(|(i, iv)| {
+                let word = self.decompose(&mut layouter, Value::known(*iv), state_boundaries(i))?;
+                let constant = self.sum(
+                    &mut layouter,
+                    &[(Term::Constant(F::from(*iv as u64)), F::one())],
+                )?;
+                self.constrain_equal(&mut layouter, &word.word.cell, &constant)?;
+                Ok(word)
+            })
+            .collect()
+    }
+
+    /// Witnesses a message block, range-checking every word.
+    pub fn load_block(
+        &self,
+        mut layouter: impl Layouter<F>,
+        block: &[Value<u32>],
+    ) -> Result<Vec<SpreadWord<F>>, Error> {
+        assert_eq!(block.len(), 16);
+        block
+            .iter()
+            .map(|word| self.decompose(&mut layouter, *word, SMALL_SIGMAS))
+            .collect()
+    }
+
+    /// The compression function: expands the schedule, runs 64 rounds and adds the
+    /// result to `state`.
+    pub fn compress(
+        &self,
+        mut layouter: impl Layouter<F>,
+        state: &[SpreadWord<F>],
+        block: Vec<SpreadWord<F>>,
+    ) -> Result<Vec<SpreadWord<F>>, Error> {
+        assert_eq!(state.len(), 8);
+
+        let mut w = block;
+        for t in 16..64 {
+            let layouter = &mut layouter.namespace(|| format!("W_{}", t));
+            let s1 = self.small_sigma_1(layouter, &w[t - 2])?;
+            let s0 = self.small_sigma_0(layouter, &w[t - 15])?;
+            let next = self.add(
+                layouter,
+                &[&s1, &w[t - 7].word, &s0, &w[t - 16].word],
+                0,
+                SMALL_SIGMAS,
+            )?;
+            w.push(next);
+        }
+
+        let [mut a, mut b, mut c, mut d, mut e, mut f, mut g, mut h]: [SpreadWord<F>; 8] =
+            state.to_vec().try_into().unwrap();
+        for t in 0..64 {
+            let layouter = &mut layouter.namespace(|| format!("round {}", t));
+            let s1 = self.big_sigma_1(layouter, &e)?;
+            let ch = self.ch(layouter, &e, &f, &g)?;
+            let s0 = self.big_sigma_0(layouter, &a)?;
+            let maj = self.maj(layouter, &a, &b, &c)?;
+
+            // T1 = h + Σ1(e) + Ch(e, f, g) + K_t + W_t, T2 = Σ0(a) + Maj(a, b, c)
+            // e' = d + T1, a' = T1 + T2
+            let new_e = self.add(
+                layouter,
+                &[&d.word, &h.word, &s1, &ch, &w[t].word],
+                K[t],
+                BIG_SIGMA_1,
+            )?;
+            let new_a = self.add(
+                layouter,
+                &[&h.word, &s1, &ch, &w[t].word, &s0, &maj],
+                K[t],
+                BIG_SIGMA_0,
+            )?;
+
+            h = g;
+            g = f;
+            f = e;
+            e = new_e;
+            d = c;
+            c = b;
+            b = a;
+            a = new_a;
+        }
+
+        let layouter = &mut layouter.namespace(|| "feed forward");
+        [a, b, c, d, e, f, g, h]
+            .iter()
+            .zip(state.iter())
+            .enumerate()
+            .map(|(i, (word, previous))| {
+                self.add(
+                    layouter,
+                    &[&previous.word, &word.word],
+                    0,
+                    state_boundaries(i),
+                )
+            })
+            .collect()
+    }
+
+    /// SHA-256 of the padded `blocks`.
+    pub fn digest(
+        &self,
+        mut layouter: impl Layouter<F>,
+        blocks: &[Vec<Value<u32>>],
+    ) -> Result<Vec<Word<F>>, Error> {
+        let mut state = self.initial_state(layouter.namespace(|| "IV"))?;
+        for (i, block) in blocks.iter().enumerate() {
+            let block = self.load_block(layouter.namespace(|| format!("block {}", i)), block)?;
+            state = self.compress(
+                layouter.namespace(|| format!("compress {}", i)),
+                &state,
+                block,
+            )?;
+        }
+        Ok(state.into_iter().map(|word| word.word).collect())
+    }
+}
+
+/// `a..d` go through Σ0 and `e..h` through Σ1.
+fn state_boundaries(i: usize) -> &'static [usize] {
+    if i < 4 {
+        BIG_SIGMA_0
+    } else {
+        BIG_SIGMA_1
+    }
+}
+
+/// Proves knowledge of a message of `BLOCKS` blocks (after padding) whose SHA-256 digest
+/// is the public input, as eight 32-bit words.
+pub struct MyCircuit<F: FieldExt, const BLOCKS: usize> {
+    pub blocks: Vec<Vec<Value<u32>>>,
+    _marker: std::marker::PhantomData<F>,
+}
+
+impl<F: FieldExt, const BLOCKS: usize> Default for MyCircuit<F, BLOCKS> {
+    fn default() -> Self {
+        Self {
+            blocks: vec![vec![Value::unknown(); 16]; BLOCKS],
+            _marker: std::marker::PhantomData,
+        }
+    }
+}
+
+impl<F: FieldExt, const BLOCKS: usize> MyCircuit<F, BLOCKS> {
+    pub fn new(message: &[u8]) -> Self {
+        let blocks = native::pad(message);
+        assert_eq!(
+            blocks.len(),
+            BLOCKS,
+            "the padded message has {} blocks",
+            blocks.len()
+        );
+        Self {
+            blocks: blocks
+                .iter()
+                .map(|block| block.iter().map(|w| Value::known(*w)).collect())
+                .collect(),
+            _marker: std::marker::PhantomData,
+        }
+    }
+
+    /// The public input: the digest of `message`.
+    pub fn instance(message: &[u8]) -> Vec<Vec<F>> {
+        vec![native::digest(message)
+            .iter()
+            .map(|w| F::from(*w as u64))
+            .collect()]
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct MyConfig<F: FieldExt> {
+    sha256: Sha256Config<F>,
+    digest: Column<Instance>,
+}
+
+impl<F: FieldExt, const BLOCKS: usize> Circuit<F> for MyCircuit<F, BLOCKS> {
+    type Config = MyConfig<F>;
+    type FloorPlanner = V1;
+
+    fn without_witnesses(&self) -> Self {
+        Self::default()
+    }
+
+    fn configure(meta: &mut ConstraintSystem<F>) -> Self::Config {
+        let digest = meta.instance_column();
+        meta.enable_equality(digest);
+
+        MyConfig {
+            sha256: Sha256Config::configure(meta),
+            digest,
+        }
+    }
+
+    fn synthesize(
+        &self,
+        config: Self::Config,
+        mut layouter: impl Layouter<F>,
+    ) -> Result<(), Error> {
+        let chip = Sha256Chip::construct(config.sha256.clone());
+        chip.load_table(&mut layouter)?;
+
+        let digest = chip.digest(layouter.namespace(|| "sha256"), &self.blocks)?;
+        for (i, word) in digest.iter().enumerate() {
+            layouter.constrain_instance(word.cell.cell(), config.digest, i)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use halo2_proofs::pasta::Fp;
+
+    use super::*;
+    use crate::dev_tools::{
+        failure::{assert_satisfied, verify},
+        min_k::min_k,
+    };
+
+    #[test]
+    fn test_chunk_plan() {
+        // every piece ends at a rotation and is at most 8 bits
+        assert_eq!(
+            chunk_plan(BIG_SIGMA_0),
+            vec![(0, 2), (2, 8), (10, 3), (13, 8), (21, 1), (22, 8), (30, 2)]
+        );
+        assert_eq!(
+            chunk_plan(BIG_SIGMA_1),
+            vec![(0, 6), (6, 5), (11, 8), (19, 6), (25, 7)]
+        );
+        for boundaries in [BIG_SIGMA_0, BIG_SIGMA_1, SMALL_SIGMAS, BYTES] {
+            let plan = chunk_plan(boundaries);
+            assert_eq!(plan.iter().map(|(_, len)| len).sum::<usize>(), 32);
+        }
+    }
+
+    /// The instance for a digest written out in hex, as the NIST examples give it.
+    fn nist_digest(hex: &str) -> Vec<Vec<Fp>> {
+        let words = (0..8).map(|i| u32::from_str_radix(&hex[8 * i..8 * (i + 1)], 16).unwrap());
+        vec![words.map(|w| Fp::from(w as u64)).collect()]
+    }
+
+    #[test]
+    fn test_sha256_abc() {
+        let circuit = MyCircuit::<Fp, 1>::new(b"abc");
+        let k = min_k(&circuit);
+        let instance =
+            nist_digest("ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad");
+        assert_eq!(MyCircuit::<Fp, 1>::instance(b"abc"), instance);
+        assert_satisfied(k, &circuit, instance);
+    }
+
+    #[test]
+    fn test_sha256_two_blocks() {
+        // 56 bytes leave no room for the length, so the padding takes a second block and
+        // the state of the first compression feeds the second
+        let message = b"abcdbcdecdefdefgefghfghighijhijkijkljklmklmnlmnomnopnopq";
+        let circuit = MyCircuit::<Fp, 2>::new(message);
+        let k = min_k(&circuit);
+        let instance =
+            nist_digest("248d6a61d20638b8e5c026930c3e6039a33ce45964ff2167f6ecedd419db06c1");
+        assert_eq!(MyCircuit::<Fp, 2>::instance(message), instance);
+        assert_satisfied(k, &circuit, instance);
+    }
+
+    #[test]
+    fn test_sha256_wrong_digest() {
+        let circuit = MyCircuit::<Fp, 1>::new(b"abc");
+        let k = min_k(&circuit);
+
+        // the digest of another message
+        let report = verify(k, &circuit, MyCircuit::<Fp, 1>::instance(b"abd")).unwrap_err();
+        assert!(report
+            .groups
+            .iter()
+            .all(|group| group.title == "Permutation"));
+    }
+
+    #[cfg(feature = "dev-graph")]
+    #[test]
+    fn print_sha256() {
+        use crate::dev_tools::graph::render_all;
+
+        let circuit = MyCircuit::<Fp, 1>::default();
+        render_all("sha256", min_k(&circuit), &circuit);
+    }
+}
@@ -0,0 +1,174 @@
+//! Reference SHA-256 (FIPS 180-4), and the "spread" helpers the chip computes its
+//! witnesses with.
+
+pub const IV: [u32; 8] = [
+    0x6a09e667, 0xbb67ae85, 0x3c6ef372, 0xa54ff53a, 0x510e527f, 0x9b05688c, 0x1f83d9ab, 0x5be0cd19,
+];
+
+pub const K: [u32; 64] = [
+    0x428a2f98, 0x71374491, 0xb5c0fbcf, 0xe9b5dba5, 0x3956c25b, 0x59f111f1, 0x923f82a4, 0xab1c5ed5,
+    0xd807aa98, 0x12835b01, 0x243185be, 0x550c7dc3, 0x72be5d74, 0x80deb1fe, 0x9bdc06a7, 0xc19bf174,
+    0xe49b69c1, 0xefbe4786, 0x0fc19dc6, 0x240ca1cc, 0x2de92c6f, 0x4a7484aa, 0x5cb0a9dc, 0x76f988da,
+    0x983e5152, 0xa831c66d, 0xb00327c8, 0xbf597fc7, 0xc6e00bf3, 0xd5a79147, 0x06ca6351, 0x14292967,
+    0x27b70a85, 0x2e1b2138, 0x4d2c6dfc, 0x53380d13, 0x650a7354, 0x766a0abb, 0x81c2c92e, 0x92722c85,
+    0xa2bfe8a1, 0xa81a664b, 0xc24b8b70, 0xc76c51a3, 0xd192e819, 0xd6990624, 0xf40e3585, 0x106aa070,
+    0x19a4c116, 0x1e376c08, 0x2748774c, 0x34b0bcb5, 0x391c0cb3, 0x4ed8aa4a, 0x5b9cca4f, 0x682e6ff3,
+    0x748f82ee, 0x78a5636f, 0x84c87814, 0x8cc70208, 0x90befffa, 0xa4506ceb, 0xbef9a3f7, 0xc67178f2,
+];
+
+/// A 512-bit message block as sixteen big-endian words.
+pub type Block = [u32; 16];
+
+/// Pads `message` (a 1 bit, zeroes, and the bit length) and splits it into blocks.
+pub fn pad(message: &[u8]) -> Vec<Block> {
+    let mut bytes = message.to_vec();
+    bytes.push(0x80);
+    while bytes.len() % 64 != 56 {
+        bytes.push(0);
+    }
+    bytes.extend_from_slice(&((message.len() as u64) * 8).to_be_bytes());
+
+    bytes
+        .chunks(64)
+        .map(|block| {
+            let mut words = [0u32; 16];
+            for (word, bytes) in words.iter_mut().zip(block.chunks(4)) {
+                *word = u32::from_be_bytes(bytes.try_into().unwrap());
+            }
+            words
+        })
+        .collect()
+}
+
+pub fn small_sigma_0(x: u32) -> u32 {
+    x.rotate_right(7) ^ x.rotate_right(18) ^ (x >> 3)
+}
+
+pub fn small_sigma_1(x: u32) -> u32 {
+    x.rotate_right(17) ^ x.rotate_right(19) ^ (x >> 10)
+}
+
+pub fn big_sigma_0(x: u32) -> u32 {
+    x.rotate_right(2) ^ x.rotate_right(13) ^ x.rotate_right(22)
+}
+
+pub fn big_sigma_1(x: u32) -> u32 {
+    x.rotate_right(6) ^ x.rotate_right(11) ^ x.rotate_right(25)
+}
+
+pub fn ch(e: u32, f: u32, g: u32) -> u32 {
+    (e & f) ^ (!e & g)
+}
+
+pub fn maj(a: u32, b: u32, c: u32) -> u32 {
+    (a & b) ^ (a & c) ^ (b & c)
+}
+
+/// The message schedule `W_0..W_63` of one block.
+pub fn schedule(block: &Block) -> [u32; 64] {
+    let mut w = [0u32; 64];
+    w[..16].copy_from_slice(block);
+    for t in 16..64 {
+        w[t] = small_sigma_1(w[t - 2])
+            .wrapping_add(w[t - 7])
+            .wrapping_add(small_sigma_0(w[t - 15]))
+            .wrapping_add(w[t - 16]);
+    }
+    w
+}
+
+/// The compression function: 64 rounds over `state`, then the feed-forward addition.
+pub fn compress(state: [u32; 8], block: &Block) -> [u32; 8] {
+    let w = schedule(block);
+    let [mut a, mut b, mut c, mut d, mut e, mut f, mut g, mut h] = state;
+    for t in 0..64 {
+        let t1 = h
+            .wrapping_add(big_sigma_1(e))
+            .wrapping_add(ch(e, f, g))
+            .wrapping_add(K[t])
+            .wrapping_add(w[t]);
+        let t2 = big_sigma_0(a).wrapping_add(maj(a, b, c));
+        h = g;
+        g = f;
+        f = e;
+        e = d.wrapping_add(t1);
+        d = c;
+        c = b;
+        b = a;
+        a = t1.wrapping_add(t2);
+    }
+
+    let mut out = state;
+    for (out, word) in out.iter_mut().zip([a, b, c, d, e, f, g, h]) {
+        *out = out.wrapping_add(word);
+    }
+    out
+}
+
+/// SHA-256 of `message`, as eight words.
+pub fn digest(message: &[u8]) -> [u32; 8] {
+    pad(message)
+        .iter()
+        .fold(IV, |state, block| compress(state, block))
+}
+
+/// Interleaves the bits of `x` with zeroes: bit `i` of `x` becomes bit `2i`.
+///
+/// Adding up to three spread words never carries from one 2-bit slot into the next,
+/// so the sum holds XOR in its even bits and majority in its odd bits.
+pub fn spread(x: u32) -> u64 {
+    (0..32).fold(0, |acc, i| acc | (((x as u64 >> i) & 1) << (2 * i)))
+}
+
+/// The even bits of a sum of spread words, compacted back into a word.
+pub fn even_bits(s: u64) -> u32 {
+    (0..32).fold(0, |acc, i| acc | (((s >> (2 * i)) & 1) as u32) << i)
+}
+
+/// The odd bits of a sum of spread words, compacted back into a word.
+pub fn odd_bits(s: u64) -> u32 {
+    even_bits(s >> 1)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn hex(words: [u32; 8]) -> String {
+        words.iter().map(|w| format!("{:08x}", w)).collect()
+    }
+
+    #[test]
+    fn test_nist_vectors() {
+        assert_eq!(
+            hex(digest(b"")),
+            "e3b0c44298fc1c149afbf4c8996fb92427ae41e4649b934ca495991b7852b855"
+        );
+        assert_eq!(
+            hex(digest(b"abc")),
+            "ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad"
+        );
+        assert_eq!(
+            hex(digest(
+                b"abcdbcdecdefdefgefghfghighijhijkijkljklmklmnlmnomnopnopq"
+            )),
+            "248d6a61d20638b8e5c026930c3e6039a33ce45964ff2167f6ecedd419db06c1"
+        );
+    }
+
+    #[test]
+    fn test_spread_sums() {
+        let (a, b, c) = (0xdeadbeef, 0x01234567, 0xf0f0f00f);
+        let s = spread(a) + spread(b) + spread(c);
+        assert_eq!(even_bits(s), a ^ b ^ c);
+        assert_eq!(odd_bits(s), maj(a, b, c));
+
+        // !e spreads to 0x5555... - spread(e), so Ch is the odd bits of two sums
+        let not_e = 0x5555_5555_5555_5555 - spread(a);
+        assert_eq!(not_e, spread(!a));
+        assert_eq!(
+            odd_bits(spread(a) + spread(b)) + odd_bits(not_e + spread(c)),
+            ch(a, b, c)
+        );
+    }
+}
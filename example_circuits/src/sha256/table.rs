@@ -0,0 +1,78 @@
+use std::marker::PhantomData;
+
+use halo2_proofs::{
+    arithmetic::FieldExt,
+    circuit::{Layouter, Value},
+    plonk::{ConstraintSystem, Error, TableColumn},
+};
+
+use super::native::spread;
+
+/// The largest chunk a word is split into.
+pub(super) const MAX_CHUNK_BITS: usize = 8;
+
+/// A lookup table of `(num_bits, dense, spread)`, tagged like `range_check::ex3` so that
+/// looking up `(n, dense, spread)` both range-checks `dense` to exactly `n` bits and
+/// gives its spread form.
+///
+///     | tag | dense | spread |
+///     --------------------------
+///     |  0  |   0   |    0   |  <- looked up when the selector is off
+///     |  1  |   0   |    0   |
+///     |  1  |   1   |    1   |
+///     |  2  |   0   |    0   |
+///     | ... |  ...  |   ...  |
+///     |  8  |  255  | 0x5555 |
+///
+#[derive(Debug, Clone)]
+pub(super) struct SpreadTable<F: FieldExt> {
+    pub(super) tag: TableColumn,
+    pub(super) dense: TableColumn,
+    pub(super) spread: TableColumn,
+    _marker: PhantomData<F>,
+}
+
+impl<F: FieldExt> SpreadTable<F> {
+    pub(super) fn configure(meta: &mut ConstraintSystem<F>) -> Self {
+        Self {
+            tag: meta.lookup_table_column(),
+            dense: meta.lookup_table_column(),
+            spread: meta.lookup_table_column(),
+            _marker: PhantomData,
+        }
+    }
+
+    pub(super) fn load(&self, layouter: &mut impl Layouter<F>) -> Result<(), Error> {
+        layouter.assign_table(
+            || "load spread table",
+            |mut table| {
+                let rows =
+                    std::iter::once((0, 0))
+                        .chain((1..=MAX_CHUNK_BITS).flat_map(|num_bits| {
+                            (0..1u32 << num_bits).map(move |v| (num_bits, v))
+                        }));
+                for (offset, (tag, dense)) in rows.enumerate() {
+                    table.assign_cell(
+                        || "tag",
+                        self.tag,
+                        offset,
+                        || Value::known(F::from(tag as u64)),
+                    )?;
+                    table.assign_cell(
+                        || "dense",
+                        self.dense,
+                        offset,
+                        || Value::known(F::from(dense as u64)),
+                    )?;
+                    table.assign_cell(
+                        || "spread",
+                        self.spread,
+                        offset,
+                        || Value::known(F::from(spread(dense))),
+                    )?;
+                }
+                Ok(())
+            },
+        )
+    }
+}